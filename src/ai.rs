@@ -0,0 +1,207 @@
+use crate::game::Game;
+use crate::map::{Map, get_player};
+use crate::direction::Direction;
+use crate::items::Item;
+use crate::tiles::Tile;
+use crate::modes::Mode;
+use crate::visibility;
+use crate::{Position, FIELDS};
+use std::collections::HashSet;
+use rand::RngCore;
+use rand::seq::SliceRandom;
+
+/// Decides the moves of a bot. Human players move from the frontend.
+/// Bots draw all their random values from `rng`, so games with bots are reproducible.
+pub trait PlayerController {
+    fn next_move(&self, game: &Game, player_code: usize, rng: &mut dyn RngCore) -> Direction;
+}
+
+/// Who controls a player.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayerKind {
+    Human,
+    RandomBot,
+    GreedyBot,
+    HunterBot,
+}
+
+/// Names of the player kinds, as shown in the setup and written in save files.
+pub const PLAYER_KINDS: [(PlayerKind, &str); 4] = [
+    (PlayerKind::Human, "human"),
+    (PlayerKind::RandomBot, "random"),
+    (PlayerKind::GreedyBot, "greedy"),
+    (PlayerKind::HunterBot, "hunter"),
+];
+
+impl PlayerKind {
+    pub fn name(&self) -> &'static str {
+        for (kind, name) in PLAYER_KINDS.iter() {
+            if kind == self {
+                return name;
+            }
+        }
+        return "";
+    }
+
+    pub fn from_name(name: &str) -> Option<PlayerKind> {
+        for (kind, kind_name) in PLAYER_KINDS.iter() {
+            if *kind_name == name {
+                return Some(*kind);
+            }
+        }
+        return None;
+    }
+
+    pub fn is_bot(&self) -> bool {
+        return *self != PlayerKind::Human;
+    }
+
+    /// Returns the controller of a bot, or None for a human player.
+    pub fn controller(&self) -> Option<Box<dyn PlayerController>> {
+        match self {
+            PlayerKind::Human => return None,
+            PlayerKind::RandomBot => return Some(Box::new(RandomWalker)),
+            PlayerKind::GreedyBot => return Some(Box::new(PortalSeeker)),
+            PlayerKind::HunterBot => return Some(Box::new(Hunter)),
+        }
+    }
+}
+
+/// Walks in a random direction without hitting walls.
+/// The bots follow the game mode: they keep away from the portal when leaving would give the game up.
+pub struct RandomWalker;
+
+/// Follows the shortest path to the portal, or to the nearest key first if it can't open the portal.
+/// In a last-standing game it collects the items instead. In a points game it goes for the treasures and the other players
+/// until the portal points are enough to win.
+pub struct PortalSeeker;
+
+/// Chases the nearest player to win battle points. Does what a `PortalSeeker` does when there is nobody to chase.
+pub struct Hunter;
+
+impl PlayerController for RandomWalker {
+    fn next_move(&self, game: &Game, player_code: usize, rng: &mut dyn RngCore) -> Direction {
+        let position = game.get_player_position(player_code).unwrap();
+        let possible: Vec<Direction> = Direction::ALL.iter().cloned()
+            .filter(|direction| game.map().step(position, *direction).map_or(false, |next| !avoids(game, player_code, next))).collect();
+        // A trapped bot tries a blocked direction, the move fails and it skips.
+        let blocked: Vec<Direction> = Direction::ALL.iter().cloned()
+            .filter(|direction| game.map().neighbour(position, *direction).map_or(true, |next| !avoids(game, player_code, next))).collect();
+        match possible.choose(rng).or_else(|| blocked.choose(rng)) {
+            Some(direction) => return *direction,
+            None => return *Direction::ALL.choose(rng).unwrap(),
+        }
+    }
+}
+
+impl PlayerController for PortalSeeker {
+    fn next_move(&self, game: &Game, player_code: usize, rng: &mut dyn RngCore) -> Direction {
+        let portal = if game.can_open_portal(player_code) { FIELDS[2] } else { Item::Key.symbol() };
+        let step = if portal_is_goal(game, player_code) {
+            step_towards(game, player_code, |field| field == portal)
+        } else if game.rules().mode == Mode::Points {
+            step_towards(game, player_code, |field| field == Item::Treasure.symbol())
+                .or_else(|| step_towards(game, player_code, |field| is_opponent(game, player_code, field)))
+        } else {
+            step_towards(game, player_code, |field| Item::from_symbol(field).is_some())
+        };
+        match step {
+            Some(direction) => return direction,
+            None => return RandomWalker.next_move(game, player_code, rng),
+        }
+    }
+}
+
+impl PlayerController for Hunter {
+    fn next_move(&self, game: &Game, player_code: usize, rng: &mut dyn RngCore) -> Direction {
+        match step_towards(game, player_code, |field| is_opponent(game, player_code, field)) {
+            Some(direction) => return direction,
+            None => return PortalSeeker.next_move(game, player_code, rng),
+        }
+    }
+}
+
+/// Checks if walking through the portal helps a player to win: never in a last-standing game
+/// and in a points game only if the portal points are enough.
+fn portal_is_goal(game: &Game, player_code: usize) -> bool {
+    let rules = game.rules();
+    match rules.mode {
+        Mode::LastStanding => return false,
+        Mode::Points => return game.players()[player_code].score() + rules.portal_score >= rules.target_score,
+        Mode::Portal | Mode::ExitAll | Mode::Rounds | Mode::Timed => return true,
+    }
+}
+
+/// Checks if a bot keeps away from a field: the portal, unless it is open and walking through it helps to win,
+/// and a door, unless the bot has a key. Stepping on a locked door or portal would fail every time.
+fn avoids(game: &Game, player_code: usize, field: Position) -> bool {
+    match game.map().tile(field) {
+        Tile::Portal => return !portal_is_goal(game, player_code) || !game.can_open_portal(player_code),
+        Tile::Door => return !game.get_player_items(player_code).contains(&Item::Key),
+        _ => return false,
+    }
+}
+
+/// Returns the fields a bot knows about: the ones it sees and the ones it has explored.
+/// None means the whole map, there is no fog of war.
+fn known_fields(game: &Game, player_code: usize) -> Option<HashSet<Position>> {
+    let radius = game.rules().view_radius;
+    if radius == 0 {
+        return None;
+    }
+    let mut known: HashSet<Position> = game.players()[player_code].explored().into_iter().collect();
+    known.extend(visibility::visible_fields(game.map(), game.get_player_position(player_code).unwrap(), radius));
+    return Some(known);
+}
+
+/// Checks if a field shows a player who isn't the bot or its teammate.
+fn is_opponent(game: &Game, player_code: usize, field: char) -> bool {
+    let code = get_player(field);
+    return code != 0 && code != player_code && !game.teammates(code, player_code);
+}
+
+/// Returns the first step of a player towards the nearest field matching `is_target`,
+/// or None if the bot knows no way there around the fields it avoids.
+fn step_towards<F: Fn(char) -> bool>(game: &Game, player_code: usize, is_target: F) -> Option<Direction> {
+    let position = game.get_player_position(player_code).unwrap();
+    let known = known_fields(game, player_code);
+    let may_enter = |field: Position| known.as_ref().map_or(true, |known| known.contains(&field)) && !avoids(game, player_code, field);
+    return first_step(game.map(), position, may_enter, is_target);
+}
+
+/// Searches for the nearest field matching `is_target` over the fields `may_enter` allows and returns the first step towards it.
+/// Players are passed through - stepping on one is a battle, not a blocked move.
+fn first_step<P: Fn(Position) -> bool, F: Fn(char) -> bool>(map: &Map, start: Position, may_enter: P, is_target: F) -> Option<Direction> {
+    let path = map.find_path_over(start, may_enter, |position| position != start && is_target(map.get_field(position)))?;
+    return path.first().cloned();
+}
+
+
+
+// ----------------------------------------------------------------------------------
+// Private functions test
+
+#[test]
+fn test_first_step() {
+    let map: Map = ". . . .\n. # # .\n. # 0 .\n. . . .\n".parse().ok().unwrap();
+    assert_eq!(first_step(&map, Position(0, 2), |_| true, |field| field == FIELDS[2]), Some(Direction::Right));
+    assert_eq!(first_step(&map, Position(3, 0), |_| true, |field| field == FIELDS[2]), Some(Direction::Right));
+    assert_eq!(first_step(&map, Position(2, 3), |_| true, |field| field == FIELDS[2]), Some(Direction::Left));
+    // Around the fields it may not enter.
+    assert_eq!(first_step(&map, Position(3, 0), |field| field != Position(3, 1), |field| field == FIELDS[2]), Some(Direction::Up));
+}
+
+#[test]
+fn test_first_step_unreachable() {
+    let map: Map = ". # .\n# # .\n. . 0\n".parse().ok().unwrap();
+    assert_eq!(first_step(&map, Position(0, 0), |_| true, |field| field == FIELDS[2]), None);
+}
+
+#[test]
+fn test_player_kind_names() {
+    for (kind, name) in PLAYER_KINDS.iter() {
+        assert_eq!(kind.name(), *name);
+        assert_eq!(PlayerKind::from_name(name), Some(*kind));
+    }
+    assert_eq!(PlayerKind::from_name("robot"), None);
+}
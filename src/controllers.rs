@@ -1,100 +1,329 @@
 use crate::*;
 use game::Game;
-use crate::{project_errors::GameError, Position};
+use crate::map::Map;
+use crate::rules::GameRules;
+use crate::engine::{Action, Event, Phase};
+use crate::input::{KeyBindings, Command, read_command, parse_field, parse_number};
+use crate::direction::{Direction, parse_path};
+use crate::items::Item;
+use crate::modes::Mode;
+use crate::project_errors::GameError;
 use utills::*;
+use termion::color;
+use std::time::Instant;
 
 impl Game {
-    pub fn init() -> Game {
+    /// Shows the menu and sets the game up. If a map is given, the game is played on it.
+    /// The human players move with the given keys.
+    pub fn init(rules: GameRules, map: Option<Map>, key_bindings: KeyBindings) -> Game {
+        let mut rules = rules;
         clear();
-        menu();
+        if let Some(path) = menu(&mut rules, &key_bindings).unwrap_or_else(|e| leave(e)) {
+            clear();
+            match Game::load(&path) {
+                Ok(mut game) => {
+                    game.set_key_bindings(key_bindings);
+                    return game;
+                },
+                Err(e) => {
+                    print_error(e.to_string());
+                    print_message("Starting a new game instead.\n".to_string());
+                    pause();
+                },
+            }
+        }
         clear();
-        let (players, number_of_players, map_size) = ask_info();
-        return Game::new(players, number_of_players, map_size);
+        let players = ask_info(&rules).unwrap_or_else(|e| leave(e));
+        let names: Vec<String> = players.iter().map(|(name, _)| name.clone()).collect();
+        let mut game = match map {
+            Some(map) => Game::with_map(names, rules, map, rand::random::<u64>()),
+            None => Game::with_rules(names, rules, rand::random::<u64>()),
+        };
+        for (i, (_, kind)) in players.iter().enumerate() {
+            game.set_player_kind(i + 1, *kind);
+        }
+        game.set_key_bindings(key_bindings);
+        let teams = game.rules().teams;
+        for player in 1..game.num_of_players {
+            if teams == 0 {
+                break;
+            }
+            loop {
+                let input = ask(format!("Team of {} (1-{}, press Enter for {}): ",
+                    game.get_player_name(player), teams, game.get_player_team(player))).unwrap_or_else(|e| leave(e));
+                if input.trim().is_empty() {
+                    break;
+                }
+                match parse_number(&input, 1, teams) {
+                    Ok(team) => {
+                        game.set_player_team(player, team);
+                        break;
+                    },
+                    Err(e) => print_error(format!("Sorry! {}", e)),
+                }
+            }
+        }
+        if game.hot_seat() {
+            for player in 1..game.num_of_players {
+                if !game.player_kind(player).is_bot() {
+                    print_message(format!("{}, choose a PIN to hide your turns from the others (press Enter for none): ",
+                        game.get_player_name(player)));
+                    flush();
+                    let pin = read_secret().unwrap_or_else(|e| leave(e));
+                    game.set_pin(player, if pin.is_empty() { None } else { Some(pin) });
+                }
+            }
+        }
+        return game;
     }
 
-    pub fn start(&mut self) {
-        for player in 1..self.num_of_players {
-            let mut x: usize = rand::random::<usize>() % self.map_size;
-            let mut y: usize = rand::random::<usize>()% self.map_size;
-            while !self.spawn_player(player, Position(x, y)) {
-                x = rand::random::<usize>() % self.map_size;
-                y = rand::random::<usize>()% self.map_size;
-            }
+    /// Places the players and the portal on the map. Returns an error if they don't fit.
+    pub fn start(&mut self) -> Result<(), GameError> {
+        if self.phase() == Phase::Setup {
+            self.apply(Action::Start)?;
+        }
+        pause();
+        return Ok(());
+    }
+
+    fn save_game(&mut self) {
+        print_message(format!("Enter file name (default {}): ", DEFAULT_SAVE_FILE));
+        flush();
+        let mut path = read_str();
+        if path.is_empty() {
+            path = DEFAULT_SAVE_FILE.to_string();
         }
-        let mut x: usize = rand::random::<usize>() % self.map_size;
-        let mut y: usize = rand::random::<usize>()% self.map_size;
-        while !self.spawn_portal(Position(x, y)) {
-            x = rand::random::<usize>() % self.map_size;
-            y = rand::random::<usize>()% self.map_size;
+        match self.save(&path) {
+            Ok(_) => print_message(format!("Game saved to {}. You can quit now and load it later.\n", path)),
+            Err(e) => print_error(e.to_string()),
         }
         pause();
     }
 
     pub fn print_map(&self, player_code: usize) {
         clear();
-        print_color(self.display_map(), player_code);
+        print_color(self.display_map_for(player_code), player_code);
     }
 
-    fn play_turn(&mut self, player: usize) -> bool {
-        let mut done: bool = false;
-        while !done {
-            use std::error::Error;
-            self.print_map(player);
-            print_message("Enter direction: ".to_string());
+    /// Waits for a human player to press Enter. Bots only give the others time to follow their moves.
+    fn wait(&self, player: usize) {
+        if self.player_kind(player).is_bot() {
+            sleep();
+            sleep();
+            clear();
+        } else {
+            pause();
+        }
+    }
+
+    /// Checks if several people share the terminal, so the board has to be hidden between their turns.
+    fn hot_seat(&self) -> bool {
+        return (1..self.num_of_players).filter(|player| !self.player_kind(*player).is_bot()).count() > 1;
+    }
+
+    /// Hides the board until the player on turn confirms it's them (with their PIN, if they have one)
+    /// and tells them about the battles since their last turn.
+    fn hand_over(&mut self, player: usize) {
+        clear();
+        print_message(format!("-- PLAYER {} --\nPass the game to {}.\n", player, self.get_player_name(player)));
+        if self.has_pin(player) {
+            loop {
+                print_message("Enter your PIN: ".to_string());
+                flush();
+                if self.check_pin(player, &read_secret().unwrap_or_else(|e| leave(e))) {
+                    break;
+                }
+                print_error("Wrong PIN! Try again.\n".to_string());
+            }
+        } else {
+            print!("Press Enter when you are ready...");
             flush();
-            let direction = &read_str();
-            match self.make_move(player, direction) {
-                Ok(result) => if result {
-                    done = true;
+            read_str();
+        }
+        clear();
+        let news: Vec<String> = self.take_news(player).iter()
+            .filter(|event| is_battle(event))
+            .filter_map(|event| self.describe(event)).collect();
+        print_message("Since your last turn:\n".to_string());
+        if news.is_empty() {
+            print!("   Nothing happened.\n");
+        }
+        for text in news {
+            print!("   {}\n", text);
+        }
+        print!("\n");
+    }
+
+    /// Shows the events of a move that the map itself doesn't show.
+    fn show_events(&self, player: usize, events: Vec<Event>) {
+        let battle: Vec<String> = events.iter().filter(|event| is_battle(event) || is_item(event) || is_tile(event))
+            .filter_map(|event| self.describe(event)).collect();
+        if !battle.is_empty() {
+            print_message(battle.join(" "));
+            self.wait(player);
+        }
+        for event in events {
+            if let (Event::Portal { .. }, Some(text)) | (Event::TimeUp, Some(text)) | (Event::GameFinished(_), Some(text))
+                = (&event, self.describe(&event)) {
+                print_message(text);
+                self.wait(player);
+            }
+        }
+    }
+
+    /// Reads the command of a human player, one keypress per step.
+    fn read_move(&self) -> Command {
+        let bindings = self.key_bindings();
+        print_message(format!("Press a direction key ({} uses an item, {} saves the game, {} quits)\n",
+            bindings.describe(Command::Use), bindings.describe(Command::Save), bindings.describe(Command::Quit)));
+        flush();
+        return read_command(bindings);
+    }
+
+    /// Asks for a path (e.g. `ddsaw`) or the field to go to.
+    /// Returns None if the player changes their mind and enters nothing.
+    fn ask_path(&self, player: usize) -> Option<Result<Vec<Direction>, GameError>> {
+        print_message(format!("Enter up to {} steps (e.g. ddsaw) or the field to go to (row,column): ", self.moves_left()));
+        flush();
+        let input = read_str();
+        if input.is_empty() {
+            return None;
+        }
+        match parse_field(&input) {
+            Some(field) => return Some(self.path_to(player, field)),
+            None => return Some(parse_path(&input)),
+        }
+    }
+
+    /// Asks which item of the inventory to use.
+    /// Returns None if the player changes their mind and enters nothing.
+    fn ask_item(&self, player: usize) -> Option<Result<Item, GameError>> {
+        let usable: Vec<&str> = self.get_player_items(player).iter().filter(|item| item.is_usable()).map(|item| item.name()).collect();
+        if usable.is_empty() {
+            return Some(Err(GameError::NoItem));
+        }
+        print_message(format!("Use which item? ({}): ", usable.join(", ")));
+        flush();
+        let input = read_str();
+        if input.is_empty() {
+            return None;
+        }
+        return Some(input.parse::<Item>());
+    }
+
+    /// Tells the game how much time has passed since `clock` was started and starts it again.
+    fn report_time(&mut self, clock: &mut Instant) {
+        self.apply(Action::Time(clock.elapsed())).ok();
+        *clock = Instant::now();
+    }
+
+    /// Plays one move of the player on turn, asking again until the move is valid.
+    /// The time is reported before the move, it may end a timed game.
+    fn play_move(&mut self, player: usize, clock: &mut Instant) {
+        loop {
+            use std::error::Error;
+            let bot = self.player_kind(player).is_bot();
+            // With the fog of war the others must not see what a bot sees.
+            if !bot || self.rules().view_radius == 0 {
+                self.print_map(player);
+            }
+            let command = match self.next_move(player) {
+                Some(direction) => Command::Move(direction),
+                None => self.read_move(),
+            };
+            let action = match command {
+                Command::Move(direction) => Action::Move(direction),
+                Command::Path => match self.ask_path(player) {
+                    Some(Ok(path)) => Action::Path(path),
+                    Some(Err(e)) => {
+                        print_error(e.to_string());
+                        pause();
+                        continue;
+                    },
+                    None => continue,
                 },
-                Err(GameError::AnotherPlayer) => {
-                    let dead = self.battle(player, direction);
-                    print_message(format!("{} was killed! {} wins {} points!",
-                        self.get_player_name(dead), self.get_player_name(player), BATTLE_SCORE));
-                    pause();
-                    done = true;
+                Command::Use => match self.ask_item(player) {
+                    Some(Ok(item)) => Action::Use(item),
+                    Some(Err(e)) => {
+                        print_error(e.to_string());
+                        pause();
+                        continue;
+                    },
+                    None => continue,
                 },
-                Err(GameError::Portal) => {
-                    self.portal(player);
-                    print_message(format!("{}! {} wins {} points!", 
-                        GameError::Portal.description(), self.get_player_name(player), PORTAL_SCORE));
-                    pause();
-                    return false;
+                Command::Save => {
+                    self.save_game();
+                    continue;
+                },
+                Command::Quit => quit(),
+            };
+            if let (true, Action::Move(direction)) = (bot, &action) {
+                print_message(format!("{} moves {}\n", self.get_player_name(player), direction));
+                sleep();
+            }
+            self.report_time(clock);
+            match self.apply(action) {
+                Ok(events) => {
+                    self.show_events(player, events);
+                    return;
                 },
                 Err(e) => {
                     print_error(e.description().to_string());
+                    if bot {
+                        // A trapped bot skips the move instead of trying forever.
+                        sleep();
+                        self.apply(Action::Skip).ok();
+                        return;
+                    }
                     pause();
                 },
             }
             flush();
         }
-        return true;
     }
 
-
-    fn play(&mut self, player: usize) -> bool {
-        let dice = roll_a_dice();
+    /// Plays the turn of the current player. Returns false when the game is over.
+    /// `clock` measures the time played since it was last reported to the game.
+    pub fn next_turn(&mut self, clock: &mut Instant) -> bool {
+        let player = self.turn();
+        if self.hot_seat() && !self.player_kind(player).is_bot() {
+            self.hand_over(player);
+        }
+        if let Phase::Roll(_) = self.phase() {
+            roll_a_dice();
+            self.apply(Action::Roll).ok();
+        }
         print_message(format!("-- PLAYER {} --\n", player));
-        print!("Username: {}\n", self.get_player_name(player));
-        print!("Moves: {}\n", dice);
-        pause();
-        for _ in 0..dice {
-            if self.play_turn(player) == false {
-                return false;
-            }
+        print!("Username: {} ({}{}{})\n", self.get_player_name(player),
+            player_color(player), PLAYERCODES[player], color::Fg(color::Reset));
+        print!("Moves: {}\n", self.moves_left());
+        match self.rules().mode {
+            Mode::Rounds => print!("Round: {} of {}\n", self.round(), self.rules().rounds),
+            Mode::Timed => print!("Round: {}  Time: {} of {} minutes\n", self.round(), self.elapsed().as_secs() / 60, self.rules().time_limit),
+            Mode::Points => print!("Score: {} of {}\n", self.players()[player].score(), self.rules().target_score),
+            _ => (),
         }
-        return true;
-    }
-
-    pub fn next_turn(&mut self) -> bool {
-        for player_code in 1..self.num_of_players {
-            if self.in_game(player_code) {
-                if self.play(player_code) == false {
-                    return false;
-                }
+        if self.rules().teams > 0 {
+            print!("Team: {}\n", self.get_player_team(player));
+        }
+        if self.rules().combat {
+            let (health, attack, defense) = self.get_player_stats(player);
+            print!("Health: {}  Attack: {}  Defense: {}\n", health, attack, defense);
+        }
+        if !self.get_player_items(player).is_empty() {
+            let items: Vec<&str> = self.get_player_items(player).iter().map(|item| item.name()).collect();
+            print!("Items: {}\n", items.join(", "));
+        }
+        self.wait(player);
+        while let Phase::Move { player: current, .. } = self.phase() {
+            if current != player {
+                break;
             }
+            self.play_move(player, clock);
         }
-        return true;
+        // The player has seen their own turn.
+        self.take_news(player);
+        return self.phase() != Phase::Finished;
     }
 
     pub fn end(self) {
@@ -105,5 +334,17 @@ impl Game {
     }
 }
 
+/// Checks if an event is a part of a battle.
+fn is_battle(event: &Event) -> bool {
+    return matches!(event, Event::Battle { .. } | Event::Fight { .. } | Event::Wounded { .. } | Event::Pushed { .. } | Event::Shielded { .. });
+}
 
+/// Checks if an event is about an item.
+fn is_item(event: &Event) -> bool {
+    return matches!(event, Event::ItemPicked { .. } | Event::ItemUsed { .. } | Event::Compass { .. });
+}
 
+/// Checks if an event is caused by a special tile.
+fn is_tile(event: &Event) -> bool {
+    return matches!(event, Event::Teleported { .. } | Event::WallBroken { .. } | Event::Stuck(_));
+}
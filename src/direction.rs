@@ -0,0 +1,107 @@
+use crate::project_errors::GameError;
+use crate::Position;
+use std::fmt;
+use std::str::FromStr;
+
+/// A direction a player can move in. Up is towards the first row of the map, left towards the first column.
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// All the directions, clockwise from up.
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Up => return "up",
+            Direction::Down => return "down",
+            Direction::Left => return "left",
+            Direction::Right => return "right",
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => return Direction::Down,
+            Direction::Down => return Direction::Up,
+            Direction::Left => return Direction::Right,
+            Direction::Right => return Direction::Left,
+        }
+    }
+
+    /// Turns right.
+    pub fn clockwise(&self) -> Direction {
+        match self {
+            Direction::Up => return Direction::Right,
+            Direction::Right => return Direction::Down,
+            Direction::Down => return Direction::Left,
+            Direction::Left => return Direction::Up,
+        }
+    }
+
+    /// Turns left.
+    pub fn counterclockwise(&self) -> Direction {
+        return self.clockwise().opposite();
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Parses a direction entered by a player: its name (`up`), a WASD or vi-style hjkl key (`w`, `k`)
+/// or the escape sequence an arrow key sends to the terminal. Case and surrounding whitespace are ignored.
+impl FromStr for Direction {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Direction, GameError> {
+        match input.trim().to_lowercase().as_str() {
+            "up" | "w" | "k" | "\x1b[a" => return Ok(Direction::Up),
+            "down" | "s" | "j" | "\x1b[b" => return Ok(Direction::Down),
+            "left" | "a" | "h" | "\x1b[d" => return Ok(Direction::Left),
+            "right" | "d" | "l" | "\x1b[c" => return Ok(Direction::Right),
+            _ => return Err(GameError::InvalidDirection),
+        }
+    }
+}
+
+/// Parses a path entered by a player: keys like `ddsaw` or direction names like `right right down`.
+/// Directions may be separated by spaces or commas.
+pub fn parse_path(input: &str) -> Result<Vec<Direction>, GameError> {
+    let mut path: Vec<Direction> = Vec::new();
+    for word in input.split(|c: char| c.is_whitespace() || c == ',').filter(|word| !word.is_empty()) {
+        match word.parse::<Direction>() {
+            Ok(direction) => path.push(direction),
+            Err(_) => {
+                for symbol in word.chars() {
+                    path.push(symbol.to_string().parse::<Direction>()?);
+                }
+            },
+        }
+    }
+    if path.is_empty() {
+        return Err(GameError::InvalidDirection);
+    }
+    return Ok(path);
+}
+
+impl Position {
+    /// Returns the neighbouring position in a direction, or None if it would be above the first row
+    /// or left of the first column. The other edges depend on the map, see `Map::neighbour`.
+    pub fn step(&self, direction: Direction) -> Option<Position> {
+        let Position(x, y) = *self;
+        match direction {
+            Direction::Up => return x.checked_sub(1).map(|x| Position(x, y)),
+            Direction::Down => return Some(Position(x + 1, y)),
+            Direction::Left => return y.checked_sub(1).map(|y| Position(x, y)),
+            Direction::Right => return Some(Position(x, y + 1)),
+        }
+    }
+}
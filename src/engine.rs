@@ -0,0 +1,379 @@
+use crate::game::Game;
+use crate::project_errors::GameError;
+use crate::{Position, POTION_MOVES};
+use crate::direction::Direction;
+use crate::items::Item;
+use crate::tiles::Tile;
+use crate::modes::Mode;
+use std::time::Duration;
+
+/// What the game is waiting for.
+/// The game runs without any terminal input or output: a frontend checks the phase,
+/// gets an `Action` (from a human or `Game::next_move`), applies it and shows the returned `Event`s.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    /// The players and the portal are not on the map yet.
+    Setup,
+    /// The player on turn has to roll the dice.
+    Roll(usize),
+    /// The player on turn has moves left from the dice.
+    Move { player: usize, moves_left: usize },
+    /// The game is over, as the win condition of its mode says.
+    Finished,
+}
+
+/// Something a player does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Places the players and the portal on the map.
+    Start,
+    /// Rolls the dice for the player on turn.
+    Roll,
+    /// Moves the player on turn in a direction.
+    Move(Direction),
+    /// Moves the player on turn along a path, using one move per step.
+    /// The whole path must fit in the moves left and may not cross walls or leave the map.
+    /// It stops early after a battle, a broken wall, a taken teleport pad or at the portal, the player keeps the moves that are left.
+    /// Every step is applied, recorded and returned as a separate move.
+    Path(Vec<Direction>),
+    /// Uses an item from the inventory of the player on turn. Doesn't take a move.
+    Use(Item),
+    /// Gives up one move, e.g. when a bot is trapped.
+    Skip,
+    /// Tells the game how much time has passed since the frontend last told it.
+    /// The game doesn't read the clock itself, so a replay plays it the same way again.
+    Time(Duration),
+}
+
+/// Something that happened in the game.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    GameStarted,
+    TurnStarted(usize),
+    DiceRolled { player: usize, value: usize },
+    Moved { player: usize, to: Position },
+    /// A player was killed in a battle.
+    Battle { winner: usize, loser: usize, points: u32 },
+    /// A battle with the combat rule on, the dice the players rolled. Followed by its result, if it isn't a draw.
+    Fight { attacker: usize, defender: usize, attack_roll: usize, defense_roll: usize },
+    Wounded { player: usize, damage: u32, health: u32 },
+    /// The defender of a battle was pushed back.
+    Pushed { player: usize, to: Position },
+    /// A teleport pad took a player to the other pad of its pair.
+    Teleported { player: usize, to: Position },
+    WallBroken { player: usize, at: Position },
+    /// A player stepped in mud and lost the moves that were left.
+    Stuck(usize),
+    /// A player walked into a teammate and they swapped places.
+    Swapped { player: usize, with: usize },
+    /// A player walked through the portal and left the labyrinth.
+    Portal { player: usize, points: u32 },
+    /// A player picked up an item by walking on its field.
+    ItemPicked { player: usize, item: Item },
+    ItemUsed { player: usize, item: Item },
+    /// A player used the compass. The direction is None if the portal can't be reached.
+    Compass { player: usize, direction: Option<Direction>, steps: usize },
+    /// A player's shield broke instead of the player being killed in a battle.
+    Shielded { player: usize },
+    MoveSkipped(usize),
+    TurnEnded(usize),
+    /// The time limit of a timed game is over, the game ends with the round.
+    TimeUp,
+    /// The game is over. The code of the winner, 0 if nobody won.
+    GameFinished(usize),
+}
+
+/// State machine
+impl Game {
+    /// Returns what the game is waiting for.
+    pub fn phase(&self) -> Phase {
+        if self.is_over() {
+            return Phase::Finished;
+        }
+        if !self.in_progress() {
+            return Phase::Setup;
+        }
+        if self.moves_left() == 0 {
+            return Phase::Roll(self.turn());
+        }
+        return Phase::Move { player: self.turn(), moves_left: self.moves_left() };
+    }
+
+    /// Applies an action of the player on turn and returns what happened.
+    /// Actions that aren't allowed in the current phase and invalid moves return an error
+    /// and don't change the game.
+    pub fn apply(&mut self, action: Action) -> Result<Vec<Event>, GameError> {
+        if let Action::Path(path) = action {
+            return self.follow_path(path);
+        }
+        let player = self.turn();
+        let round = self.round();
+        let mut events = self.apply_action(action.clone())?;
+        self.check_end(round != self.round(), &mut events);
+        self.log(player, &action, &events);
+        return Ok(events);
+    }
+
+    fn apply_action(&mut self, action: Action) -> Result<Vec<Event>, GameError> {
+        let mut events: Vec<Event> = Vec::new();
+        match (self.phase(), action) {
+            (Phase::Setup, Action::Start) => {
+                self.spawn_all()?;
+                events.push(Event::GameStarted);
+                events.push(Event::TurnStarted(self.turn()));
+            },
+            (Phase::Roll(player), Action::Roll) => {
+                let value = self.roll_dice();
+                self.set_moves_left(value);
+                events.push(Event::DiceRolled { player: player, value: value });
+            },
+            (Phase::Move { player, moves_left }, Action::Move(direction)) => {
+                let target = self.map().neighbour(self.get_player_position(player).unwrap(), direction);
+                let tile = target.map(|field| self.map().tile(field));
+                if tile == Some(Tile::BreakableWall) {
+                    self.break_wall(target.unwrap());
+                    events.push(Event::WallBroken { player: player, at: target.unwrap() });
+                    self.use_moves(1, &mut events);
+                    return Ok(events);
+                }
+                let cost = tile.map_or(1, |tile| tile.cost());
+                if cost > moves_left {
+                    return Err(GameError::NotEnoughMoves);
+                }
+                match self.move_player(player, direction) {
+                    Ok(arrived) => {
+                        events.push(Event::Moved { player: player, to: target.unwrap() });
+                        events.extend(arrived);
+                        let cost = self.mud_cost(player, moves_left, cost, &mut events);
+                        self.use_moves(cost, &mut events);
+                        return Ok(events);
+                    },
+                    Err(GameError::AnotherPlayer) if self.teammates(player, self.opponent(player, direction)) => {
+                        if !self.rules().swap_teammates {
+                            return Err(GameError::Teammate);
+                        }
+                        let teammate = self.opponent(player, direction);
+                        let teleported = self.swap_players(player, direction)?;
+                        events.push(Event::Moved { player: player, to: target.unwrap() });
+                        events.push(Event::Swapped { player: player, with: teammate });
+                        events.extend(teleported);
+                        let cost = self.mud_cost(player, moves_left, cost, &mut events);
+                        self.use_moves(cost, &mut events);
+                        return Ok(events);
+                    },
+                    Err(GameError::AnotherPlayer) if self.rules().combat => {
+                        events.extend(self.fight(player, direction)?);
+                        if !self.in_game(player) {
+                            self.pass_turn(&mut events);
+                            return Ok(events);
+                        }
+                    },
+                    Err(GameError::AnotherPlayer) => {
+                        let defender = self.opponent(player, direction);
+                        if self.break_shield(defender) {
+                            events.push(Event::Shielded { player: defender });
+                        } else {
+                            let (loser, arrived) = self.battle(player, direction)?;
+                            events.push(Event::Battle { winner: player, loser: loser, points: self.rules().battle_score });
+                            events.push(Event::Moved { player: player, to: target.unwrap() });
+                            events.extend(arrived);
+                        }
+                    },
+                    Err(GameError::Portal) if !self.can_open_portal(player) => return Err(GameError::PortalLocked),
+                    Err(GameError::Portal) => {
+                        self.portal(player);
+                        events.push(Event::Portal { player: player, points: self.rules().portal_score });
+                        self.check_end(false, &mut events);
+                        if !self.is_over() {
+                            self.pass_turn(&mut events);
+                        }
+                        return Ok(events);
+                    },
+                    Err(e) => return Err(e),
+                }
+                self.use_moves(1, &mut events);
+            },
+            (Phase::Move { player, .. }, Action::Use(item)) => events.push(self.use_item(player, item)?),
+            (Phase::Roll(_), Action::Time(time)) | (Phase::Move { .. }, Action::Time(time)) => self.add_time(time),
+            (Phase::Move { player, .. }, Action::Skip) => {
+                events.push(Event::MoveSkipped(player));
+                self.use_moves(1, &mut events);
+            },
+            _ => return Err(GameError::InvalidAction),
+        }
+        return Ok(events);
+    }
+
+    /// Returns a message about an event for the players, or None if there is nothing to tell.
+    pub fn describe(&self, event: &Event) -> Option<String> {
+        match event {
+            Event::TurnStarted(player) => return Some(format!("It's {}'s turn.", self.get_player_name(*player))),
+            Event::DiceRolled { player, value } => return Some(format!("{} rolls {}.", self.get_player_name(*player), value)),
+            Event::Battle { winner, loser, points } => return Some(format!("{} was killed! {} wins {} points!",
+                self.get_player_name(*loser), self.get_player_name(*winner), points)),
+            Event::Fight { attacker, defender, attack_roll, defense_roll } => return Some(format!("{} attacks {}! {} rolls {}, {} rolls {}.",
+                self.get_player_name(*attacker), self.get_player_name(*defender),
+                self.get_player_name(*attacker), attack_roll, self.get_player_name(*defender), defense_roll)),
+            Event::Wounded { player, damage, health } => return Some(format!("{} loses {} health, {} left.",
+                self.get_player_name(*player), damage, health)),
+            Event::Pushed { player, .. } => return Some(format!("{} is pushed back.", self.get_player_name(*player))),
+            Event::Portal { player, points } if self.rules().mode != Mode::Portal => return Some(format!(
+                "{} walks through the portal and leaves the labyrinth with {} points!", self.get_player_name(*player), points)),
+            Event::Portal { player, points } if self.get_player_team(*player) != 0 => return Some(format!(
+                "Portal has been found! {} wins {} points! Team {} wins the game!", self.get_player_name(*player), points,
+                self.get_player_team(*player))),
+            Event::Portal { player, points } => return Some(format!("Portal has been found! {} wins {} points!",
+                self.get_player_name(*player), points)),
+            Event::ItemPicked { player, item: Item::Treasure } => return Some(format!("{} finds a treasure worth {} points!",
+                self.get_player_name(*player), self.rules().treasure_score)),
+            Event::ItemPicked { player, item } => return Some(format!("{} picks up a {}.", self.get_player_name(*player), item)),
+            Event::ItemUsed { player, item: Item::Potion } => return Some(format!("{} drinks a potion and gets {} more moves.",
+                self.get_player_name(*player), POTION_MOVES)),
+            Event::ItemUsed { player, item } => return Some(format!("{} uses the {}.", self.get_player_name(*player), item)),
+            Event::Compass { player, direction: Some(direction), steps } => return Some(format!(
+                "{}'s compass points {}, the portal is {} steps away.", self.get_player_name(*player), direction, steps)),
+            Event::Compass { player, direction: None, .. } => return Some(format!(
+                "{}'s compass spins around, the portal can't be reached.", self.get_player_name(*player))),
+            Event::Shielded { player } => return Some(format!("{}'s shield breaks and saves them from death!", self.get_player_name(*player))),
+            Event::Teleported { player, .. } => return Some(format!("{} is teleported to the other pad.", self.get_player_name(*player))),
+            Event::WallBroken { player, .. } => return Some(format!("{} breaks the wall down.", self.get_player_name(*player))),
+            Event::Stuck(player) => return Some(format!("{} is stuck in the mud.", self.get_player_name(*player))),
+            Event::Swapped { player, with } => return Some(format!("{} swaps places with {}.",
+                self.get_player_name(*player), self.get_player_name(*with))),
+            Event::TimeUp => return Some("Time is up!".to_string()),
+            // The portal has already told who won.
+            Event::GameFinished(_) if self.rules().mode == Mode::Portal => return None,
+            Event::GameFinished(0) => return Some("The game is over, nobody wins.".to_string()),
+            Event::GameFinished(player) if self.get_player_team(*player) != 0 => return Some(format!(
+                "The game is over! Team {} wins!", self.get_player_team(*player))),
+            Event::GameFinished(player) => return Some(format!("The game is over! {} wins!", self.get_player_name(*player))),
+            _ => return None,
+        }
+    }
+
+    /// Ends the game if the win condition of its mode says it is over, or if nobody is left in the labyrinth.
+    /// `round_ended` tells the win condition whether the last action finished a round.
+    fn check_end(&mut self, round_ended: bool, events: &mut Vec<Event>) {
+        if self.is_over() {
+            return;
+        }
+        let condition = self.win_condition();
+        let left = !self.players_left().is_empty();
+        if left && !condition.is_over(self, round_ended) {
+            return;
+        }
+        if left && self.rules().mode == Mode::Timed {
+            events.push(Event::TimeUp);
+        }
+        let winner = condition.winner(self);
+        self.end_game(winner);
+        events.push(Event::GameFinished(winner.unwrap_or(0)));
+    }
+
+    /// Applies the steps of a path as moves, after checking the whole path.
+    fn follow_path(&mut self, path: Vec<Direction>) -> Result<Vec<Event>, GameError> {
+        let (player, moves_left) = match self.phase() {
+            Phase::Move { player, moves_left } => (player, moves_left),
+            _ => return Err(GameError::InvalidAction),
+        };
+        self.check_path(player, moves_left, &path)?;
+        let mut events: Vec<Event> = Vec::new();
+        for direction in path {
+            let step = match self.apply(Action::Move(direction)) {
+                Ok(step) => step,
+                Err(_) => break,
+            };
+            let battle = step.iter().any(|event| matches!(event, Event::Battle { .. } | Event::Fight { .. } | Event::Shielded { .. }
+                | Event::WallBroken { .. }));
+            // The path goes on from the pair of a teleport pad, it is no use if the pair was taken.
+            let stranded = self.get_player_position(player).map(|position| self.map().tile(position)) == Some(Tile::Teleport)
+                && !step.iter().any(|event| matches!(event, Event::Teleported { .. }));
+            events.extend(step);
+            let on_turn = match self.phase() {
+                Phase::Move { player: current, .. } => current == player,
+                _ => false,
+            };
+            if battle || stranded || !on_turn {
+                break;
+            }
+        }
+        return Ok(events);
+    }
+
+    /// Checks that a path fits in the moves left and stays on the map without crossing walls.
+    /// Water takes two moves, arrows can't be walked against and a breakable wall can only be the last step.
+    fn check_path(&self, player: usize, moves_left: usize, path: &[Direction]) -> Result<(), GameError> {
+        if path.is_empty() {
+            return Err(GameError::InvalidDirection);
+        }
+        if path.len() > moves_left {
+            return Err(GameError::PathTooLong);
+        }
+        let mut position = self.get_player_position(player).unwrap();
+        let mut cost = 0;
+        // A key picked up on the way opens the doors and the portal after it.
+        let mut has_key = self.get_player_items(player).contains(&Item::Key);
+        for (i, direction) in path.iter().enumerate() {
+            position = match self.map().neighbour(position, *direction) {
+                Some(next) => next,
+                None => return Err(GameError::Outside),
+            };
+            let tile = self.map().tile(position);
+            if tile == Tile::Wall || (tile == Tile::BreakableWall && i + 1 < path.len()) {
+                return Err(GameError::InvalidCoordinates);
+            }
+            if !tile.can_enter(*direction) {
+                return Err(GameError::OneWay);
+            }
+            if tile == Tile::Door && !has_key {
+                return Err(GameError::DoorLocked);
+            }
+            if tile == Tile::Portal && !has_key && !self.can_open_portal(player) {
+                return Err(GameError::PortalLocked);
+            }
+            if self.map().item(position) == Some(Item::Key) {
+                has_key = true;
+            }
+            cost += tile.cost();
+            if let Some(pair) = self.map().teleport_pair(position) {
+                position = pair;
+            }
+        }
+        if cost > moves_left {
+            return Err(GameError::PathTooLong);
+        }
+        return Ok(());
+    }
+
+    /// Returns the moves a step of the player on turn costs: all the moves left if it ended in mud.
+    /// Mud only holds the player on turn, not a teammate who swapped places with it.
+    fn mud_cost(&self, player: usize, moves_left: usize, cost: usize, events: &mut Vec<Event>) -> usize {
+        if self.map().tile(self.get_player_position(player).unwrap()) == Tile::Mud && moves_left > cost {
+            events.push(Event::Stuck(player));
+            return moves_left;
+        }
+        return cost;
+    }
+
+    /// Takes moves from the player on turn and passes the turn when there are none left.
+    fn use_moves(&mut self, moves: usize, events: &mut Vec<Event>) {
+        let moves = self.moves_left() - moves;
+        self.set_moves_left(moves);
+        if moves > 0 {
+            return;
+        }
+        self.pass_turn(events);
+    }
+
+    /// Passes the turn to the next player who is alive.
+    fn pass_turn(&mut self, events: &mut Vec<Event>) {
+        events.push(Event::TurnEnded(self.turn()));
+        self.end_turn();
+        // Dead players and the ones who got out are skipped.
+        // A battle only kills one of its players and the last one through the portal ends the game, so this always stops.
+        while !self.in_game(self.turn()) {
+            self.end_turn();
+        }
+        events.push(Event::TurnStarted(self.turn()));
+    }
+}
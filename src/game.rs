@@ -1,33 +1,184 @@
-use crate::player::{Player, change_coordinates};
-use crate::map::{Map, get_player};
-use crate::{project_errors::GameError, Position, BATTLE_SCORE, PORTAL_SCORE};
+use crate::player::{Player, PinHash, change_coordinates};
+use crate::direction::Direction;
+use crate::map::Map;
+use crate::{project_errors::GameError, Position, HIDDEN_FIELD, POTION_MOVES};
+use crate::project_errors::{SaveError, ReplayError};
+use crate::rules::GameRules;
+use crate::generators::{self, MazeGenerator, RecursiveBacktracker};
+use crate::spawns::{self, RandomSpawns};
+use crate::modes::WinCondition;
+use crate::ai::PlayerKind;
+use crate::items::Item;
+use crate::tiles::Tile;
+use crate::save;
+use crate::visibility;
+use crate::replay::Recorder;
+use crate::engine::{Action, Event};
+use crate::input::KeyBindings;
+use crate::random::GameRng;
+use rand::{Rng, SeedableRng};
+use rand::seq::SliceRandom;
+use rand::rngs::StdRng;
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
 
 /// Game class - contains the labyrinth's map and players' information
+/// All the randomness of a game (map, spawns and dice) comes from a single seeded generator,
+/// so two games with the same seed and the same moves are identical.
 pub struct Game{
     map: Map,
     players: Vec<Player>,
+    rules: GameRules,
+    rng: GameRng,
+    seed: u64,
+    turn: usize,
+    moves_left: usize,
+    winner: Option<usize>,
+    over: bool,
+    /// The codes of the players who walked through the portal, in the order they did.
+    exits: Vec<usize>,
+    round: usize,
+    /// Time played, as the frontend reports it with `Action::Time`.
+    played: Duration,
+    recorder: Option<Recorder>,
+    history: Vec<Event>,
+    key_bindings: KeyBindings,
+    /// Dice values to use instead of rolling in the next battles, when a replay plays a recorded game again.
+    recorded_rolls: VecDeque<usize>,
     pub num_of_players: usize,
-    pub map_size: usize
+    pub map_width: usize,
+    pub map_height: usize
 }
 
-/// Constructor 
+/// Constructors
 impl Game {
+    /// Creates a game with a random seed.
     pub fn new(player_names: Vec<String>, num_of_players: usize, size: usize) -> Game {
+        return Game::with_seed(player_names, num_of_players, size, rand::random::<u64>());
+    }
+
+    /// Creates a reproducible game - the same seed always generates the same map, spawns and dice rolls.
+    pub fn with_seed(player_names: Vec<String>, num_of_players: usize, size: usize, seed: u64) -> Game {
+        let mut names = player_names;
+        names.truncate(num_of_players);
+        return Game::with_rules(names, GameRules::with_size(size), seed);
+    }
+
+    /// Creates a reproducible game with the given rules.
+    pub fn with_rules(player_names: Vec<String>, rules: GameRules, seed: u64) -> Game {
+        let mut rng = GameRng::seed_from_u64(seed);
+        let generator: Box<dyn MazeGenerator> = generators::by_name(&rules.generator)
+            .unwrap_or_else(|| Box::new(RecursiveBacktracker));
+        let map = Map::with_generator(rules.map_width, rules.map_height, &generator, &mut rng);
+        return Game::create(player_names, rules, map, rng, seed);
+    }
+
+    /// Creates a game on a given (e.g. hand-authored) map. The size of the map overrides the rules.
+    pub fn with_map(player_names: Vec<String>, rules: GameRules, map: Map, seed: u64) -> Game {
+        let mut rules = rules;
+        rules.map_width = map.width;
+        rules.map_height = map.height;
+        return Game::create(player_names, rules, map, GameRng::seed_from_u64(seed), seed);
+    }
+
+    fn create(player_names: Vec<String>, rules: GameRules, map: Map, rng: GameRng, seed: u64) -> Game {
         let mut players: Vec<Player> = Vec::new();
         players.push(Player::new(String::new()));
         for (i, name) in player_names.iter().enumerate() {
-            players.push(Player::with_code(name.to_string(), i+1));
+            let mut player = Player::with_code(name.to_string(), i+1);
+            player.set_stats(rules.health, rules.attack, rules.defense);
+            if rules.teams > 0 {
+                player.team = i % rules.teams + 1;
+            }
+            players.push(player);
+        }
+
+        let (width, height) = (map.width, map.height);
+        let num_of_players = players.len();
+        Game{
+            map: map,
+            players: players,
+            rules: rules,
+            rng: rng,
+            seed: seed,
+            turn: 1,
+            moves_left: 0,
+            winner: None,
+            over: false,
+            exits: Vec::new(),
+            round: 1,
+            played: Duration::from_secs(0),
+            recorder: None,
+            history: Vec::new(),
+            key_bindings: KeyBindings::default(),
+            recorded_rolls: VecDeque::new(),
+            num_of_players: num_of_players,
+            map_width: width,
+            map_height: height
         }
+    }
 
+    /// Restores a game from its saved parts.
+    /// `players` must contain the placeholder player with code 0 as its first element.
+    pub(crate) fn from_parts(map: Map, players: Vec<Player>, rules: GameRules, seed: u64, rng: GameRng,
+                             turn: usize, moves_left: usize) -> Game {
+        let (width, height) = (map.width, map.height);
+        let num_of_players = players.len();
         Game{
-            map: Map::new(size),
+            map: map,
             players: players,
-            num_of_players: num_of_players + 1,
-            map_size: size
+            rules: rules,
+            rng: rng,
+            seed: seed,
+            turn: turn,
+            moves_left: moves_left,
+            winner: None,
+            over: false,
+            exits: Vec::new(),
+            round: 1,
+            played: Duration::from_secs(0),
+            recorder: None,
+            history: Vec::new(),
+            key_bindings: KeyBindings::default(),
+            recorded_rolls: VecDeque::new(),
+            num_of_players: num_of_players,
+            map_width: width,
+            map_height: height
         }
     }
 }
 
+/// Saving and loading
+impl Game {
+    /// Saves the game to a file. 
+    /// The file has the state of the random generator, so a loaded game continues in the same way as the saved one.
+    pub fn save(&self, path: &str) -> Result<(), SaveError> {
+        std::fs::write(path, save::serialize(self))?;
+        return Ok(());
+    }
+
+    /// Loads a game saved with `Game::save`.
+    pub fn load(path: &str) -> Result<Game, SaveError> {
+        let content = std::fs::read_to_string(path)?;
+        return save::deserialize(&content);
+    }
+
+    /// Starts recording the game to a replay file. Every action applied from now on is written to it.
+    /// Start recording once the players are on the map.
+    pub fn record(&mut self, path: &str) -> Result<(), ReplayError> {
+        self.recorder = Some(Recorder::create(path, self)?);
+        return Ok(());
+    }
+
+    /// Remembers what an action of a player caused and writes it to the replay, if the game is recorded.
+    pub(crate) fn log(&mut self, player: usize, action: &Action, events: &[Event]) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(player, action, events);
+        }
+        self.history.extend(events.iter().cloned());
+    }
+}
+
 /// Class methods
 impl Game {
     /// Returns String representation of the map
@@ -35,6 +186,38 @@ impl Game {
         return self.map.display();
     }
 
+    /// Returns String representation of the map as a player sees it.
+    /// With the fog of war on, the player sees the fields in the view radius that aren't behind walls
+    /// and remembers the walls, the portal and the items on the fields explored before. The rest is hidden.
+    pub fn display_map_for(&self, player_code: usize) -> String {
+        let radius = self.rules.view_radius;
+        if radius == 0 {
+            return self.display_map();
+        }
+        let player = &self.players[player_code];
+        let visible: HashSet<Position> = match player.coordinates {
+            Some(position) => visibility::visible_fields(&self.map, position, radius).into_iter().collect(),
+            None => HashSet::new(),
+        };
+        let mut result = String::new();
+        for x in 0..self.map_height {
+            for y in 0..self.map_width {
+                let position = Position(x, y);
+                let field = self.map.get_field(position);
+                if visible.contains(&position) {
+                    result.push(field);
+                } else if player.has_explored(position) {
+                    result.push(if self.map.player_at(position).is_some() { self.map.tile(position).symbol() } else { field });
+                } else {
+                    result.push(HIDDEN_FIELD);
+                }
+                result.push(' ');
+            }
+            result.push('\n');
+        }
+        return result;
+    }
+
     /// Returns string representation of the game info
     pub fn display(&self) -> String {
         let mut result = String::new();
@@ -63,40 +246,455 @@ impl Game {
     /// Initialises a player. Returns false if an Error occured.
     pub fn spawn_player(&mut self, player_code: usize, position: Position) -> bool {
         match self.map.spawn_player(&mut self.players[player_code], position) {
-            Ok (_) => return true,
+            Ok (_) => {
+                self.explore(player_code);
+                return true;
+            },
             Err(_) => return false,
         }
     }
 
+    /// Returns the rules of the game.
+    pub fn rules(&self) -> &GameRules {
+        return &self.rules;
+    }
+
+    /// Returns the seed the game was created with.
+    pub fn seed(&self) -> u64 {
+        return self.seed;
+    }
+
+    /// Returns the state of the random generator: the seed it started from and the words drawn since.
+    pub(crate) fn rng_state(&self) -> (u64, u64) {
+        return self.rng.state();
+    }
+
+    /// Returns the code of the player on turn.
+    pub fn turn(&self) -> usize {
+        return self.turn;
+    }
+
+    /// Returns the number of moves the player on turn has left from the dice.
+    pub fn moves_left(&self) -> usize {
+        return self.moves_left;
+    }
+
+    /// Sets the number of moves the player on turn has left.
+    pub fn set_moves_left(&mut self, moves: usize) {
+        self.moves_left = moves;
+    }
+
+    /// Passes the turn to the next player. A new round starts when the first player is on turn again.
+    pub fn end_turn(&mut self) {
+        self.moves_left = 0;
+        self.turn = self.turn % (self.num_of_players - 1) + 1;
+        if self.turn == 1 {
+            self.round += 1;
+        }
+    }
+
+    /// Returns the number of the round being played, starting with 1.
+    pub fn round(&self) -> usize {
+        return self.round;
+    }
+
+    /// Returns how long the game has been played, including the time before it was saved.
+    /// The game doesn't read the clock itself, the frontend reports the time with `Action::Time`.
+    pub fn elapsed(&self) -> Duration {
+        return self.played;
+    }
+
+    /// Counts some more time as played.
+    pub(crate) fn add_time(&mut self, time: Duration) {
+        self.played += time;
+    }
+
+    /// Restores the round, the players who got out and the time played of a saved game.
+    pub(crate) fn set_progress(&mut self, round: usize, exits: Vec<usize>, played: Duration) {
+        self.round = round;
+        self.exits = exits;
+        self.played = played;
+    }
+
+    /// Checks if the players are already on the map.
+    pub fn in_progress(&self) -> bool {
+        for player_code in 1..self.num_of_players {
+            if self.in_game(player_code) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    /// Returns the map of the game.
+    pub fn map(&self) -> &Map {
+        return &self.map;
+    }
+
+    /// Returns who controls a player.
+    pub fn player_kind(&self, player_code: usize) -> PlayerKind {
+        return self.players[player_code].kind;
+    }
+
+    /// Sets who controls a player.
+    pub fn set_player_kind(&mut self, player_code: usize, kind: PlayerKind) {
+        self.players[player_code].kind = kind;
+    }
+
+    /// Sets the PIN a player enters before their view of the map is shown. None means no PIN.
+    /// Only a salted hash of the PIN is kept.
+    pub fn set_pin(&mut self, player_code: usize, pin: Option<String>) {
+        self.players[player_code].pin = pin.map(|pin| PinHash::new(&pin));
+    }
+
+    /// Checks if a player has a PIN.
+    pub fn has_pin(&self, player_code: usize) -> bool {
+        return self.players[player_code].pin.is_some();
+    }
+
+    /// Checks a player's PIN. Always true for players without a PIN.
+    pub fn check_pin(&self, player_code: usize, pin: &str) -> bool {
+        match &self.players[player_code].pin {
+            Some(expected) => return expected.matches(pin),
+            None => return true,
+        }
+    }
+
+    /// Returns everything that happened in the game since it was created or loaded.
+    pub fn history(&self) -> &[Event] {
+        return &self.history;
+    }
+
+    /// Returns what happened since the last time a player asked.
+    pub fn take_news(&mut self, player_code: usize) -> Vec<Event> {
+        let seen = self.players[player_code].seen_events;
+        self.players[player_code].seen_events = self.history.len();
+        return self.history[seen..].to_vec();
+    }
+
+    /// Returns the keys the human players move with.
+    pub fn key_bindings(&self) -> &KeyBindings {
+        return &self.key_bindings;
+    }
+
+    pub fn set_key_bindings(&mut self, key_bindings: KeyBindings) {
+        self.key_bindings = key_bindings;
+    }
+
+    /// Asks a bot for its next move. Returns None for a human player, whose moves come from the frontend.
+    /// Bots get their own generator, seeded from the game's one, so their moves are reproducible too.
+    pub fn next_move(&mut self, player_code: usize) -> Option<Direction> {
+        let controller = self.player_kind(player_code).controller()?;
+        let mut rng = StdRng::seed_from_u64(self.rng.gen::<u64>());
+        return Some(controller.next_move(self, player_code, &mut rng));
+    }
+
+    /// Returns all players, including the placeholder with code 0.
+    pub(crate) fn players(&self) -> &[Player] {
+        return &self.players;
+    }
+
+    /// Returns a player's coordinates or None if the player is dead or not spawned yet.
+    pub fn get_player_position(&self, player_code: usize) -> Option<Position> {
+        return self.players[player_code].coordinates;
+    }
+
+    /// Places all the players, then the portal and then the items on the map.
+    /// Players and portals the map defines are placed on their fields,
+    /// the rest go where the spawn strategy of the rules chooses.
+    /// Returns `GameError::NoRoom` and places nobody if the map has too few empty fields.
+    pub fn spawn_all(&mut self) -> Result<(), GameError> {
+        let fixed: Vec<Option<Position>> = (1..self.num_of_players).map(|player| self.map.spawn_point(player)).collect();
+        let portal = self.map.portal();
+        let strategy = spawns::by_name(&self.rules.spawn).unwrap_or_else(|| Box::new(RandomSpawns));
+        let spawns = strategy.place(&self.map, &fixed, portal, &mut self.rng)?;
+        for (i, position) in spawns.players.iter().enumerate() {
+            self.spawn_player(i + 1, *position);
+        }
+        if portal.is_none() {
+            self.spawn_portal(spawns.portal);
+        }
+        self.scatter_items();
+        return Ok(());
+    }
+
+    /// Scatters the items of the rules on random empty fields that can be reached from the portal:
+    /// a key for every player if the portal is locked and the map has no keys, and `items` items of the other kinds.
+    fn scatter_items(&mut self) {
+        let mut items: Vec<Item> = Vec::new();
+        if self.rules.locked_portal && !self.map.items().iter().any(|(_, item)| *item == Item::Key) {
+            items.resize(self.num_of_players - 1, Item::Key);
+        }
+        for _ in 0..self.rules.items {
+            items.push(*Item::SCATTERED.choose(&mut self.rng).unwrap());
+        }
+        let portal = match self.map.portal() {
+            Some(portal) if !items.is_empty() => portal,
+            _ => return,
+        };
+        let mut fields: Vec<Position> = self.map.distances(portal).reachable().into_iter()
+            .filter(|field| self.map.empty(field.0, field.1)).collect();
+        fields.shuffle(&mut self.rng);
+        for (item, field) in items.into_iter().zip(fields) {
+            self.map.spawn_item(field, item).ok();
+        }
+    }
+
+    /// Rolls the dice. The number of sides comes from the rules.
+    pub fn roll_dice(&mut self) -> usize {
+        return self.rng.gen_range(1, self.rules.dice_sides + 1);
+    }
+
     /// Returns a player's name by its code.
     pub fn get_player_name(&self, player_code: usize) -> &str {
         return self.players[player_code].name.as_str();
     }
 
+    /// Returns the code of the player on the neighbouring field in a direction, 0 if there is none.
+    pub(crate) fn opponent(&self, player_code: usize, direction: Direction) -> usize {
+        match self.players[player_code].coordinates.and_then(|position| self.map.neighbour(position, direction)) {
+            Some(field) => return self.map.player_at(field).unwrap_or(0),
+            None => return 0,
+        }
+    }
+
+    /// Leaves the items of a player who left the map on the empty fields nearest to `field`, where the player was,
+    /// so the keys and the other items stay in the game.
+    fn drop_items(&mut self, player_code: usize, field: Position) {
+        let items = self.players[player_code].take_items();
+        if items.is_empty() {
+            return;
+        }
+        let distances = self.map.distances(field);
+        let mut fields: Vec<Position> = distances.reachable().into_iter()
+            .filter(|position| self.map.empty(position.0, position.1)).collect();
+        fields.sort_by_key(|position| distances.get(*position));
+        for (item, position) in items.into_iter().zip(fields) {
+            self.map.spawn_item(position, item).ok();
+        }
+    }
+
     /// Simulates a battle between players.
-    /// Removes the opponent from the map, adds points to the attaking player and moves it to the new field.
-    /// Returns the code of the defeated player.
-    pub fn battle(&mut self, player_code: usize, direction: &str) -> usize {   
-        let field: Position = change_coordinates(self.players[player_code].coordinates.unwrap(), direction).unwrap();
-        let opponent_code: usize = get_player(self.map.get_field(field));
+    /// Removes the opponent from the map, adds points to the attaking player and moves it to the new field
+    /// like any other move, so it picks up the item there and takes a teleport pad.
+    /// The opponent's items are left around the field.
+    /// Returns the code of the defeated player and what happened after the attacker got on the field.
+    pub fn battle(&mut self, player_code: usize, direction: Direction) -> Result<(usize, Vec<Event>), GameError> {
+        let (field, opponent_code) = self.defender(player_code, direction)?;
         self.map.kill_player(&mut self.players[opponent_code]);
-        self.map.move_player(&mut self.players[player_code], direction).unwrap();
-        self.players[player_code].win_points(BATTLE_SCORE);
-        return opponent_code;
+        let arrived = self.move_player(player_code, direction)?;
+        self.drop_items(opponent_code, field);
+        let points = self.rules.battle_score;
+        self.players[player_code].win_points(points);
+        return Ok((opponent_code, arrived));
+    }
+
+    /// Returns the field a player attacks in a direction and the code of the player on it.
+    fn defender(&self, player_code: usize, direction: Direction) -> Result<(Position, usize), GameError> {
+        let position = self.players[player_code].coordinates.ok_or(GameError::InvalidCoordinates)?;
+        let field: Position = change_coordinates(position, direction)?;
+        match self.map.player_at(field) {
+            Some(opponent_code) => return Ok((field, opponent_code)),
+            None => return Err(GameError::InvalidField),
+        }
+    }
+
+    /// Simulates a battle with the combat rule on.
+    /// Both players roll the dice, the attacker adds its attack and the defender its defense.
+    /// The one with the lower total loses the difference in health, nothing happens on a draw.
+    /// A player without any health left is killed and the other one wins the battle points.
+    /// A defender who survives is pushed back if the field behind it is empty and the attacker takes its field,
+    /// an attacker who survives stays where it was. Returns what happened.
+    pub fn fight(&mut self, player_code: usize, direction: Direction) -> Result<Vec<Event>, GameError> {
+        let (field, opponent_code) = self.defender(player_code, direction)?;
+        let attack_roll = self.combat_roll();
+        let defense_roll = self.combat_roll();
+        let attack = attack_roll as u32 + self.players[player_code].attack;
+        let defense = defense_roll as u32 + self.players[opponent_code].defense;
+        let points = self.rules.battle_score;
+        let mut events = vec![Event::Fight { attacker: player_code, defender: opponent_code,
+            attack_roll: attack_roll, defense_roll: defense_roll }];
+        if attack > defense {
+            if !self.players[opponent_code].take_damage(attack - defense) && !self.break_shield(opponent_code) {
+                let (_, arrived) = self.battle(player_code, direction)?;
+                events.push(Event::Battle { winner: player_code, loser: opponent_code, points: points });
+                events.push(Event::Moved { player: player_code, to: field });
+                events.extend(arrived);
+                return Ok(events);
+            }
+            if self.players[opponent_code].health == 0 {
+                events.push(Event::Shielded { player: opponent_code });
+                self.players[opponent_code].health = 1;
+            }
+            events.push(Event::Wounded { player: opponent_code, damage: attack - defense,
+                health: self.players[opponent_code].health });
+            let behind = self.map.neighbour(field, direction);
+            if let Ok(arrived) = self.move_player(opponent_code, direction) {
+                events.push(Event::Pushed { player: opponent_code, to: behind.unwrap() });
+                events.extend(arrived);
+                let arrived = self.move_player(player_code, direction)?;
+                events.push(Event::Moved { player: player_code, to: field });
+                events.extend(arrived);
+            }
+        } else if defense > attack {
+            if !self.players[player_code].take_damage(defense - attack) && !self.break_shield(player_code) {
+                let position = self.players[player_code].coordinates.unwrap();
+                self.map.kill_player(&mut self.players[player_code]);
+                self.drop_items(player_code, position);
+                self.players[opponent_code].win_points(points);
+                events.push(Event::Battle { winner: opponent_code, loser: player_code, points: points });
+                return Ok(events);
+            }
+            if self.players[player_code].health == 0 {
+                events.push(Event::Shielded { player: player_code });
+                self.players[player_code].health = 1;
+            }
+            events.push(Event::Wounded { player: player_code, damage: defense - attack,
+                health: self.players[player_code].health });
+        }
+        return Ok(events);
+    }
+
+    /// Breaks the shield of a player who would be killed in a battle.
+    /// Returns false if the player has no shield.
+    pub fn break_shield(&mut self, player_code: usize) -> bool {
+        return self.players[player_code].take_item(Item::Shield);
+    }
+
+    /// Rolls the dice for a battle, or takes the next recorded roll when a replay is played.
+    fn combat_roll(&mut self) -> usize {
+        match self.recorded_rolls.pop_front() {
+            Some(value) => return value,
+            None => return self.roll_dice(),
+        }
+    }
+
+    /// Makes the next battles use these dice values instead of rolling.
+    pub(crate) fn queue_rolls(&mut self, rolls: &[usize]) {
+        self.recorded_rolls.extend(rolls.iter().cloned());
+    }
+
+    /// Returns a player's health, attack and defense.
+    pub fn get_player_stats(&self, player_code: usize) -> (u32, u32, u32) {
+        let player = &self.players[player_code];
+        return (player.health, player.attack, player.defense);
+    }
+
+    /// Returns the items in a player's inventory.
+    pub fn get_player_items(&self, player_code: usize) -> &[Item] {
+        return self.players[player_code].items();
+    }
+
+    /// Checks if a player may walk through the portal - the portal is not locked or the player has a key.
+    pub fn can_open_portal(&self, player_code: usize) -> bool {
+        return !self.rules.locked_portal || self.players[player_code].has_item(Item::Key);
+    }
+
+    /// Uses an item from a player's inventory. A potion is drunk and gives more moves,
+    /// the compass shows the first step of the shortest way to the portal and how long it is, see `portal_path`.
+    pub fn use_item(&mut self, player_code: usize, item: Item) -> Result<Event, GameError> {
+        if !item.is_usable() {
+            return Err(GameError::NotUsable);
+        }
+        if !self.players[player_code].has_item(item) {
+            return Err(GameError::NoItem);
+        }
+        if item == Item::Potion {
+            self.players[player_code].take_item(item);
+            self.moves_left += POTION_MOVES;
+            return Ok(Event::ItemUsed { player: player_code, item: item });
+        }
+        let path = self.portal_path(player_code);
+        return Ok(Event::Compass { player: player_code, direction: path.as_ref().and_then(|path| path.first().cloned()),
+            steps: path.map_or(0, |path| path.len()) });
+    }
+
+    /// Returns the shortest way of a player to the portal that doesn't cross a locked door or the locked portal.
+    /// Like a path checked by the engine, it may pick up a key on the way, which opens the doors and the portal after it.
+    fn portal_path(&self, player_code: usize) -> Option<Vec<Direction>> {
+        let position = self.get_player_position(player_code)?;
+        let portal = self.map.portal()?;
+        if self.players[player_code].has_item(Item::Key) {
+            return self.map.shortest_path(position, portal);
+        }
+        let can_open_portal = self.can_open_portal(player_code);
+        let unlocked = |field: Position| match self.map.tile(field) {
+            Tile::Door => false,
+            Tile::Portal => can_open_portal,
+            _ => true,
+        };
+        let mut paths: Vec<Vec<Direction>> = self.map.find_path_over(position, unlocked, |field| field == portal).into_iter().collect();
+        for (key, _) in self.map.items().into_iter().filter(|(_, item)| *item == Item::Key) {
+            if let (Some(mut path), Some(rest)) = (self.map.find_path_over(position, unlocked, |field| field == key), self.map.shortest_path(key, portal)) {
+                path.extend(rest);
+                paths.push(path);
+            }
+        }
+        return paths.into_iter().min_by_key(|path| path.len());
     }
 
     /// Simulates walking through a portal.
-    /// Adds points to the player.
+    /// Adds points to the player, who leaves the labyrinth. Whether that wins the game depends on the mode.
+    /// The player's items stay in the labyrinth, next to the portal.
     pub fn portal(&mut self, player_code: usize) {
-        self.players[player_code].win_points(PORTAL_SCORE);
+        let points = self.rules.portal_score;
+        self.players[player_code].win_points(points);
+        let position = self.players[player_code].coordinates.unwrap();
+        self.map.kill_player(&mut self.players[player_code]);
+        self.drop_items(player_code, position);
+        self.exits.push(player_code);
+    }
+
+    /// Returns the codes of the players who walked through the portal, in the order they did.
+    pub fn exits(&self) -> &[usize] {
+        return &self.exits;
+    }
+
+    /// Returns the codes of the players who are still in the labyrinth.
+    pub fn players_left(&self) -> Vec<usize> {
+        return (1..self.num_of_players).filter(|player| self.in_game(*player)).collect();
+    }
+
+    /// Returns the win condition of the game's mode.
+    pub fn win_condition(&self) -> Box<dyn WinCondition> {
+        return self.rules.mode.win_condition(&self.rules);
+    }
+
+    /// Checks if the game is over.
+    pub fn is_over(&self) -> bool {
+        return self.over;
+    }
+
+    /// Ends the game. The winner is None if nobody won.
+    pub(crate) fn end_game(&mut self, winner: Option<usize>) {
+        self.over = true;
+        self.winner = winner;
+    }
+
+    /// Remembers the fields a player sees from its position. Does nothing without the fog of war.
+    fn explore(&mut self, player_code: usize) {
+        let radius = self.rules.view_radius;
+        if radius == 0 {
+            return;
+        }
+        if let Some(position) = self.players[player_code].coordinates {
+            for field in visibility::visible_fields(&self.map, position, radius) {
+                self.players[player_code].explore(field);
+            }
+        }
+    }
+
+    /// Returns the code of the player who won, or None if the game isn't over or nobody won.
+    pub fn winner(&self) -> Option<usize> {
+        return self.winner;
     }
 
     /// Simulates making a move.
     /// Returns true if the move os successfully done and the game may continue. 
     /// Returns false if the portal was found and the game ends=
     /// Returns an Error otherwise.
-    pub fn make_move(&mut self, player_code: usize, direction: &str) -> Result<bool, GameError> {
-        match self.map.move_player(&mut self.players[player_code], direction) {
+    pub fn make_move(&mut self, player_code: usize, direction: Direction) -> Result<bool, GameError> {
+        match self.move_player(player_code, direction) {
             Ok (_) => return Ok(true),
             Err(GameError::AnotherPlayer) => {
                 return Err(GameError::AnotherPlayer);
@@ -110,17 +708,135 @@ impl Game {
         }
     }
 
+    /// Returns the shortest path of a player to a field, going around the walls.
+    /// The path may be longer than the moves the player has left.
+    pub fn path_to(&self, player_code: usize, target: Position) -> Result<Vec<Direction>, GameError> {
+        if target.0 >= self.map.height || target.1 >= self.map.width {
+            return Err(GameError::Outside);
+        }
+        let start = match self.get_player_position(player_code) {
+            Some(start) => start,
+            None => return Err(GameError::InvalidAction),
+        };
+        match self.map.shortest_path(start, target) {
+            Some(ref path) if path.is_empty() => return Err(GameError::InvalidCoordinates),
+            Some(path) => return Ok(path),
+            None => return Err(GameError::InvalidField),
+        }
+    }
+
+    /// Moves a player on the map. Unlike `make_move`, returns the exact reason a move is invalid.
+    /// The player picks up the item on the new field and a teleport pad takes it to the other pad, if it is free.
+    /// Returns what happened after the player got on the new field. A treasure wins its points at once.
+    pub(crate) fn move_player(&mut self, player_code: usize, direction: Direction) -> Result<Vec<Event>, GameError> {
+        let item = self.players[player_code].coordinates.and_then(|position| self.map.neighbour(position, direction))
+            .and_then(|field| self.map.item(field));
+        self.map.move_player(&mut self.players[player_code], direction)?;
+        self.explore(player_code);
+        let mut events: Vec<Event> = Vec::new();
+        if let Some(item) = item {
+            if item == Item::Treasure {
+                let points = self.rules.treasure_score;
+                self.players[player_code].win_points(points);
+            }
+            self.players[player_code].add_item(item);
+            events.push(Event::ItemPicked { player: player_code, item: item });
+        }
+        if let Ok(pair) = self.map.teleport_player(&mut self.players[player_code]) {
+            self.explore(player_code);
+            events.push(Event::Teleported { player: player_code, to: pair });
+        }
+        return Ok(events);
+    }
+
+    /// Breaks a breakable wall. Returns false if there is no breakable wall on the field.
+    pub fn break_wall(&mut self, position: Position) -> bool {
+        return self.map.break_wall(position);
+    }
+
+    /// Returns a player's team, 0 when the game isn't played in teams.
+    pub fn get_player_team(&self, player_code: usize) -> usize {
+        return self.players[player_code].team;
+    }
+
+    /// Moves a player to another team. The players are split in teams in turns when the game is created.
+    pub fn set_player_team(&mut self, player_code: usize, team: usize) {
+        self.players[player_code].team = team;
+    }
+
+    /// Checks if two different players are in the same team.
+    pub fn teammates(&self, first: usize, second: usize) -> bool {
+        let team = self.players[first].team;
+        return first != second && team != 0 && team == self.players[second].team;
+    }
+
+    /// Swaps a player with the teammate next to it in a direction.
+    /// The teammate steps on the player's field the other way, so an arrow or a locked door there stops the swap.
+    /// A teleport pad takes either of them to its pair. Returns the teleports.
+    pub(crate) fn swap_players(&mut self, player_code: usize, direction: Direction) -> Result<Vec<Event>, GameError> {
+        let teammate_code = self.opponent(player_code, direction);
+        match self.map.tile(self.players[player_code].coordinates.unwrap()) {
+            Tile::Door if !self.players[teammate_code].has_item(Item::Key) => return Err(GameError::DoorLocked),
+            tile if !tile.can_enter(direction.opposite()) => return Err(GameError::OneWay),
+            _ => (),
+        }
+        let (low, high) = self.players.split_at_mut(player_code.max(teammate_code));
+        let (player, teammate) = if player_code < teammate_code {
+            (&mut low[player_code], &mut high[0])
+        } else {
+            (&mut high[0], &mut low[teammate_code])
+        };
+        self.map.swap_players(player, teammate);
+        let mut events: Vec<Event> = Vec::new();
+        for code in [player_code, teammate_code].iter() {
+            self.explore(*code);
+            if let Ok(pair) = self.map.teleport_player(&mut self.players[*code]) {
+                self.explore(*code);
+                events.push(Event::Teleported { player: *code, to: pair });
+            }
+        }
+        return Ok(events);
+    }
+
+    /// Returns the team of the player who won, or None if the game isn't over or isn't played in teams.
+    pub fn winning_team(&self) -> Option<usize> {
+        return self.winner.map(|player_code| self.players[player_code].team).filter(|team| *team != 0);
+    }
+
+    /// Returns the teams with the sums of their players' scores, the best team first.
+    /// Teams without players, e.g. when there are more teams than players, are left out.
+    pub fn team_scores(&self) -> Vec<(usize, u32)> {
+        let mut result: Vec<(usize, u32)> = (1..=self.rules.teams)
+            .filter(|team| self.players.iter().any(|player| player.team == *team))
+            .map(|team| (team, self.players.iter()
+            .filter(|player| player.team == team).map(|player| player.score()).sum())).collect();
+        result.sort_by(|first, second| second.1.cmp(&first.1));
+        return result;
+    }
+
     /// Checks if a player is still in the game.
     pub fn in_game(&self, player_code: usize) -> bool {
         return self.players[player_code].is_alive();
     }
 
     /// Returns a string representation of the final ranklist.
+    /// In a team game the teams with their total scores and players come first, the winning team at the top.
+    /// The players are ranked as the mode of the game says, by their scores unless it is `exit-all`.
     pub fn finish(self) -> String {
         let mut ranklist = self.players.clone();
         ranklist.remove(0);
-        ranklist = Player::rank(ranklist);
+        ranklist = self.win_condition().rank(&self, ranklist);
         let mut result = String::new();
+        let mut teams = self.team_scores();
+        if let Some(winner) = self.winning_team() {
+            let position = teams.iter().position(|(team, _)| *team == winner).unwrap();
+            let first = teams.remove(position);
+            teams.insert(0, first);
+        }
+        for (team, score) in teams {
+            let names: Vec<&str> = ranklist.iter().filter(|player| player.team == team).map(|player| player.name.as_str()).collect();
+            result = result + "=== team " + &team.to_string() + " ===\nscore: " + &score.to_string() + "\n" + &names.join(", ") + "\n\n";
+        }
         for (i, player) in ranklist.iter().enumerate() {
             result = result + "--- " + &(i+1).to_string() + " ---\n" + &player.display() + "\n";
         }
@@ -0,0 +1,332 @@
+use simple_matrix::Matrix;
+use rand::{Rng, RngCore};
+use rand::seq::SliceRandom;
+use crate::FIELDS;
+
+/// Maze generation algorithms.
+/// The labyrinth is a grid of cells placed on the even rows and columns of the map.
+/// When the width or the height is even, the last column or row of cells is on the border of the map instead,
+/// so the passages to it are two fields long. The fields between two neighbouring cells are walls or passages,
+/// depending on the algorithm.
+/// All the generators produce perfect mazes (exactly one path between any two cells),
+/// except `Braided`, which removes the dead ends of another generator's maze.
+/// Every generator works in bounded time, proportional to the number of fields.
+pub trait MazeGenerator {
+    /// Generates a map of empty fields and walls with `height` rows and `width` columns.
+    fn generate(&self, width: usize, height: usize, rng: &mut dyn RngCore) -> Matrix<char>;
+}
+
+/// Names of the generators, as accepted by `by_name`.
+pub const GENERATORS: [&str; 4] = ["backtracker", "prim", "kruskal", "wilson"];
+/// Prefix for a braided version of a generator, e.g. `braided-prim`.
+pub const BRAIDED_PREFIX: &str = "braided-";
+
+/// Returns a generator by its name or None if there is no such generator.
+pub fn by_name(name: &str) -> Option<Box<dyn MazeGenerator>> {
+    if name.starts_with(BRAIDED_PREFIX) {
+        return match by_name(&name[BRAIDED_PREFIX.len()..]) {
+            Some(generator) => Some(Box::new(Braided(generator))),
+            None => None,
+        };
+    }
+    match name {
+        "backtracker" => return Some(Box::new(RecursiveBacktracker)),
+        "prim" => return Some(Box::new(Prim)),
+        "kruskal" => return Some(Box::new(Kruskal)),
+        "wilson" => return Some(Box::new(Wilson)),
+        _ => return None,
+    }
+}
+
+/// Returns the fewest empty fields a generated maze of this size has: its cells and a passage between every two of them.
+pub fn min_empty_fields(width: usize, height: usize) -> usize {
+    let cells = ((width + 1) / 2) * ((height + 1) / 2);
+    return (2 * cells).saturating_sub(1);
+}
+
+/// Depth-first search with backtracking - long, winding corridors with few branches.
+pub struct RecursiveBacktracker;
+
+/// Randomized Prim's algorithm - many short branches growing from a single cell.
+pub struct Prim;
+
+/// Randomized Kruskal's algorithm - joins random passages until everything is connected.
+pub struct Kruskal;
+
+/// Wilson's algorithm - loop-erased random walks, every possible maze is equally likely.
+pub struct Wilson;
+
+/// Removes all the dead ends of another generator's maze, creating loops.
+pub struct Braided<G: MazeGenerator>(pub G);
+
+impl MazeGenerator for Box<dyn MazeGenerator> {
+    fn generate(&self, width: usize, height: usize, rng: &mut dyn RngCore) -> Matrix<char> {
+        return self.as_ref().generate(width, height, rng);
+    }
+}
+
+impl MazeGenerator for RecursiveBacktracker {
+    fn generate(&self, width: usize, height: usize, rng: &mut dyn RngCore) -> Matrix<char> {
+        let mut grid = Grid::new(width, height);
+        let mut visited = vec![false; grid.cells()];
+        let start = rng.gen_range(0, grid.cells());
+        let mut stack: Vec<usize> = vec![start];
+        visited[start] = true;
+        grid.open_cell(start);
+
+        while let Some(&cell) = stack.last() {
+            let unvisited: Vec<usize> = grid.neighbours(cell).into_iter().filter(|n| !visited[*n]).collect();
+            match unvisited.choose(rng) {
+                Some(&next) => {
+                    visited[next] = true;
+                    grid.connect(cell, next);
+                    stack.push(next);
+                },
+                None => {
+                    stack.pop();
+                },
+            }
+        }
+        return grid.fields;
+    }
+}
+
+impl MazeGenerator for Prim {
+    fn generate(&self, width: usize, height: usize, rng: &mut dyn RngCore) -> Matrix<char> {
+        let mut grid = Grid::new(width, height);
+        let mut in_maze = vec![false; grid.cells()];
+        let start = rng.gen_range(0, grid.cells());
+        in_maze[start] = true;
+        grid.open_cell(start);
+
+        let mut frontier: Vec<(usize, usize)> = grid.neighbours(start).into_iter().map(|n| (start, n)).collect();
+        while !frontier.is_empty() {
+            let (from, to) = frontier.swap_remove(rng.gen_range(0, frontier.len()));
+            if in_maze[to] {
+                continue;
+            }
+            in_maze[to] = true;
+            grid.connect(from, to);
+            for next in grid.neighbours(to) {
+                if !in_maze[next] {
+                    frontier.push((to, next));
+                }
+            }
+        }
+        return grid.fields;
+    }
+}
+
+impl MazeGenerator for Kruskal {
+    fn generate(&self, width: usize, height: usize, rng: &mut dyn RngCore) -> Matrix<char> {
+        let mut grid = Grid::new(width, height);
+        let mut walls: Vec<(usize, usize)> = Vec::new();
+        for cell in 0..grid.cells() {
+            grid.open_cell(cell);
+            for next in grid.neighbours(cell) {
+                if cell < next {
+                    walls.push((cell, next));
+                }
+            }
+        }
+        walls.shuffle(rng);
+
+        let mut parents: Vec<usize> = (0..grid.cells()).collect();
+        for (a, b) in walls {
+            let root_a = find_root(&mut parents, a);
+            let root_b = find_root(&mut parents, b);
+            if root_a != root_b {
+                parents[root_a] = root_b;
+                grid.connect(a, b);
+            }
+        }
+        return grid.fields;
+    }
+}
+
+impl MazeGenerator for Wilson {
+    fn generate(&self, width: usize, height: usize, rng: &mut dyn RngCore) -> Matrix<char> {
+        let mut grid = Grid::new(width, height);
+        let mut in_maze = vec![false; grid.cells()];
+        let mut next = vec![0; grid.cells()];
+        let start = rng.gen_range(0, grid.cells());
+        in_maze[start] = true;
+        grid.open_cell(start);
+
+        let mut order: Vec<usize> = (0..grid.cells()).collect();
+        order.shuffle(rng);
+        for walk_start in order {
+            // Random walk until the maze is reached. Overwriting `next` erases the loops.
+            let mut cell = walk_start;
+            while !in_maze[cell] {
+                next[cell] = *grid.neighbours(cell).choose(rng).unwrap();
+                cell = next[cell];
+            }
+            cell = walk_start;
+            while !in_maze[cell] {
+                in_maze[cell] = true;
+                grid.connect(cell, next[cell]);
+                cell = next[cell];
+            }
+        }
+        return grid.fields;
+    }
+}
+
+impl<G: MazeGenerator> MazeGenerator for Braided<G> {
+    fn generate(&self, width: usize, height: usize, rng: &mut dyn RngCore) -> Matrix<char> {
+        let mut grid = Grid::new(width, height);
+        grid.fields = self.0.generate(width, height, rng);
+        for cell in 0..grid.cells() {
+            let neighbours = grid.neighbours(cell);
+            let closed: Vec<usize> = neighbours.iter().cloned().filter(|n| !grid.connected(cell, *n)).collect();
+            if neighbours.len() - closed.len() != 1 {
+                continue;
+            }
+            // Prefer joining two dead ends, so fewer walls are removed.
+            let dead_ends: Vec<usize> = closed.iter().cloned().filter(|n| grid.is_dead_end(*n)).collect();
+            let candidates = if dead_ends.is_empty() { closed } else { dead_ends };
+            if let Some(&other) = candidates.choose(rng) {
+                grid.connect(cell, other);
+            }
+        }
+        return grid.fields;
+    }
+}
+
+fn find_root(parents: &mut Vec<usize>, cell: usize) -> usize {
+    let mut root = cell;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    let mut current = cell;
+    while parents[current] != root {
+        let parent = parents[current];
+        parents[current] = root;
+        current = parent;
+    }
+    return root;
+}
+
+/// Maps the maze cells to fields of the map. Cell `i` is on row `2 * (i / columns)`, column `2 * (i % columns)`,
+/// except the last row and column of cells, which are on the last row and column of the map.
+struct Grid {
+    columns: usize,
+    rows: usize,
+    width: usize,
+    height: usize,
+    fields: Matrix<char>,
+}
+
+impl Grid {
+    fn new(width: usize, height: usize) -> Grid {
+        let mut fields: Matrix<char> = Matrix::new(height, width);
+        fields.apply_mut(|field| *field = FIELDS[1]);
+        Grid{
+            columns: (width + 1) / 2,
+            rows: (height + 1) / 2,
+            width: width,
+            height: height,
+            fields: fields,
+        }
+    }
+
+    fn cells(&self) -> usize {
+        return self.rows * self.columns;
+    }
+
+    fn field(&self, cell: usize) -> (usize, usize) {
+        let (row, column) = (cell / self.columns, cell % self.columns);
+        let x = if row > 0 && row + 1 == self.rows { self.height - 1 } else { 2 * row };
+        let y = if column > 0 && column + 1 == self.columns { self.width - 1 } else { 2 * column };
+        return (x, y);
+    }
+
+    fn neighbours(&self, cell: usize) -> Vec<usize> {
+        let (row, column) = (cell / self.columns, cell % self.columns);
+        let mut result: Vec<usize> = Vec::new();
+        if row > 0 {
+            result.push(cell - self.columns);
+        }
+        if row + 1 < self.rows {
+            result.push(cell + self.columns);
+        }
+        if column > 0 {
+            result.push(cell - 1);
+        }
+        if column + 1 < self.columns {
+            result.push(cell + 1);
+        }
+        return result;
+    }
+
+    fn open_cell(&mut self, cell: usize) {
+        let (x, y) = self.field(cell);
+        self.fields.set(x, y, FIELDS[0]);
+    }
+
+    /// Opens both cells and the walls between them.
+    fn connect(&mut self, a: usize, b: usize) {
+        let ((ax, ay), (bx, by)) = (self.field(a), self.field(b));
+        self.open_cell(a);
+        self.open_cell(b);
+        for x in ax.min(bx)..=ax.max(bx) {
+            for y in ay.min(by)..=ay.max(by) {
+                self.fields.set(x, y, FIELDS[0]);
+            }
+        }
+    }
+
+    fn connected(&self, a: usize, b: usize) -> bool {
+        let ((ax, ay), (bx, by)) = (self.field(a), self.field(b));
+        return *self.fields.get((ax + bx) / 2, (ay + by) / 2).unwrap() == FIELDS[0];
+    }
+
+    fn is_dead_end(&self, cell: usize) -> bool {
+        return self.neighbours(cell).iter().filter(|n| self.connected(cell, **n)).count() == 1;
+    }
+}
+
+
+
+// ----------------------------------------------------------------------------------
+// Private functions test
+
+#[test]
+fn test_grid_neighbours() {
+    let grid = Grid::new(5, 5);
+    assert_eq!(grid.cells(), 9);
+    assert_eq!(grid.field(5), (2, 4));
+    assert_eq!(grid.neighbours(0), vec![3, 1]);
+    assert_eq!(grid.neighbours(4), vec![1, 7, 3, 5]);
+    let wide = Grid::new(7, 3);
+    assert_eq!(wide.cells(), 8);
+    assert_eq!(wide.field(5), (2, 2));
+    assert_eq!(wide.neighbours(3), vec![7, 2]);
+    let even = Grid::new(6, 4);
+    assert_eq!(even.cells(), 6);
+    assert_eq!(even.field(4), (3, 2));
+    assert_eq!(even.field(5), (3, 5));
+}
+
+#[test]
+fn test_grid_connect() {
+    let mut grid = Grid::new(3, 3);
+    grid.connect(0, 1);
+    assert_eq!(grid.connected(0, 1), true);
+    assert_eq!(grid.connected(0, 2), false);
+    assert_eq!(*grid.fields.get(0, 1).unwrap(), FIELDS[0]);
+    assert_eq!(grid.is_dead_end(0), true);
+    let mut even = Grid::new(4, 3);
+    even.connect(0, 1);
+    assert_eq!(*even.fields.get(0, 2).unwrap(), FIELDS[0]);
+    assert_eq!(*even.fields.get(0, 3).unwrap(), FIELDS[0]);
+    assert_eq!(even.connected(0, 1), true);
+}
+
+#[test]
+fn test_find_root() {
+    let mut parents = vec![1, 2, 2, 3];
+    assert_eq!(find_root(&mut parents, 0), 2);
+    assert_eq!(parents, vec![2, 2, 2, 3]);
+}
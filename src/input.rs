@@ -0,0 +1,282 @@
+use crate::project_errors::{KeyBindingsError, InputError};
+use crate::direction::Direction;
+use crate::{Position, SAVE_COMMAND, QUIT_COMMAND, PATH_COMMAND, USE_COMMAND};
+use std::io::BufRead;
+use std::str::FromStr;
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+
+/// What a player can do with a single keypress on their turn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Move(Direction),
+    /// Asks for a whole path or a target field instead of a single step.
+    Path,
+    /// Asks for an item from the inventory to use.
+    Use,
+    Save,
+    Quit,
+}
+
+/// Names of the commands, as written in key binding files.
+pub const COMMANDS: [&str; 8] = ["up", "down", "left", "right", PATH_COMMAND, USE_COMMAND, SAVE_COMMAND, QUIT_COMMAND];
+
+/// Maps the keys to commands.
+/// Key bindings are read from a config file with one `command = key, key, ...` line per command
+/// (lines starting with '#' are comments). The keys of a listed command replace its default keys.
+/// Keys are single characters or the names `Up`, `Down`, `Left`, `Right`, `Esc`, `Enter`, `Space`,
+/// `Tab`, `Backspace`, `F1`-`F12` and `Ctrl-<char>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    bindings: Vec<(Key, Command)>,
+}
+
+impl Command {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Move(direction) => return direction.name(),
+            Command::Path => return PATH_COMMAND,
+            Command::Use => return USE_COMMAND,
+            Command::Save => return SAVE_COMMAND,
+            Command::Quit => return QUIT_COMMAND,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Command> {
+        for direction in Direction::ALL.iter() {
+            if direction.name() == name {
+                return Some(Command::Move(*direction));
+            }
+        }
+        match name {
+            PATH_COMMAND => return Some(Command::Path),
+            USE_COMMAND => return Some(Command::Use),
+            SAVE_COMMAND => return Some(Command::Save),
+            QUIT_COMMAND => return Some(Command::Quit),
+            _ => return None,
+        }
+    }
+}
+
+/// Arrow keys, WASD and vi-style hjkl move, P asks for a path, U uses an item, Ctrl-S saves the game, Esc, Ctrl-C and Ctrl-D quit.
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        let mut bindings = KeyBindings{ bindings: Vec::new() };
+        bindings.set(Command::Move(Direction::Up), &[Key::Up, Key::Char('w'), Key::Char('k')]);
+        bindings.set(Command::Move(Direction::Down), &[Key::Down, Key::Char('s'), Key::Char('j')]);
+        bindings.set(Command::Move(Direction::Left), &[Key::Left, Key::Char('a'), Key::Char('h')]);
+        bindings.set(Command::Move(Direction::Right), &[Key::Right, Key::Char('d'), Key::Char('l')]);
+        bindings.set(Command::Path, &[Key::Char('p')]);
+        bindings.set(Command::Use, &[Key::Char('u')]);
+        bindings.set(Command::Save, &[Key::Ctrl('s')]);
+        bindings.set(Command::Quit, &[Key::Esc, Key::Ctrl('c'), Key::Ctrl('d')]);
+        return bindings;
+    }
+}
+
+/// Constructors
+impl KeyBindings {
+    /// Loads the key bindings from a config file. Commands missing in it keep their default keys.
+    pub fn load(path: &str) -> Result<KeyBindings, KeyBindingsError> {
+        let content = std::fs::read_to_string(path)?;
+        return content.parse::<KeyBindings>();
+    }
+}
+
+/// Class methods
+impl KeyBindings {
+    /// Returns the command of a key, if it is bound.
+    pub fn command(&self, key: Key) -> Option<Command> {
+        for (bound, command) in self.bindings.iter() {
+            if *bound == key {
+                return Some(*command);
+            }
+        }
+        return None;
+    }
+
+    /// Returns the keys of a command.
+    pub fn keys(&self, command: Command) -> Vec<Key> {
+        return self.bindings.iter().filter(|(_, bound)| *bound == command).map(|(key, _)| *key).collect();
+    }
+
+    /// Returns the names of the keys of a command, e.g. "Up/w/k".
+    pub fn describe(&self, command: Command) -> String {
+        return self.keys(command).into_iter().map(key_name).collect::<Vec<String>>().join("/");
+    }
+
+    /// Replaces the keys of a command. The keys are taken from the commands they were bound to.
+    pub fn set(&mut self, command: Command, keys: &[Key]) {
+        self.bindings.retain(|(key, bound)| *bound != command && !keys.contains(key));
+        for key in keys {
+            self.bindings.push((*key, command));
+        }
+    }
+}
+
+impl FromStr for KeyBindings {
+    type Err = KeyBindingsError;
+
+    fn from_str(content: &str) -> Result<KeyBindings, KeyBindingsError> {
+        let mut bindings = KeyBindings::default();
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            let (name, value) = match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) => (name.trim(), value),
+                _ => return Err(KeyBindingsError::InvalidLine(i + 1, "expected `command = key, key, ...`".to_string())),
+            };
+            let command = match Command::from_name(name) {
+                Some(command) => command,
+                None => return Err(KeyBindingsError::InvalidLine(i + 1, format!("unknown command `{}`", name))),
+            };
+            let mut keys: Vec<Key> = Vec::new();
+            for key in value.split(',').map(|key| key.trim()).filter(|key| !key.is_empty()) {
+                match parse_key(key) {
+                    Some(key) => keys.push(key),
+                    None => return Err(KeyBindingsError::InvalidLine(i + 1, format!("unknown key `{}`", key))),
+                }
+            }
+            if keys.is_empty() {
+                return Err(KeyBindingsError::InvalidLine(i + 1, format!("no keys for `{}`", name)));
+            }
+            bindings.set(command, &keys);
+        }
+        for name in COMMANDS.iter() {
+            if bindings.keys(Command::from_name(name).unwrap()).is_empty() {
+                return Err(KeyBindingsError::Invalid(format!("`{}` has no keys left", name)));
+            }
+        }
+        return Ok(bindings);
+    }
+}
+
+/// Parses the name of a key, e.g. `w`, `Up` or `Ctrl-s`.
+pub fn parse_key(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    if let (Some(symbol), None) = (chars.next(), chars.next()) {
+        return Some(Key::Char(symbol));
+    }
+    match name.to_lowercase().as_str() {
+        "up" => return Some(Key::Up),
+        "down" => return Some(Key::Down),
+        "left" => return Some(Key::Left),
+        "right" => return Some(Key::Right),
+        "esc" => return Some(Key::Esc),
+        "enter" => return Some(Key::Char('\n')),
+        "space" => return Some(Key::Char(' ')),
+        "tab" => return Some(Key::Char('\t')),
+        "backspace" => return Some(Key::Backspace),
+        _ => (),
+    }
+    let lowercase = name.to_lowercase();
+    if lowercase.starts_with("ctrl-") && name.chars().count() == 6 {
+        return Some(Key::Ctrl(lowercase.chars().last().unwrap()));
+    }
+    if let Some(Ok(number)) = lowercase.strip_prefix('f').map(|number| number.parse::<u8>()) {
+        if (1..=12).contains(&number) {
+            return Some(Key::F(number));
+        }
+    }
+    return None;
+}
+
+/// Returns the name of a key, as `parse_key` reads it.
+pub fn key_name(key: Key) -> String {
+    match key {
+        Key::Up => return "Up".to_string(),
+        Key::Down => return "Down".to_string(),
+        Key::Left => return "Left".to_string(),
+        Key::Right => return "Right".to_string(),
+        Key::Esc => return "Esc".to_string(),
+        Key::Char('\n') => return "Enter".to_string(),
+        Key::Char(' ') => return "Space".to_string(),
+        Key::Char('\t') => return "Tab".to_string(),
+        Key::Backspace => return "Backspace".to_string(),
+        Key::Char(symbol) => return symbol.to_string(),
+        Key::Ctrl(symbol) => return format!("Ctrl-{}", symbol),
+        Key::F(number) => return format!("F{}", number),
+        other => return format!("{:?}", other),
+    }
+}
+
+/// Waits for a key with a command, without the player having to press Enter.
+/// The terminal is switched to raw mode only while waiting and restored before returning.
+/// Without a terminal the commands are read line by line - a key, a command name or anything else
+/// a direction is parsed from per line.
+/// The end of the input quits.
+pub fn read_command(bindings: &KeyBindings) -> Command {
+    let _raw = match std::io::stdout().into_raw_mode() {
+        Ok(raw) => raw,
+        Err(_) => return read_command_line(bindings),
+    };
+    for key in std::io::stdin().keys() {
+        match key {
+            Ok(key) => {
+                if let Some(command) = bindings.command(key) {
+                    return command;
+                }
+            },
+            Err(_) => break,
+        }
+    }
+    return Command::Quit;
+}
+
+fn read_command_line(bindings: &KeyBindings) -> Command {
+    loop {
+        let input = match read_line() {
+            Ok(input) => input,
+            Err(_) => return Command::Quit,
+        };
+        let input = input.as_str();
+        if let Some(command) = parse_key(input).and_then(|key| bindings.command(key)).or_else(|| Command::from_name(input)) {
+            return command;
+        }
+        if let Ok(direction) = input.parse::<Direction>() {
+            return Command::Move(direction);
+        }
+    }
+}
+
+/// Reads a line from the standard input. See `read_line_from`.
+pub fn read_line() -> Result<String, InputError> {
+    let stdin = std::io::stdin();
+    let mut reader = stdin.lock();
+    return read_line_from(&mut reader);
+}
+
+/// Reads a line without the surrounding whitespace and the line break (`\n` or `\r\n`).
+/// Returns `InputError::Eof` when there is nothing more to read.
+pub fn read_line_from<R: BufRead>(reader: &mut R) -> Result<String, InputError> {
+    let mut line = String::new();
+    if BufRead::read_line(reader, &mut line)? == 0 {
+        return Err(InputError::Eof);
+    }
+    return Ok(line.trim().to_string());
+}
+
+/// Parses a number between `min` and `max`, both included.
+pub fn parse_number(input: &str, min: usize, max: usize) -> Result<usize, InputError> {
+    match input.trim().parse::<usize>() {
+        Ok(number) if (min..=max).contains(&number) => return Ok(number),
+        Ok(_) => return Err(InputError::OutOfRange { min: min, max: max }),
+        Err(_) => return Err(InputError::NotANumber(input.trim().to_string())),
+    }
+}
+
+/// Parses a field entered by a player as `row,column` or `row column`, counting from 1.
+pub fn parse_field(input: &str) -> Option<Position> {
+    let numbers: Vec<&str> = input.split(|c: char| c.is_whitespace() || c == ',').filter(|part| !part.is_empty()).collect();
+    if numbers.len() != 2 {
+        return None;
+    }
+    match (numbers[0].parse::<usize>(), numbers[1].parse::<usize>()) {
+        (Ok(row), Ok(column)) if row > 0 && column > 0 => return Some(Position(row - 1, column - 1)),
+        _ => return None,
+    }
+}
@@ -0,0 +1,91 @@
+use crate::project_errors::GameError;
+use termion::color;
+use std::fmt;
+use std::str::FromStr;
+
+/// Something a player can pick up in the labyrinth by walking on its field.
+/// Picked items go to the player's inventory, see `Player::items`.
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+pub enum Item {
+    /// Opens the portal when the `locked_portal` rule is on.
+    Key,
+    /// Worth `treasure_score` points as soon as it is picked up.
+    Treasure,
+    /// Gives `POTION_MOVES` more moves when it is used.
+    Potion,
+    /// Saves the player from being killed in a battle once. It breaks by itself.
+    Shield,
+    /// Shows the way to the portal when it is used. It isn't used up.
+    Compass,
+}
+
+impl Item {
+    pub const ALL: [Item; 5] = [Item::Key, Item::Treasure, Item::Potion, Item::Shield, Item::Compass];
+
+    /// The items scattered by the `items` rule. Keys are scattered by the `locked_portal` rule instead.
+    pub const SCATTERED: [Item; 4] = [Item::Treasure, Item::Potion, Item::Shield, Item::Compass];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Item::Key => return "key",
+            Item::Treasure => return "treasure",
+            Item::Potion => return "potion",
+            Item::Shield => return "shield",
+            Item::Compass => return "compass",
+        }
+    }
+
+    /// Returns the symbol of the item on the map.
+    pub fn symbol(&self) -> char {
+        match self {
+            Item::Key => return '&',
+            Item::Treasure => return '$',
+            Item::Potion => return '!',
+            Item::Shield => return '%',
+            Item::Compass => return '*',
+        }
+    }
+
+    /// Returns the item with this symbol on the map, or None if the symbol isn't an item.
+    pub fn from_symbol(symbol: char) -> Option<Item> {
+        return Item::ALL.iter().cloned().find(|item| item.symbol() == symbol);
+    }
+
+    /// Checks if a player can use the item on their turn. The other items work by themselves.
+    pub fn is_usable(&self) -> bool {
+        return *self == Item::Potion || *self == Item::Compass;
+    }
+
+    /// Returns the terminal color of the item's symbol.
+    pub fn color(&self) -> String {
+        match self {
+            Item::Key => return color::Fg(color::Yellow).to_string(),
+            Item::Treasure => return color::Fg(color::LightYellow).to_string(),
+            Item::Potion => return color::Fg(color::LightMagenta).to_string(),
+            Item::Shield => return color::Fg(color::LightWhite).to_string(),
+            Item::Compass => return color::Fg(color::Cyan).to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Parses an item entered by a player: its name (`potion`) or its symbol on the map (`!`).
+/// Case and surrounding whitespace are ignored.
+impl FromStr for Item {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Item, GameError> {
+        let input = input.trim().to_lowercase();
+        for item in Item::ALL.iter() {
+            if item.name() == input || item.symbol().to_string() == input {
+                return Ok(*item);
+            }
+        }
+        return Err(GameError::InvalidItem);
+    }
+}
@@ -3,26 +3,77 @@ pub mod player;
 pub mod map;
 pub mod utills;
 pub mod controllers;
+pub mod save;
+pub mod generators;
+pub mod rules;
+pub mod options;
+pub mod ai;
+pub mod engine;
+pub mod visibility;
+pub mod replay;
+pub mod network;
+pub mod input;
+pub mod direction;
+pub mod pathfinding;
+pub mod spawns;
+pub mod items;
+pub mod tiles;
+pub mod modes;
+pub mod random;
 extern crate termion;
 
 pub const MIN_PLAYERS: usize = 2;
+/// The default limit of the rules, see `GameRules::max_players`.
 pub const MAX_PLAYERS: usize = 4;
-pub const PLAYERCODES: [char; (MAX_PLAYERS+1) as usize] = [' ', '1', '2', '3', '4'];
+/// The most players a game can have, one for every symbol in `PLAYERCODES`.
+pub const PLAYER_LIMIT: usize = 34;
+/// The players' symbols on the map, by their codes. Code 0 is nobody.
+/// There is no 'O', it would look like the portal.
+pub const PLAYERCODES: [char; PLAYER_LIMIT + 1] = [' ', '1', '2', '3', '4', '5', '6', '7', '8', '9',
+    'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z'];
 pub const FIELDS: [char; 3] = ['.', '#', '0'];
 pub const MAP_SIZE: usize = 10;
 pub const BATTLE_SCORE: u32 = 15;
 pub const PORTAL_SCORE: u32 = 50;
+pub const DICE_SIDES: usize = 6;
+/// Combat stats the players start with. They are only used when the combat rule is on,
+/// otherwise the attacked player is always killed.
+pub const HEALTH: u32 = 10;
+pub const ATTACK: u32 = 2;
+pub const DEFENSE: u32 = 1;
+/// Points for picking up a treasure.
+pub const TREASURE_SCORE: u32 = 10;
+/// Moves a potion gives.
+pub const POTION_MOVES: usize = 3;
+/// Points that win the game in the `points` mode.
+pub const TARGET_SCORE: u32 = 100;
+/// Rounds played in the `rounds` mode.
+pub const ROUNDS: usize = 20;
+/// Minutes played in the `timed` mode.
+pub const TIME_LIMIT: usize = 15;
+/// How far the players see. 0 turns the fog of war off and shows everybody the whole map.
+pub const VIEW_RADIUS: usize = 0;
+/// Seconds a network player has for a move. A player who doesn't answer in time loses the rest of the turn.
+pub const MOVE_TIMEOUT: u64 = 60;
+/// Shown instead of the fields a player hasn't explored yet.
+pub const HIDDEN_FIELD: char = '?';
+pub const SAVE_COMMAND: &str = "save";
+pub const QUIT_COMMAND: &str = "quit";
+pub const PATH_COMMAND: &str = "path";
+pub const USE_COMMAND: &str = "use";
+pub const DEFAULT_SAVE_FILE: &str = "labyrinth.save";
+pub const MAP_FLAG: &str = "--map";
+pub const RULES_FLAG: &str = "--rules";
+pub const RECORD_FLAG: &str = "--record";
+pub const REPLAY_FLAG: &str = "--replay";
+pub const HOST_FLAG: &str = "--host";
+pub const JOIN_FLAG: &str = "--join";
+pub const KEYS_FLAG: &str = "--keys";
 
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
 pub struct Position(pub usize, pub usize);
 
-pub mod directions{
-    pub static DOWN: &str = "s";
-    pub static UP: &str = "w";
-    pub static LEFT: &str = "a";
-    pub static RIGHT: &str = "d";
-}
 
 pub mod project_errors{
     use std::fmt;
@@ -35,12 +86,23 @@ pub mod project_errors{
         InvalidDirection,
         AnotherPlayer,
         Portal,
+        InvalidAction,
+        PathTooLong,
+        InvalidItem,
+        NoItem,
+        NotUsable,
+        PortalLocked,
+        DoorLocked,
+        OneWay,
+        NotEnoughMoves,
+        Teammate,
+        NoRoom,
     }
     
     impl fmt::Display for GameError {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             use std::error::Error;
-            write!(f, "{}: {} ", self, self.description())
+            write!(f, "{}", self.description())
         }
     }
 
@@ -53,8 +115,229 @@ pub mod project_errors{
                 GameError::InvalidField => "Invalid field! You can't reach it.\n",
                 GameError::AnotherPlayer => "Battle!",
                 GameError::Portal => "Portal has been found!",
+                GameError::InvalidAction => "This action is not allowed now!\n",
+                GameError::PathTooLong => "The path is longer than your moves!\n",
+                GameError::InvalidItem => "Invalid item! Allowed: key, treasure, potion, shield, compass.\n",
+                GameError::NoItem => "You don't have this item!\n",
+                GameError::NotUsable => "This item can't be used, it works by itself!\n",
+                GameError::PortalLocked => "The portal is locked! Find a key first.\n",
+                GameError::DoorLocked => "The door is locked! Find a key first.\n",
+                GameError::OneWay => "You can't walk against the arrow!\n",
+                GameError::NotEnoughMoves => "You need two moves to swim through the water!\n",
+                GameError::Teammate => "You can't attack your teammate!\n",
+                GameError::NoRoom => "There isn't enough room on the map for all the players and the portal!\n",
+            }
+        }
+    }
+
+    /// Errors that may occur while saving or loading a game.
+    #[derive(Debug)]
+    pub enum SaveError {
+        Io(std::io::Error),
+        UnsupportedVersion(String),
+        InvalidFormat(usize, String),
+    }
+
+    impl fmt::Display for SaveError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match &self {
+                SaveError::Io(e) => write!(f, "Could not access the save file: {}\n", e),
+                SaveError::UnsupportedVersion(version) => write!(f, "Unsupported save file version: {}\n", version),
+                SaveError::InvalidFormat(line, message) => write!(f, "Invalid save file (line {}): {}\n", line, message),
+            }
+        }
+    }
+
+    impl std::error::Error for SaveError {}
+
+    impl From<std::io::Error> for SaveError {
+        fn from(error: std::io::Error) -> SaveError {
+            return SaveError::Io(error);
+        }
+    }
+
+    /// Errors that may occur while reading the game rules.
+    #[derive(Debug)]
+    pub enum RulesError {
+        Io(std::io::Error),
+        InvalidLine(usize, String),
+        InvalidFlag(String),
+        Invalid(String),
+    }
+
+    impl fmt::Display for RulesError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match &self {
+                RulesError::Io(e) => write!(f, "Could not read the rules file: {}\n", e),
+                RulesError::InvalidLine(line, message) => write!(f, "Invalid rules file (line {}): {}\n", line, message),
+                RulesError::InvalidFlag(message) => write!(f, "Invalid command line: {}\n", message),
+                RulesError::Invalid(message) => write!(f, "Invalid rules: {}\n", message),
+            }
+        }
+    }
+
+    impl std::error::Error for RulesError {}
+
+    impl From<std::io::Error> for RulesError {
+        fn from(error: std::io::Error) -> RulesError {
+            return RulesError::Io(error);
+        }
+    }
+
+    /// Errors that may occur while reading a hand-authored map.
+    /// Lines and columns start from 1.
+    #[derive(Debug)]
+    pub enum MapError {
+        Io(std::io::Error),
+        Parse { line: usize, column: usize, message: String },
+    }
+
+    impl MapError {
+        pub fn parse(line: usize, column: usize, message: &str) -> MapError {
+            return MapError::Parse { line: line, column: column, message: message.to_string() };
+        }
+    }
+
+    impl fmt::Display for MapError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match &self {
+                MapError::Io(e) => write!(f, "Could not read the map file: {}\n", e),
+                MapError::Parse { line, column, message } => write!(f, "Invalid map (line {}, column {}): {}\n", line, column, message),
+            }
+        }
+    }
+
+    impl std::error::Error for MapError {}
+
+    impl From<std::io::Error> for MapError {
+        fn from(error: std::io::Error) -> MapError {
+            return MapError::Io(error);
+        }
+    }
+
+    /// Errors that may occur while recording or playing a replay.
+    #[derive(Debug)]
+    pub enum ReplayError {
+        Io(std::io::Error),
+        Save(SaveError),
+        InvalidLine(usize, String),
+    }
+
+    impl fmt::Display for ReplayError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match &self {
+                ReplayError::Io(e) => write!(f, "Could not access the replay file: {}\n", e),
+                ReplayError::Save(e) => write!(f, "Invalid replay, the initial state is broken: {}", e),
+                ReplayError::InvalidLine(line, message) => write!(f, "Invalid replay (line {}): {}\n", line, message),
             }
         }
     }
 
+    impl std::error::Error for ReplayError {}
+
+    impl From<std::io::Error> for ReplayError {
+        fn from(error: std::io::Error) -> ReplayError {
+            return ReplayError::Io(error);
+        }
+    }
+
+    impl From<SaveError> for ReplayError {
+        fn from(error: SaveError) -> ReplayError {
+            return ReplayError::Save(error);
+        }
+    }
+
+    /// Errors that may occur in a networked game.
+    #[derive(Debug)]
+    pub enum NetworkError {
+        Io(std::io::Error),
+        Disconnected,
+        /// Nothing was received before the read timeout.
+        Timeout,
+        Protocol(String),
+        /// The game couldn't start, e.g. the map has no room for all the players.
+        Game(GameError),
+    }
+
+    impl fmt::Display for NetworkError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match &self {
+                NetworkError::Io(e) => write!(f, "Connection error: {}\n", e),
+                NetworkError::Disconnected => write!(f, "The other side has disconnected.\n"),
+                NetworkError::Timeout => write!(f, "The other side didn't answer in time.\n"),
+                NetworkError::Protocol(message) => write!(f, "Unexpected message: {}\n", message),
+                NetworkError::Game(e) => write!(f, "{}", e),
+            }
+        }
+    }
+
+    impl std::error::Error for NetworkError {}
+
+    impl From<std::io::Error> for NetworkError {
+        fn from(error: std::io::Error) -> NetworkError {
+            return NetworkError::Io(error);
+        }
+    }
+
+    impl From<GameError> for NetworkError {
+        fn from(error: GameError) -> NetworkError {
+            return NetworkError::Game(error);
+        }
+    }
+
+    /// Errors that may occur while reading what the players type.
+    #[derive(Debug)]
+    pub enum InputError {
+        Io(std::io::Error),
+        /// The input has ended, e.g. the player pressed Ctrl-D.
+        Eof,
+        NotANumber(String),
+        OutOfRange { min: usize, max: usize },
+    }
+
+    impl fmt::Display for InputError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match &self {
+                InputError::Io(e) => write!(f, "Could not read the input: {}\n", e),
+                InputError::Eof => write!(f, "The input has ended.\n"),
+                InputError::NotANumber(input) => write!(f, "`{}` is not a number.\n", input),
+                InputError::OutOfRange { min, max } => write!(f, "Enter a number between {} and {}.\n", min, max),
+            }
+        }
+    }
+
+    impl std::error::Error for InputError {}
+
+    impl From<std::io::Error> for InputError {
+        fn from(error: std::io::Error) -> InputError {
+            return InputError::Io(error);
+        }
+    }
+
+    /// Errors that may occur while reading the key bindings.
+    #[derive(Debug)]
+    pub enum KeyBindingsError {
+        Io(std::io::Error),
+        InvalidLine(usize, String),
+        Invalid(String),
+    }
+
+    impl fmt::Display for KeyBindingsError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match &self {
+                KeyBindingsError::Io(e) => write!(f, "Could not read the key bindings file: {}\n", e),
+                KeyBindingsError::InvalidLine(line, message) => write!(f, "Invalid key bindings file (line {}): {}\n", line, message),
+                KeyBindingsError::Invalid(message) => write!(f, "Invalid key bindings: {}\n", message),
+            }
+        }
+    }
+
+    impl std::error::Error for KeyBindingsError {}
+
+    impl From<std::io::Error> for KeyBindingsError {
+        fn from(error: std::io::Error) -> KeyBindingsError {
+            return KeyBindingsError::Io(error);
+        }
+    }
+
 }
@@ -1,10 +1,90 @@
 use project::*;
 use game::Game;
+use map::Map;
+use options::Options;
+use replay::Replay;
+use input::KeyBindings;
 
 
 fn main() {
-    let mut game = Game::init();
-    game.start();
-    while game.next_turn() {}
+    let args: Vec<String> = std::env::args().collect();
+    let options = match Options::parse(&args[1..]) {
+        Ok(options) => options,
+        Err(e) => {
+            eprint!("{}{}", e, Options::usage(&args[0]));
+            std::process::exit(1);
+        },
+    };
+    if let Some(path) = options.replay_file {
+        match Replay::load(&path) {
+            Ok(replay) => utills::watch_replay(&replay),
+            Err(e) => {
+                eprint!("{}", e);
+                std::process::exit(1);
+            },
+        }
+        return;
+    }
+    let key_bindings = match options.keys_file {
+        Some(path) => match KeyBindings::load(&path) {
+            Ok(key_bindings) => key_bindings,
+            Err(e) => {
+                eprint!("{}", e);
+                std::process::exit(1);
+            },
+        },
+        None => KeyBindings::default(),
+    };
+    if let Some(address) = options.join {
+        if let Err(e) = utills::join_game(&address, &key_bindings) {
+            eprint!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let map = match options.map_file {
+        Some(path) => match Map::load(&path) {
+            Ok(map) => Some(map),
+            Err(e) => {
+                eprint!("{}", e);
+                std::process::exit(1);
+            },
+        },
+        None => None,
+    };
+    let mut rules = options.rules;
+    if let Some(map) = &map {
+        rules.map_width = map.width;
+        rules.map_height = map.height;
+    }
+    if let Err(e) = rules.validate() {
+        eprint!("{}", e);
+        std::process::exit(1);
+    }
+
+    if let Some(address) = options.host {
+        match utills::host_game(&address, rules, map) {
+            Ok(ranklist) => print!("{}", ranklist),
+            Err(e) => {
+                eprint!("{}", e);
+                std::process::exit(1);
+            },
+        }
+        return;
+    }
+
+    let mut game = Game::init(rules, map, key_bindings);
+    if let Err(e) = game.start() {
+        utills::print_error(e.to_string());
+        std::process::exit(1);
+    }
+    if let Some(path) = options.record_file {
+        if let Err(e) = game.record(&path) {
+            utills::print_error(e.to_string());
+            utills::pause();
+        }
+    }
+    let mut clock = std::time::Instant::now();
+    while game.next_turn(&mut clock) {}
     Game::end(game);
-}
\ No newline at end of file
+}
@@ -1,53 +1,321 @@
 use simple_matrix::Matrix;
+use rand::RngCore;
+use crate::generators::{MazeGenerator, RecursiveBacktracker};
 use crate::{Position, PLAYERCODES, FIELDS};
-use crate::project_errors::GameError;
+use crate::direction::Direction;
+use crate::project_errors::{GameError, MapError};
 use crate::player::*;
+use crate::items::Item;
+use crate::tiles::Tile;
+use std::str::FromStr;
 
 
+/// Something on a field of the map, over its tile. The occupants come and go, the tiles stay.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Occupant {
+    /// A player, by its code.
+    Player(usize),
+    /// An item, which the player walking on the field picks up.
+    Item(Item),
+}
+
+impl Occupant {
+    /// Returns the symbol of the occupant on the map.
+    pub fn symbol(&self) -> char {
+        match self {
+            Occupant::Player(code) => return PLAYERCODES[*code],
+            Occupant::Item(item) => return item.symbol(),
+        }
+    }
+
+    /// Returns the occupant with this symbol on the map, or None if the symbol isn't a player's code or an item.
+    pub fn from_symbol(symbol: char) -> Option<Occupant> {
+        if let Some(item) = Item::from_symbol(symbol) {
+            return Some(Occupant::Item(item));
+        }
+        if PLAYERCODES[1..].contains(&symbol) {
+            return Some(Occupant::Player(get_player(symbol)));
+        }
+        return None;
+    }
+}
+
 /// Map class - contains the labyrinth map
-/// If a field value is:
+/// The map has two layers: the terrain, a `Tile` on every field, and the occupants standing or lying on the tiles.
+/// The tiles are shown with their symbols:
 ///    '.' - it is empty and the players can move on it.
 ///    '#' - this is a wall.
-///    '0' - this is a portal. Walking through it finishes the game.
+///    '0' - this is a portal. Walking through it takes the player out of the labyrinth.
+/// The other tiles (water, mud, arrows, teleport pads, breakable walls and doors) have their own symbols, see `Tile`.
+/// An occupant hides the tile under it: a player is shown with its code and an item with its symbol (see `Item::symbol`).
+/// Hand-authored maps may also mark where each player starts with the player's code.
+/// A field's coordinates are its row (from 0 to height - 1) and its column (from 0 to width - 1).
 pub struct Map {
-    pub size: usize,
-    map: Matrix<char>
+    pub width: usize,
+    pub height: usize,
+    terrain: Vec<Vec<Tile>>,
+    occupants: Vec<Vec<Option<Occupant>>>,
+    spawns: Vec<Option<Position>>,
+    /// The teleport pads row by row, kept up to date by `set_tile`.
+    teleports: Vec<Position>
 }
 
 /// Constructors
 impl Map{
-    pub fn new(size: usize) -> Map {
-        Map{
-            size: size,
-            map: generate_map(size),
+    /// Generates a random labyrinth with the default generator, drawing every random value from `rng`.
+    pub fn new<R: RngCore>(width: usize, height: usize, rng: &mut R) -> Map {
+        return Map::with_generator(width, height, &RecursiveBacktracker, rng);
+    }
+
+    /// Generates a random labyrinth with the given maze generator.
+    pub fn with_generator<G: MazeGenerator + ?Sized, R: RngCore>(width: usize, height: usize, generator: &G, rng: &mut R) -> Map {
+        let map = Map::from_symbols(&generator.generate(width, height, rng), width, height);
+        debug_assert!(map.is_connected(), "the generator left some fields closed off");
+        return map;
+    }
+
+    /// Constructs a map from an integer 2D array, using the values as indexes in FIELDS.
+    pub fn from_matrix(matrix: &[&[i8]]) -> Map {
+        let map = from_array(matrix);
+        return Map::from_symbols(&map, matrix[0].len(), matrix.len());
+    }
+
+    /// Constructs a map from rows of field symbols, including players and portals.
+    /// The fields with players and items are floor.
+    pub(crate) fn from_fields(fields: &[Vec<char>]) -> Map {
+        let mut map: Matrix<char> = Matrix::new(fields.len(), fields[0].len());
+        for (i, row) in fields.iter().enumerate() {
+            for (j, symbol) in row.iter().enumerate() {
+                map.set(i, j, *symbol);
+            }
         }
+        return Map::from_symbols(&map, fields[0].len(), fields.len());
+    }
+
+    /// Splits a matrix of field symbols into the terrain and the occupants.
+    /// The fields with players and items are floor, unknown symbols too.
+    fn from_symbols(map: &Matrix<char>, width: usize, height: usize) -> Map {
+        let symbol = |x: usize, y: usize| *map.get(x, y).unwrap();
+        let mut result = Map{
+            width: width,
+            height: height,
+            terrain: (0..height).map(|x| (0..width)
+                .map(|y| Tile::from_symbol(symbol(x, y)).unwrap_or(Tile::Floor)).collect()).collect(),
+            occupants: (0..height).map(|x| (0..width)
+                .map(|y| Occupant::from_symbol(symbol(x, y))).collect()).collect(),
+            spawns: vec![None; PLAYERCODES.len()],
+            teleports: Vec::new(),
+        };
+        result.find_teleports();
+        return result;
     }
 
-    pub fn from_matrix(size: usize, matrix: &[&[i8]]) -> Map {
-        Map{
-            size: size,
-            map: from_array(matrix),
+    /// Loads a hand-authored map from a text file. See `Map::from_str` for the format.
+    pub fn load(path: &str) -> Result<Map, MapError> {
+        let content = std::fs::read_to_string(path)?;
+        return content.parse::<Map>();
+    }
+}
+
+/// Parses a hand-authored map.
+/// Every line is a row of the map, written with the tiles' symbols ('.', '#', '0' and the others, see `Tile`) and the items' symbols
+/// and optionally separated by spaces, so the output of `Map::display` is a valid map.
+/// A player's symbol ('1' to '9', then 'A' to 'Z' without 'O', see `PLAYERCODES`) marks the empty field the player starts on.
+/// Empty lines and lines starting with ';' are ignored.
+/// The players' starting fields and the portal must be reachable from each other.
+impl FromStr for Map {
+    type Err = MapError;
+
+    fn from_str(content: &str) -> Result<Map, MapError> {
+        let mut rows: Vec<Vec<char>> = Vec::new();
+        let mut spawns: Vec<Option<Position>> = vec![None; PLAYERCODES.len()];
+        let mut portal: Option<Position> = None;
+        // Where the starting fields and the portal are written in the file, to report them.
+        let mut marks: Vec<(Position, char, usize, usize)> = Vec::new();
+
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() || line.trim_start().starts_with(';') {
+                continue;
+            }
+            let x = rows.len();
+            let mut row: Vec<char> = Vec::new();
+            for (j, symbol) in line.chars().enumerate() {
+                if symbol.is_whitespace() {
+                    continue;
+                }
+                if !rows.is_empty() && row.len() == rows[0].len() {
+                    return Err(MapError::parse(i + 1, j + 1, &format!("expected {} fields in a row", rows[0].len())));
+                }
+                let position = Position(x, row.len());
+                if symbol == FIELDS[2] {
+                    if portal.is_some() {
+                        return Err(MapError::parse(i + 1, j + 1, "the map can have only one portal"));
+                    }
+                    portal = Some(position);
+                    marks.push((position, symbol, i + 1, j + 1));
+                    row.push(symbol);
+                } else if Tile::from_symbol(symbol).is_some() || Item::from_symbol(symbol).is_some() {
+                    row.push(symbol);
+                } else if PLAYERCODES[1..].contains(&symbol) {
+                    let code = get_player(symbol);
+                    if spawns[code].is_some() {
+                        return Err(MapError::parse(i + 1, j + 1, &format!("player {} already has a starting field", code)));
+                    }
+                    spawns[code] = Some(position);
+                    marks.push((position, symbol, i + 1, j + 1));
+                    row.push(FIELDS[0]);
+                } else {
+                    return Err(MapError::parse(i + 1, j + 1, &format!("unknown field `{}`", symbol)));
+                }
+            }
+            if !rows.is_empty() && row.len() != rows[0].len() {
+                return Err(MapError::parse(i + 1, line.chars().count() + 1,
+                    &format!("expected {} fields, found {}", rows[0].len(), row.len())));
+            }
+            rows.push(row);
+        }
+
+        if rows.is_empty() {
+            return Err(MapError::parse(1, 1, "the map is empty"));
+        }
+
+        let mut map = Map::from_fields(&rows);
+        map.spawns = spawns;
+        if let Some((start, first, _, _)) = marks.first() {
+            let fields: Vec<Position> = marks.iter().map(|(position, _, _, _)| *position).collect();
+            if let Some(field) = map.unreachable(*start, &fields) {
+                let (_, symbol, line, column) = marks.iter().find(|(position, _, _, _)| *position == field).unwrap();
+                return Err(MapError::parse(*line, *column, &format!("`{}` can't be reached from `{}`", symbol, first)));
+            }
         }
+        return Ok(map);
     }
 }
 
 
 /// Class getters and setters
 impl Map{
+    /// Returns the symbol shown on a field: the occupant's, or the tile's if there is nobody and nothing on it.
     fn get(&self, x: usize, y: usize) -> char {
-        return *self.map.get(x, y).unwrap();
+        match self.occupants[x][y] {
+            Some(occupant) => return occupant.symbol(),
+            None => return self.terrain[x][y].symbol(),
+        }
     }
 
     pub fn get_field(&self, coordinates: Position) -> char {
         return self.get(coordinates.0, coordinates.1);
     }
 
+    /// Checks if a field is floor without anything on it.
     pub fn empty(&self, x: usize, y:usize) -> bool {
-        return self.get(x, y) == FIELDS[0];
+        return self.terrain[x][y] == Tile::Floor && self.occupants[x][y].is_none();
+    }
+
+    /// Returns the tile of a field, even if there is a player or an item on it.
+    pub fn tile(&self, coordinates: Position) -> Tile {
+        return self.terrain[coordinates.0][coordinates.1];
+    }
+
+    /// Changes the tile of a field. A player or an item on the field stays there.
+    pub fn set_tile(&mut self, coordinates: Position, tile: Tile) {
+        let old_tile = self.tile(coordinates);
+        self.terrain[coordinates.0][coordinates.1] = tile;
+        if old_tile == Tile::Teleport || tile == Tile::Teleport {
+            self.find_teleports();
+        }
     }
 
+    /// Returns the player or the item on a field, or None if there is nothing on its tile.
+    pub fn occupant(&self, coordinates: Position) -> Option<Occupant> {
+        return self.occupants[coordinates.0][coordinates.1];
+    }
+
+    /// Returns the occupants of the map with their fields, row by row.
+    pub fn occupants(&self) -> Vec<(Position, Occupant)> {
+        let mut result: Vec<(Position, Occupant)> = Vec::new();
+        for x in 0..self.height {
+            for y in 0..self.width {
+                if let Some(occupant) = self.occupants[x][y] {
+                    result.push((Position(x, y), occupant));
+                }
+            }
+        }
+        return result;
+    }
+
+    /// Returns the code of the player standing on a field, or None if there is none.
+    pub fn player_at(&self, coordinates: Position) -> Option<usize> {
+        match self.occupant(coordinates) {
+            Some(Occupant::Player(code)) => return Some(code),
+            _ => return None,
+        }
+    }
+
+    /// Returns the other teleport pad of a pair, or None if the field isn't a pad or the pad has no pair.
+    /// Pads are paired row by row: the first with the second, the third with the fourth and so on.
+    pub fn teleport_pair(&self, coordinates: Position) -> Option<Position> {
+        let index = self.teleports.iter().position(|pad| *pad == coordinates)?;
+        return self.teleports.get(index ^ 1).cloned();
+    }
+
+    /// Collects the teleport pads row by row, after the map is built or a pad is added or removed.
+    fn find_teleports(&mut self) {
+        self.teleports.clear();
+        for x in 0..self.height {
+            for y in 0..self.width {
+                if self.terrain[x][y] == Tile::Teleport {
+                    self.teleports.push(Position(x, y));
+                }
+            }
+        }
+    }
+
+    /// Returns the item lying on a field, or None if there is none.
+    pub fn item(&self, coordinates: Position) -> Option<Item> {
+        match self.occupant(coordinates) {
+            Some(Occupant::Item(item)) => return Some(item),
+            _ => return None,
+        }
+    }
+
+    /// Returns the items lying on the map with their fields, row by row.
+    pub fn items(&self) -> Vec<(Position, Item)> {
+        return self.occupants().into_iter().filter_map(|(position, occupant)| match occupant {
+            Occupant::Item(item) => Some((position, item)),
+            _ => None,
+        }).collect();
+    }
+
+    /// Returns the field a player starts on in a hand-authored map.
+    pub fn spawn_point(&self, player_code: usize) -> Option<Position> {
+        return *self.spawns.get(player_code).unwrap_or(&None);
+    }
+
+    /// Checks if there is a portal on the map.
+    pub fn has_portal(&self) -> bool {
+        return self.portal().is_some();
+    }
+
+    /// Returns the field of the portal or None if there is no portal on the map.
+    pub fn portal(&self) -> Option<Position> {
+        for x in 0..self.height {
+            for y in 0..self.width {
+                if self.terrain[x][y] == Tile::Portal {
+                    return Some(Position(x, y));
+                }
+            }
+        }
+        return None;
+    }
+
+    /// Removes whatever is on a field, leaving its tile.
     fn set_empty(&mut self, coordinates: Position) {
-        self.map.set(coordinates.0, coordinates.1, FIELDS[0]);
+        self.occupants[coordinates.0][coordinates.1] = None;
+    }
+
+    fn set_occupant(&mut self, coordinates: Position, occupant: Occupant) {
+        self.occupants[coordinates.0][coordinates.1] = Some(occupant);
     }
 }
 
@@ -56,17 +324,29 @@ impl  Map {
     /// Spawns a portal on the map. Returns an Error if the field is not empty.
     pub fn spawn_portal(&mut self, position: Position) -> Result<(), GameError> {
         if self.empty(position.0, position.1) {
-            self.map.set(position.0, position.1, FIELDS[2]);
+            self.set_tile(position, Tile::Portal);
             return Ok(());
         } else {
             return Err(GameError::InvalidCoordinates);
         }
     }
 
-    /// Spawns a player on the map. Returns an Error if the field is not empty.
-    pub fn spawn_player(&mut self, player: &mut Player, position: Position) -> Result<(), GameError> {
+    /// Puts an item on the map. Returns an Error if the field is not empty.
+    pub fn spawn_item(&mut self, position: Position, item: Item) -> Result<(), GameError> {
         if self.empty(position.0, position.1) {
-            self.map.set(position.0, position.1, PLAYERCODES[player.player_code]);
+            self.set_occupant(position, Occupant::Item(item));
+            return Ok(());
+        } else {
+            return Err(GameError::InvalidCoordinates);
+        }
+    }
+
+    /// Spawns a player on the map. Returns an Error if there is something on the field
+    /// or the player can't stand on its tile (a wall or the portal).
+    pub fn spawn_player(&mut self, player: &mut Player, position: Position) -> Result<(), GameError> {
+        let tile = self.tile(position);
+        if self.occupant(position).is_none() && tile.is_passable() && tile != Tile::BreakableWall && tile != Tile::Portal {
+            self.set_occupant(position, Occupant::Player(player.player_code));
             player.spawn(position);
             return Ok(());
         } else {
@@ -74,33 +354,75 @@ impl  Map {
         }
     }
 
+    /// Returns the neighbouring field in a direction, or None if it is outside the map.
+    pub fn neighbour(&self, position: Position, direction: Direction) -> Option<Position> {
+        match position.step(direction) {
+            Some(next) if next.0 < self.height && next.1 < self.width => return Some(next),
+            _ => return None,
+        }
+    }
+
     /// Tries to move a player in a certain direction. 
     /// Returns an Error if the move is invalid, there is another player in the new field (battle occurs)
     /// or there is a portal on the new field (end of the game).
-    pub fn move_player(&mut self, player: &mut Player, direction: &str) -> Result<(), GameError> {
-        match change_coordinates(player.coordinates.unwrap(), direction) {
-            Ok(Position(new_x, new_y)) => {
-                if new_x == self.size || new_y == self.size {
-                    return Err(GameError::Outside);
+    /// Walls (breakable ones too) block the way, arrows only let the player in in their direction
+    /// and doors only let players with a key in. The other effects of the tiles are up to the game.
+    /// An item on the new field is removed from the map, picking it up is up to the game.
+    pub fn move_player(&mut self, player: &mut Player, direction: Direction) -> Result<(), GameError> {
+        match self.neighbour(player.coordinates.unwrap(), direction) {
+            Some(Position(new_x, new_y)) => {
+                match self.tile(Position(new_x, new_y)) {
+                    Tile::Wall | Tile::BreakableWall => return Err(GameError::InvalidCoordinates),
+                    Tile::Portal => return Err(GameError::Portal),
+                    Tile::Door if !player.has_item(Item::Key) => return Err(GameError::DoorLocked),
+                    tile if !tile.can_enter(direction) => return Err(GameError::OneWay),
+                    _ => (),
                 }
-                if !self.empty(new_x, new_y) {
-                    if self.get(new_x as usize, new_y as usize) == FIELDS[2] {
-                        return Err(GameError::Portal);
-                    } else if PLAYERCODES.contains(&self.get(new_x as usize, new_y as usize)) {
-                        return Err(GameError::AnotherPlayer);
-                    } else {
-                        return Err(GameError::InvalidCoordinates);
-                    }
+                if self.player_at(Position(new_x, new_y)).is_some() {
+                    return Err(GameError::AnotherPlayer);
                 }
 
                 let coordinates: Position = player.coordinates.unwrap();
                 self.set_empty(coordinates);
                 player.set_move(direction);
-                self.map.set(new_x, new_y, PLAYERCODES[player.player_code]);
+                self.set_occupant(Position(new_x, new_y), Occupant::Player(player.player_code));
                 return Ok(());
             },
-            Err(e) => return Err(e),
+            None => return Err(GameError::Outside),
+        }
+    }
+
+    /// Moves a player to the other pad of its teleport pad's pair.
+    /// Returns an Error if the player isn't on a pad with a pair or the other pad isn't free.
+    pub fn teleport_player(&mut self, player: &mut Player) -> Result<Position, GameError> {
+        let coordinates: Position = player.coordinates.unwrap();
+        match self.teleport_pair(coordinates) {
+            Some(pair) if self.occupant(pair).is_none() => {
+                self.set_empty(coordinates);
+                player.spawn(pair);
+                self.set_occupant(pair, Occupant::Player(player.player_code));
+                return Ok(pair);
+            },
+            _ => return Err(GameError::InvalidField),
+        }
+    }
+
+    /// Swaps the fields of two players, e.g. teammates. Only the occupants move, the tiles stay.
+    pub fn swap_players(&mut self, first: &mut Player, second: &mut Player) {
+        let (first_field, second_field) = (first.coordinates.unwrap(), second.coordinates.unwrap());
+        self.set_occupant(first_field, Occupant::Player(second.player_code));
+        self.set_occupant(second_field, Occupant::Player(first.player_code));
+        first.spawn(second_field);
+        second.spawn(first_field);
+    }
+
+    /// Breaks a breakable wall, which becomes floor. Returns false if there is no breakable wall on the field.
+    pub fn break_wall(&mut self, position: Position) -> bool {
+        if self.tile(position) != Tile::BreakableWall {
+            return false;
         }
+        self.set_tile(position, Tile::Floor);
+        return true;
     }
 
     /// Emulates killing a player.
@@ -110,11 +432,11 @@ impl  Map {
         self.set_empty(coordinates);
     }
 
-    /// Returns a string representaion of the map.
+    /// Returns a string representaion of the map, every occupant shown over its tile.
     pub fn display(&self) -> String {
         let mut result: String = String::new();
-        for x in 0..self.size{
-            for y in 0..self.size{
+        for x in 0..self.height{
+            for y in 0..self.width{
                 result.push(self.get(x, y));
                 result.push(' ');
             }
@@ -124,25 +446,6 @@ impl  Map {
     }
 }
 
-fn generate_map(size: usize) -> Matrix<char> {
-    let mut map: Matrix<char> = generate_random_map(size);
-    while check_if_connected(size, map.clone()) == false {
-        map = generate_random_map(size)
-    }
-    return map;
-}
-
-/// Generates a random map.
-fn generate_random_map(size: usize) -> Matrix<char> {
-    let mut map: Matrix<char> = Matrix::new(size, size);
-    for i in 0..size{
-        for j in 0..size{
-            map.set(i as usize, j as usize, FIELDS[(rand::random::<i8>()%2).abs() as usize]);
-        }
-    }
-    return map;
-}
-
 /// Constructs a matrix from an integer 2D array.
 fn from_array(matrix: &[&[i8]]) -> Matrix<char> {
     let mut map: Matrix<char> = Matrix::new(matrix.len(), matrix[0].len());
@@ -155,10 +458,10 @@ fn from_array(matrix: &[&[i8]]) -> Matrix<char> {
 } 
 
 fn match_player(field_code: char) -> Result<usize, GameError> {
-    if FIELDS.contains(&field_code) || !PLAYERCODES.contains(&field_code){
-        return Err(GameError::InvalidField);
+    match PLAYERCODES[1..].iter().position(|code| *code == field_code) {
+        Some(index) => return Ok(index + 1),
+        None => return Err(GameError::InvalidField),
     }
-    return Ok(field_code.to_string().parse::<usize>().unwrap());
 }
 
 pub fn get_player(field: char) -> usize {
@@ -168,43 +471,6 @@ pub fn get_player(field: char) -> usize {
     }
 }
 
-fn check_if_connected(size: usize, matrix: Matrix<char>) -> bool {
-    let mut parts = 0;
-    let mut map: Matrix<char> = matrix;
-    for i in 0..size {
-        for j in 0..size {
-            if *map.get(i, j).unwrap() == FIELDS[0] {
-                traverse(size, &mut map, i, j);
-                parts += 1;
-            }
-        }
-    }
-
-    return parts == 1;
-}
-
-fn traverse (size: usize, map: &mut Matrix<char>, start_x: usize, start_y: usize) {
-    let mut queue: Vec<(usize, usize)> = vec![(start_x, start_y)];
-    while !queue.is_empty() {
-        let (x, y) = queue.remove(0);
-        map.set(x, y, FIELDS[1]);
-        if x > 0 && *map.get(x-1, y).unwrap() == FIELDS[0] {
-            queue.push((x-1, y));
-        }
-        if x < size-1 && *map.get(x+1, y).unwrap() == FIELDS[0] {
-            queue.push((x+1, y));
-        }
-        if y > 0 && *map.get(x, y-1).unwrap() == FIELDS[0] {
-            queue.push((x, y-1));
-        }
-        if y < size-1 && *map.get(x, y+1).unwrap() == FIELDS[0] {
-            queue.push((x, y+1));
-        }
-    }
-}
-
-
-
 // ----------------------------------------------------------------------------------
 // Private functions test
 
@@ -226,6 +492,10 @@ fn test_match_player() {
     assert_eq!(match_player('2'), Ok(2));
     assert_eq!(match_player('3'), Ok(3));
     assert_eq!(match_player('4'), Ok(4));
+    assert_eq!(match_player('9'), Ok(9));
+    assert_eq!(match_player('A'), Ok(10));
+    assert_eq!(match_player('P'), Ok(24));
+    assert_eq!(match_player('Z'), Ok(34));
 }
 
 #[test]
@@ -233,14 +503,17 @@ fn test_match_bad_player() {
     assert_eq!(match_player('.'), Err(GameError::InvalidField));
     assert_eq!(match_player('#'), Err(GameError::InvalidField));
     assert_eq!(match_player('0'), Err(GameError::InvalidField));
-    assert_eq!(match_player('5'), Err(GameError::InvalidField));
+    assert_eq!(match_player('a'), Err(GameError::InvalidField));
+    assert_eq!(match_player('O'), Err(GameError::InvalidField));
+    assert_eq!(match_player(' '), Err(GameError::InvalidField));
 }
 
 
 #[test]
 fn test_get_player() {
     assert_eq!(get_player('0'), 0);
-    assert_eq!(get_player('7'), 0);
+    assert_eq!(get_player('?'), 0);
+    assert_eq!(get_player('7'), 7);
     assert_eq!(get_player('1'), 1);
     assert_eq!(get_player('2'), 2);
 }
\ No newline at end of file
@@ -0,0 +1,192 @@
+use crate::game::Game;
+use crate::player::Player;
+use crate::rules::GameRules;
+use std::time::Duration;
+
+/// Ways to win a game, chosen with the `mode` rule.
+/// Walking through the portal takes a player out of the labyrinth with the portal points, see `Game::portal`.
+/// Whatever the mode, the game is over when nobody is left in the labyrinth.
+pub trait WinCondition {
+    /// Checks if the game is over after an action. `round_ended` is true if the action passed the turn
+    /// from the last player of a round to the first one.
+    fn is_over(&self, game: &Game, round_ended: bool) -> bool;
+
+    /// Returns the code of the player who won a finished game, or None if nobody won.
+    /// In a team game the team of the winner wins.
+    fn winner(&self, game: &Game) -> Option<usize>;
+
+    /// Orders the players for the final ranklist, the best first. By their scores unless the mode says otherwise.
+    fn rank(&self, _game: &Game, players: Vec<Player>) -> Vec<Player> {
+        return Player::rank(players);
+    }
+}
+
+/// The game modes, chosen with the `mode` rule.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Portal,
+    LastStanding,
+    Points,
+    ExitAll,
+    Rounds,
+    Timed,
+}
+
+/// Names of the game modes, as written in the rules.
+pub const MODES: [(Mode, &str); 6] = [
+    (Mode::Portal, "portal"),
+    (Mode::LastStanding, "last-standing"),
+    (Mode::Points, "points"),
+    (Mode::ExitAll, "exit-all"),
+    (Mode::Rounds, "rounds"),
+    (Mode::Timed, "timed"),
+];
+
+impl Mode {
+    pub fn name(&self) -> &'static str {
+        for (mode, name) in MODES.iter() {
+            if mode == self {
+                return name;
+            }
+        }
+        return "";
+    }
+
+    pub fn from_name(name: &str) -> Option<Mode> {
+        for (mode, mode_name) in MODES.iter() {
+            if *mode_name == name {
+                return Some(*mode);
+            }
+        }
+        return None;
+    }
+
+    /// Returns the win condition of the mode.
+    /// The target score, the number of rounds and the time limit come from the rules.
+    pub fn win_condition(&self, rules: &GameRules) -> Box<dyn WinCondition> {
+        match self {
+            Mode::Portal => return Box::new(FirstToPortal),
+            Mode::LastStanding => return Box::new(LastStanding),
+            Mode::Points => return Box::new(FirstToPoints(rules.target_score)),
+            Mode::ExitAll => return Box::new(EveryoneExits),
+            Mode::Rounds => return Box::new(FixedRounds(rules.rounds)),
+            Mode::Timed => return Box::new(TimedRounds(Duration::from_secs(rules.time_limit as u64 * 60))),
+        }
+    }
+}
+
+/// The first player who walks through the portal wins and the game ends at once.
+pub struct FirstToPortal;
+
+/// The game goes on until only one player (or players of one team) is left in the labyrinth, who wins.
+/// Leaving through the portal gives up the game.
+pub struct LastStanding;
+
+/// The first player (or team) with the target score wins.
+pub struct FirstToPoints(pub u32);
+
+/// The game goes on until everybody who is alive has walked through the portal.
+/// The players are ranked by the order they got out, the first one wins.
+pub struct EveryoneExits;
+
+/// The game ends after a number of rounds, the most points win.
+pub struct FixedRounds(pub usize);
+
+/// The game ends with the first round that finishes after the time limit, the most points win.
+pub struct TimedRounds(pub Duration);
+
+impl WinCondition for FirstToPortal {
+    fn is_over(&self, game: &Game, _round_ended: bool) -> bool {
+        return !game.exits().is_empty();
+    }
+
+    fn winner(&self, game: &Game) -> Option<usize> {
+        return game.exits().first().cloned();
+    }
+}
+
+impl WinCondition for LastStanding {
+    fn is_over(&self, game: &Game, _round_ended: bool) -> bool {
+        let left = game.players_left();
+        return left.iter().all(|player| *player == left[0] || game.teammates(*player, left[0]));
+    }
+
+    fn winner(&self, game: &Game) -> Option<usize> {
+        return game.players_left().first().cloned();
+    }
+}
+
+impl WinCondition for FirstToPoints {
+    fn is_over(&self, game: &Game, _round_ended: bool) -> bool {
+        if game.rules().teams > 0 {
+            return game.team_scores().iter().any(|(_, score)| *score >= self.0);
+        }
+        return game.players().iter().any(|player| player.score() >= self.0);
+    }
+
+    fn winner(&self, game: &Game) -> Option<usize> {
+        return best_player(game);
+    }
+}
+
+impl WinCondition for EveryoneExits {
+    fn is_over(&self, _game: &Game, _round_ended: bool) -> bool {
+        return false;
+    }
+
+    fn winner(&self, game: &Game) -> Option<usize> {
+        return game.exits().first().cloned();
+    }
+
+    /// The players who got out come first in the order they did, then the rest by their scores.
+    fn rank(&self, game: &Game, players: Vec<Player>) -> Vec<Player> {
+        let (exited, rest): (Vec<Player>, Vec<Player>) = players.into_iter()
+            .partition(|player| game.exits().contains(&player.player_code));
+        let mut result: Vec<Player> = Vec::new();
+        for code in game.exits() {
+            result.extend(exited.iter().filter(|player| player.player_code == *code).cloned());
+        }
+        result.extend(Player::rank(rest));
+        return result;
+    }
+}
+
+impl WinCondition for FixedRounds {
+    fn is_over(&self, game: &Game, _round_ended: bool) -> bool {
+        return game.round() > self.0;
+    }
+
+    fn winner(&self, game: &Game) -> Option<usize> {
+        return best_player(game);
+    }
+}
+
+impl WinCondition for TimedRounds {
+    fn is_over(&self, game: &Game, round_ended: bool) -> bool {
+        return round_ended && game.elapsed() >= self.0;
+    }
+
+    fn winner(&self, game: &Game) -> Option<usize> {
+        return best_player(game);
+    }
+}
+
+/// Returns the player with the most points, or None on a draw.
+/// In a team game the team with the most points wins, the winner is its best player.
+fn best_player(game: &Game) -> Option<usize> {
+    let players = &game.players()[1..];
+    if game.rules().teams > 0 {
+        let scores = game.team_scores();
+        if scores.len() > 1 && scores[0].1 == scores[1].1 {
+            return None;
+        }
+        return players.iter().filter(|player| player.team == scores[0].0)
+            .max_by_key(|player| player.score()).map(|player| player.player_code);
+    }
+    let best = players.iter().map(|player| player.score()).max()?;
+    let mut winners = players.iter().filter(|player| player.score() == best);
+    match (winners.next(), winners.next()) {
+        (Some(winner), None) => return Some(winner.player_code),
+        _ => return None,
+    }
+}
@@ -0,0 +1,460 @@
+use crate::game::Game;
+use crate::engine::{Action, Event, Phase};
+use crate::direction::{Direction, parse_path};
+use crate::items::Item;
+use crate::project_errors::NetworkError;
+use crate::utills::add_player;
+use crate::MOVE_TIMEOUT;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs, SocketAddr};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Messages from the server to the clients.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerMessage {
+    Welcome(usize),
+    Taken,
+    Map(String),
+    Info(String),
+    /// Asks the player on turn for a move: the moves left and the items the player can use.
+    YourMove(usize, Vec<Item>),
+    Error(String),
+    Finished(String),
+}
+
+/// Messages from a client to the server.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientMessage {
+    Join(String),
+    Move(Direction),
+    Path(Vec<Direction>),
+    Use(Item),
+}
+
+/// A connection to the other side, sending and receiving messages line by line.
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    /// The part of a line received before a read timed out.
+    pending: String,
+}
+
+/// Hosts a game. The players connect with a `Client`.
+/// Every message is a single line: a keyword and its value.
+/// Line breaks in the values are written as `\n` and backslashes as `\\`.
+///
+/// Lobby: the client sends `join <name>` until the server answers `welcome <player code>`
+/// instead of `taken`. The game starts when all the players have joined.
+/// Game: the server sends every client its own view of the map (`map`) and the news (`info`).
+/// The player on turn gets `move <moves left> <item>...`, with the items they can use, and answers with `move <direction>`, `path <direction>...` or `use <item>`.
+/// An invalid move or message gets an `error` and the same question again. At the end everybody gets the ranklist (`finished`).
+/// A player who disconnects skips their moves and the game goes on without them.
+/// A player who doesn't answer in time loses the rest of the turn.
+pub struct Server {
+    listener: TcpListener,
+    move_timeout: Duration,
+    /// The connections of the players by their codes, None for the ones who have disconnected.
+    clients: Vec<Option<Connection>>,
+}
+
+/// A player connected to a `Server`.
+pub struct Client {
+    connection: Connection,
+}
+
+impl ServerMessage {
+    pub fn encode(&self) -> String {
+        match self {
+            ServerMessage::Welcome(code) => return format!("welcome {}", code),
+            ServerMessage::Taken => return "taken".to_string(),
+            ServerMessage::Map(map) => return format!("map {}", escape(map)),
+            ServerMessage::Info(text) => return format!("info {}", escape(text)),
+            ServerMessage::YourMove(moves_left, items) => return format!("move {}",
+                std::iter::once(moves_left.to_string()).chain(items.iter().map(|item| item.to_string())).collect::<Vec<String>>().join(" ")),
+            ServerMessage::Error(text) => return format!("error {}", escape(text)),
+            ServerMessage::Finished(ranklist) => return format!("finished {}", escape(ranklist)),
+        }
+    }
+}
+
+impl FromStr for ServerMessage {
+    type Err = NetworkError;
+
+    fn from_str(line: &str) -> Result<ServerMessage, NetworkError> {
+        let (keyword, value) = split(line);
+        match keyword {
+            "welcome" => return Ok(ServerMessage::Welcome(parse_number(line, value)?)),
+            "taken" => return Ok(ServerMessage::Taken),
+            "map" => return Ok(ServerMessage::Map(unescape(value))),
+            "info" => return Ok(ServerMessage::Info(unescape(value))),
+            "move" => {
+                let mut parts = value.split(' ');
+                let moves_left = parse_number(line, parts.next().unwrap_or(""))?;
+                let items: Result<Vec<Item>, _> = parts.map(|item| item.parse::<Item>()).collect();
+                match items {
+                    Ok(items) => return Ok(ServerMessage::YourMove(moves_left, items)),
+                    Err(_) => return Err(NetworkError::Protocol(line.to_string())),
+                }
+            },
+            "error" => return Ok(ServerMessage::Error(unescape(value))),
+            "finished" => return Ok(ServerMessage::Finished(unescape(value))),
+            _ => return Err(NetworkError::Protocol(line.to_string())),
+        }
+    }
+}
+
+impl ClientMessage {
+    pub fn encode(&self) -> String {
+        match self {
+            ClientMessage::Join(name) => return format!("join {}", escape(name)),
+            ClientMessage::Move(direction) => return format!("move {}", direction),
+            ClientMessage::Path(path) => return format!("path {}",
+                path.iter().map(|direction| direction.name()).collect::<Vec<&str>>().join(" ")),
+            ClientMessage::Use(item) => return format!("use {}", item),
+        }
+    }
+}
+
+impl FromStr for ClientMessage {
+    type Err = NetworkError;
+
+    fn from_str(line: &str) -> Result<ClientMessage, NetworkError> {
+        let (keyword, value) = split(line);
+        match keyword {
+            "join" => return Ok(ClientMessage::Join(unescape(value))),
+            "move" => match value.parse::<Direction>() {
+                Ok(direction) => return Ok(ClientMessage::Move(direction)),
+                Err(_) => return Err(NetworkError::Protocol(line.to_string())),
+            },
+            "path" => match parse_path(value) {
+                Ok(path) => return Ok(ClientMessage::Path(path)),
+                Err(_) => return Err(NetworkError::Protocol(line.to_string())),
+            },
+            "use" => match value.parse::<Item>() {
+                Ok(item) => return Ok(ClientMessage::Use(item)),
+                Err(_) => return Err(NetworkError::Protocol(line.to_string())),
+            },
+            _ => return Err(NetworkError::Protocol(line.to_string())),
+        }
+    }
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> Result<Connection, NetworkError> {
+        return Ok(Connection{ reader: BufReader::new(stream.try_clone()?), writer: stream, pending: String::new() });
+    }
+
+    fn send(&mut self, line: String) -> Result<(), NetworkError> {
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()?;
+        return Ok(());
+    }
+
+    fn receive(&mut self) -> Result<String, NetworkError> {
+        match self.reader.read_line(&mut self.pending) {
+            Ok(0) => return Err(NetworkError::Disconnected),
+            Ok(_) => {
+                let line = std::mem::take(&mut self.pending);
+                return Ok(line.trim_end_matches(['\n', '\r']).to_string());
+            },
+            Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => return Err(NetworkError::Timeout),
+            Err(e) => return Err(NetworkError::Io(e)),
+        }
+    }
+
+    /// Sets how long `receive` waits for a line, None waits forever.
+    fn set_timeout(&self, timeout: Option<Duration>) -> Result<(), NetworkError> {
+        self.writer.set_read_timeout(timeout)?;
+        return Ok(());
+    }
+}
+
+/// Constructors
+impl Server {
+    /// Starts listening for players, e.g. on `0.0.0.0:7878`.
+    pub fn bind<A: ToSocketAddrs>(address: A) -> Result<Server, NetworkError> {
+        return Ok(Server{ listener: TcpListener::bind(address)?, move_timeout: Duration::from_secs(MOVE_TIMEOUT), clients: Vec::new() });
+    }
+}
+
+/// Class methods
+impl Server {
+    /// Returns the address the server listens on.
+    pub fn local_addr(&self) -> Result<SocketAddr, NetworkError> {
+        return Ok(self.listener.local_addr()?);
+    }
+
+    /// Sets how long the players have for a move, `MOVE_TIMEOUT` seconds by default.
+    pub fn set_move_timeout(&mut self, timeout: Duration) {
+        self.move_timeout = timeout;
+    }
+
+    /// Waits for the players to connect and choose their usernames.
+    /// The players get their codes in the order they connect. Returns the usernames.
+    pub fn lobby(&mut self, num_of_players: usize) -> Result<Vec<String>, NetworkError> {
+        let mut names: Vec<String> = Vec::new();
+        while names.len() < num_of_players {
+            let (stream, _) = self.listener.accept()?;
+            let mut client = Connection::new(stream)?;
+            if !join(&mut client, &mut names) {
+                // Somebody left before joining, wait for another player.
+                continue;
+            }
+            self.clients.push(Some(client));
+            let joined = format!("{} joined the game ({}/{}).", names.last().unwrap(), names.len(), num_of_players);
+            self.broadcast(ServerMessage::Info(joined));
+        }
+        return Ok(names);
+    }
+
+    /// Plays a game with the players from the lobby, asking the player on turn for the moves.
+    /// Returns the final ranklist, or `Disconnected` if everybody has left.
+    pub fn run(&mut self, game: Game) -> Result<String, NetworkError> {
+        let mut game = game;
+        for client in self.clients.iter().flatten() {
+            client.set_timeout(Some(self.move_timeout))?;
+        }
+        // The time played since it was last reported to the game.
+        let mut clock = Instant::now();
+        loop {
+            match game.phase() {
+                Phase::Setup => {
+                    let events = game.apply(Action::Start)?;
+                    self.broadcast_events(&game, &events);
+                },
+                Phase::Roll(_) => {
+                    let events = game.apply(Action::Roll)?;
+                    self.broadcast_events(&game, &events);
+                },
+                Phase::Move { player, moves_left } => {
+                    if self.clients.iter().all(|client| client.is_none()) {
+                        return Err(NetworkError::Disconnected);
+                    }
+                    if self.clients[player - 1].is_none() {
+                        let events = game.apply(Action::Skip)?;
+                        self.broadcast_events(&game, &events);
+                        continue;
+                    }
+                    self.send_maps(&game);
+                    let usable: Vec<Item> = game.get_player_items(player).iter().filter(|item| item.is_usable()).cloned().collect();
+                    self.send_to(player, ServerMessage::YourMove(moves_left, usable));
+                    let received = self.receive_from(player);
+                    game.apply(Action::Time(clock.elapsed())).ok();
+                    clock = Instant::now();
+                    let message = match received {
+                        Ok(message) => message,
+                        Err(NetworkError::Disconnected) => {
+                            let left = format!("{} has left the game.", game.get_player_name(player));
+                            self.broadcast(ServerMessage::Info(left));
+                            continue;
+                        },
+                        Err(NetworkError::Timeout) => {
+                            self.send_to(player, ServerMessage::Error(NetworkError::Timeout.to_string()));
+                            // Skip the rest of the turn, the next phase is somebody else's roll or the end.
+                            while matches!(game.phase(), Phase::Move { player: on_turn, .. } if on_turn == player) {
+                                let events = game.apply(Action::Skip)?;
+                                self.broadcast_events(&game, &events);
+                            }
+                            continue;
+                        },
+                        Err(e) => {
+                            self.send_to(player, ServerMessage::Error(e.to_string()));
+                            continue;
+                        },
+                    };
+                    let result = match message {
+                        ClientMessage::Move(direction) => game.apply(Action::Move(direction)),
+                        ClientMessage::Path(path) => game.apply(Action::Path(path)),
+                        ClientMessage::Use(item) => game.apply(Action::Use(item)),
+                        message => {
+                            self.send_to(player, ServerMessage::Error(NetworkError::Protocol(message.encode()).to_string()));
+                            continue;
+                        },
+                    };
+                    match result {
+                        Ok(events) => self.broadcast_events(&game, &events),
+                        Err(e) => self.send_to(player, ServerMessage::Error(e.to_string())),
+                    }
+                },
+                Phase::Finished => break,
+            }
+        }
+        self.send_maps(&game);
+        let ranklist = game.finish();
+        self.broadcast(ServerMessage::Finished(ranklist.clone()));
+        return Ok(ranklist);
+    }
+
+    /// Sends a message to a player. A player whose connection is broken is disconnected.
+    fn send_to(&mut self, player: usize, message: ServerMessage) {
+        let sent = match &mut self.clients[player - 1] {
+            Some(client) => client.send(message.encode()).is_ok(),
+            None => return,
+        };
+        if !sent {
+            self.clients[player - 1] = None;
+        }
+    }
+
+    /// Waits for a message from a player. A player whose connection is broken is disconnected,
+    /// a message that can't be read is a `Protocol` error and no message in time is a `Timeout`.
+    fn receive_from(&mut self, player: usize) -> Result<ClientMessage, NetworkError> {
+        let received = match &mut self.clients[player - 1] {
+            Some(client) => client.receive(),
+            None => return Err(NetworkError::Disconnected),
+        };
+        match received {
+            Ok(line) => return line.parse::<ClientMessage>(),
+            Err(NetworkError::Timeout) => return Err(NetworkError::Timeout),
+            Err(_) => {
+                self.clients[player - 1] = None;
+                return Err(NetworkError::Disconnected);
+            },
+        }
+    }
+
+    /// Sends every player the map as they see it.
+    fn send_maps(&mut self, game: &Game) {
+        for player in 1..=self.clients.len() {
+            self.send_to(player, ServerMessage::Map(game.display_map_for(player)));
+        }
+    }
+
+    fn broadcast(&mut self, message: ServerMessage) {
+        for player in 1..=self.clients.len() {
+            self.send_to(player, message.clone());
+        }
+    }
+
+    /// Tells everybody what happened in the game.
+    fn broadcast_events(&mut self, game: &Game, events: &[Event]) {
+        for event in events {
+            let news = match (event, game.describe(event)) {
+                (Event::TurnStarted(player), Some(text)) => format!("-- PLAYER {} --\n{}", player, text),
+                (_, Some(text)) => text,
+                (_, None) => continue,
+            };
+            self.broadcast(ServerMessage::Info(news));
+        }
+    }
+}
+
+/// Lets a new client choose a username. Returns false if the client disconnects first.
+/// A message other than `join` gets an `error`.
+fn join(client: &mut Connection, names: &mut Vec<String>) -> bool {
+    loop {
+        let line = match client.receive() {
+            Ok(line) => line,
+            Err(_) => return false,
+        };
+        let answer = match line.parse::<ClientMessage>() {
+            Ok(ClientMessage::Join(name)) => {
+                if add_player(names, name) {
+                    if client.send(ServerMessage::Welcome(names.len()).encode()).is_ok() {
+                        return true;
+                    }
+                    names.pop();
+                    return false;
+                }
+                ServerMessage::Taken
+            },
+            Ok(message) => ServerMessage::Error(NetworkError::Protocol(message.encode()).to_string()),
+            Err(e) => ServerMessage::Error(e.to_string()),
+        };
+        if client.send(answer.encode()).is_err() {
+            return false;
+        }
+    }
+}
+
+/// Constructors
+impl Client {
+    /// Connects to a server, e.g. on `127.0.0.1:7878`.
+    pub fn connect<A: ToSocketAddrs>(address: A) -> Result<Client, NetworkError> {
+        return Ok(Client{ connection: Connection::new(TcpStream::connect(address)?)? });
+    }
+}
+
+/// Class methods
+impl Client {
+    /// Asks to join the game with a username. Returns the player code or None if the username is taken.
+    pub fn join(&mut self, name: &str) -> Result<Option<usize>, NetworkError> {
+        self.connection.send(ClientMessage::Join(name.to_string()).encode())?;
+        match self.receive()? {
+            ServerMessage::Welcome(code) => return Ok(Some(code)),
+            ServerMessage::Taken => return Ok(None),
+            message => return Err(NetworkError::Protocol(message.encode())),
+        }
+    }
+
+    /// Waits for the next message from the server.
+    pub fn receive(&mut self) -> Result<ServerMessage, NetworkError> {
+        return self.connection.receive()?.parse::<ServerMessage>();
+    }
+
+    /// Sends a move when the server asks for it.
+    pub fn send_move(&mut self, direction: Direction) -> Result<(), NetworkError> {
+        return self.connection.send(ClientMessage::Move(direction).encode());
+    }
+
+    /// Sends a path of several moves when the server asks for a move.
+    pub fn send_path(&mut self, path: Vec<Direction>) -> Result<(), NetworkError> {
+        return self.connection.send(ClientMessage::Path(path).encode());
+    }
+
+    /// Uses an item instead of a move when the server asks for a move.
+    pub fn send_use(&mut self, item: Item) -> Result<(), NetworkError> {
+        return self.connection.send(ClientMessage::Use(item).encode());
+    }
+}
+
+fn split(line: &str) -> (&str, &str) {
+    let mut parts = line.splitn(2, ' ');
+    return (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+}
+
+fn parse_number(line: &str, value: &str) -> Result<usize, NetworkError> {
+    return value.parse::<usize>().map_err(|_| NetworkError::Protocol(line.to_string()));
+}
+
+fn escape(text: &str) -> String {
+    return text.replace('\\', "\\\\").replace('\n', "\\n");
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(symbol) = chars.next() {
+        if symbol != '\\' {
+            result.push(symbol);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    return result;
+}
+
+
+
+// ----------------------------------------------------------------------------------
+// Private functions test
+
+#[test]
+fn test_escape() {
+    assert_eq!(escape("1 . #\n. 0 2\n"), "1 . #\\n. 0 2\\n");
+    assert_eq!(escape("a\\nb"), "a\\\\nb");
+    for text in ["1 . #\n. 0 2\n", "a\\nb\n", "\\", ""].iter() {
+        assert_eq!(unescape(&escape(text)), *text);
+    }
+}
+
+#[test]
+fn test_split() {
+    assert_eq!(split("join John Smith"), ("join", "John Smith"));
+    assert_eq!(split("taken"), ("taken", ""));
+}
@@ -0,0 +1,81 @@
+use crate::project_errors::RulesError;
+use crate::rules::{GameRules, flag_key};
+use crate::{MAP_FLAG, RULES_FLAG, RECORD_FLAG, REPLAY_FLAG, HOST_FLAG, JOIN_FLAG, KEYS_FLAG};
+
+/// Command line options of the game.
+/// A rules file is applied first, so flags override the values in it regardless of their order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub rules: GameRules,
+    pub map_file: Option<String>,
+    pub record_file: Option<String>,
+    pub replay_file: Option<String>,
+    pub host: Option<String>,
+    pub join: Option<String>,
+    pub keys_file: Option<String>,
+}
+
+impl Options {
+    /// Parses the command line arguments, without the program name.
+    pub fn parse(args: &[String]) -> Result<Options, RulesError> {
+        let mut options = Options{
+            rules: GameRules::default(),
+            map_file: None,
+            record_file: None,
+            replay_file: None,
+            host: None,
+            join: None,
+            keys_file: None,
+        };
+        let mut flags: Vec<(&str, &str)> = Vec::new();
+        let mut i = 0;
+        while i < args.len() {
+            match args.get(i + 1) {
+                Some(value) => flags.push((&args[i], value)),
+                None => return Err(RulesError::InvalidFlag(format!("missing value for `{}`", args[i]))),
+            }
+            i += 2;
+        }
+
+        for (flag, value) in flags.iter() {
+            if *flag == RULES_FLAG {
+                options.rules = GameRules::load(value)?;
+            }
+        }
+        for (flag, value) in flags {
+            if flag == RULES_FLAG {
+                continue;
+            } else if flag == MAP_FLAG {
+                options.map_file = Some(value.to_string());
+            } else if flag == RECORD_FLAG {
+                options.record_file = Some(value.to_string());
+            } else if flag == REPLAY_FLAG {
+                options.replay_file = Some(value.to_string());
+            } else if flag == HOST_FLAG {
+                options.host = Some(value.to_string());
+            } else if flag == JOIN_FLAG {
+                options.join = Some(value.to_string());
+            } else if flag == KEYS_FLAG {
+                options.keys_file = Some(value.to_string());
+            } else if let Some(key) = flag_key(flag) {
+                options.rules.set(&key, value).map_err(RulesError::InvalidFlag)?;
+            } else {
+                return Err(RulesError::InvalidFlag(format!("unknown flag `{}`", flag)));
+            }
+        }
+        return Ok(options);
+    }
+
+    /// Returns the usage message of the program.
+    pub fn usage(program: &str) -> String {
+        let mut result = format!("Usage: {} [{} <file>] [{} <file>] [{} <file>] [{} <file>] [--<rule> <value>]...\n",
+            program, MAP_FLAG, RULES_FLAG, RECORD_FLAG, KEYS_FLAG);
+        result.push_str(&format!("       {} {} <file>\n", program, REPLAY_FLAG));
+        result.push_str(&format!("       {} {} <address> [{} <file>] [--<rule> <value>]...\n", program, HOST_FLAG, MAP_FLAG));
+        result.push_str(&format!("       {} {} <address> [{} <file>]\nRules:\n", program, JOIN_FLAG, KEYS_FLAG));
+        for (key, description) in crate::rules::RULES.iter() {
+            result.push_str(&format!("   --{:<14} {}\n", key.replace('_', "-"), description));
+        }
+        return result;
+    }
+}
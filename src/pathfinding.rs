@@ -0,0 +1,154 @@
+use crate::map::Map;
+use crate::direction::Direction;
+use crate::Position;
+use crate::tiles::Tile;
+use std::collections::VecDeque;
+
+/// The number of steps from one field to every other field of a map.
+/// Built by `Map::distances`.
+pub struct Distances {
+    steps: Vec<Vec<Option<usize>>>,
+}
+
+impl Distances {
+    /// Returns the number of steps to a field, or None if it can't be reached or is outside the map.
+    pub fn get(&self, position: Position) -> Option<usize> {
+        return *self.steps.get(position.0)?.get(position.1)?;
+    }
+
+    /// Returns every field that can be reached, the starting field included.
+    pub fn reachable(&self) -> Vec<Position> {
+        let mut result: Vec<Position> = Vec::new();
+        for (x, row) in self.steps.iter().enumerate() {
+            for (y, steps) in row.iter().enumerate() {
+                if steps.is_some() {
+                    result.push(Position(x, y));
+                }
+            }
+        }
+        return result;
+    }
+}
+
+/// Pathfinding. Walls block the way, players and the portal don't -
+/// stepping on them is a battle or the end of the game, not a blocked move.
+/// Breakable walls and doors don't block the way either, they can be broken or opened.
+/// Arrows can only be entered in their direction and teleport pads lead to the other pad of their pair.
+/// Every step counts as one, whatever the tile costs.
+impl Map {
+    /// Checks if a field is on the map.
+    pub fn contains(&self, position: Position) -> bool {
+        return position.0 < self.height && position.1 < self.width;
+    }
+
+    /// Checks if a player can step on a field.
+    pub fn is_passable(&self, position: Position) -> bool {
+        return self.contains(position) && self.tile(position).is_passable();
+    }
+
+    /// Returns the field a player stepping in a direction ends up on, if it can step there.
+    pub fn step(&self, position: Position, direction: Direction) -> Option<Position> {
+        match self.neighbour(position, direction) {
+            Some(next) if self.tile(next).can_enter(direction) => return Some(self.teleport_pair(next).unwrap_or(next)),
+            _ => return None,
+        }
+    }
+
+    /// Returns the shortest path between two fields, or None if there is no way or one of them is outside the map.
+    /// The path is empty if both fields are the same.
+    pub fn shortest_path(&self, from: Position, to: Position) -> Option<Vec<Direction>> {
+        if !self.contains(from) || !self.is_passable(to) {
+            return None;
+        }
+        return self.find_path(from, |position| position == to);
+    }
+
+    /// Searches for the nearest field matching `is_target` and returns the shortest path to it,
+    /// which is empty if `start` itself matches. Returns None if `start` is outside the map.
+    pub fn find_path<F: Fn(Position) -> bool>(&self, start: Position, is_target: F) -> Option<Vec<Direction>> {
+        return self.find_path_over(start, |_| true, is_target);
+    }
+
+    /// Like `find_path`, but only steps on the fields `may_enter` allows, e.g. the fields a player knows.
+    pub fn find_path_over<P: Fn(Position) -> bool, F: Fn(Position) -> bool>(&self, start: Position, may_enter: P, is_target: F) -> Option<Vec<Direction>> {
+        if !self.contains(start) {
+            return None;
+        }
+        // The field every visited field was entered from and the direction of the step.
+        let mut came_from: Vec<Vec<Option<(Position, Direction)>>> = vec![vec![None; self.width]; self.height];
+        let mut visited = vec![vec![false; self.width]; self.height];
+        let mut queue: VecDeque<Position> = VecDeque::new();
+        visited[start.0][start.1] = true;
+        queue.push_back(start);
+        while let Some(position) = queue.pop_front() {
+            if is_target(position) {
+                let mut path: Vec<Direction> = Vec::new();
+                let mut current = position;
+                while let Some((previous, direction)) = came_from[current.0][current.1] {
+                    path.push(direction);
+                    current = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for direction in Direction::ALL.iter() {
+                if let Some(next) = self.step(position, *direction) {
+                    if !visited[next.0][next.1] && may_enter(next) {
+                        visited[next.0][next.1] = true;
+                        came_from[next.0][next.1] = Some((position, *direction));
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        return None;
+    }
+
+    /// Returns the number of steps from a field to every field of the map.
+    /// Nothing can be reached from a field outside the map.
+    pub fn distances(&self, from: Position) -> Distances {
+        let mut steps: Vec<Vec<Option<usize>>> = vec![vec![None; self.width]; self.height];
+        if !self.contains(from) {
+            return Distances{ steps: steps };
+        }
+        let mut queue: VecDeque<Position> = VecDeque::new();
+        steps[from.0][from.1] = Some(0);
+        queue.push_back(from);
+        while let Some(position) = queue.pop_front() {
+            let distance = steps[position.0][position.1].unwrap();
+            for direction in Direction::ALL.iter() {
+                if let Some(next) = self.step(position, *direction) {
+                    if steps[next.0][next.1].is_none() {
+                        steps[next.0][next.1] = Some(distance + 1);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        return Distances{ steps: steps };
+    }
+
+    /// Returns the fields that can be reached from a field in at most `max_steps` steps, the field itself included.
+    pub fn reachable_within(&self, from: Position, max_steps: usize) -> Vec<Position> {
+        let distances = self.distances(from);
+        return distances.reachable().into_iter().filter(|position| distances.get(*position).unwrap() <= max_steps).collect();
+    }
+
+    /// Returns the first of `fields` that can't be reached from `from`, or None if all of them can.
+    /// Used to check that the players can reach each other and the portal on a loaded map or game.
+    pub fn unreachable(&self, from: Position, fields: &[Position]) -> Option<Position> {
+        let distances = self.distances(from);
+        return fields.iter().cloned().find(|field| distances.get(*field).is_none());
+    }
+
+    /// Checks if every field a player can step on is reachable from all the others, as the generators guarantee.
+    /// Teleport pads are left out, the players land on the other pad.
+    pub fn is_connected(&self) -> bool {
+        let counted: Vec<Position> = (0..self.height).flat_map(|x| (0..self.width).map(move |y| Position(x, y)))
+            .filter(|position| self.is_passable(*position) && self.tile(*position) != Tile::Teleport).collect();
+        match counted.first() {
+            Some(start) => return self.unreachable(*start, &counted).is_none(),
+            None => return false,
+        }
+    }
+}
@@ -1,14 +1,28 @@
 use crate::project_errors::GameError;
-use crate::Position;
-use crate::directions;
+use crate::{Position, HEALTH, ATTACK, DEFENSE};
+use crate::direction::Direction;
+use crate::ai::PlayerKind;
+use crate::items::Item;
 use std::cmp::Ordering;
+use std::collections::HashSet;
 
 #[derive(Clone)]
 pub struct Player{
     pub name: String,
     score: u32,
     pub player_code: usize,
-    pub coordinates: Option<Position>
+    pub coordinates: Option<Position>,
+    pub kind: PlayerKind,
+    /// Combat stats, only used when the combat rule is on.
+    pub health: u32,
+    pub attack: u32,
+    pub defense: u32,
+    /// The player's team, 0 when the game isn't played in teams.
+    pub team: usize,
+    items: Vec<Item>,
+    explored: HashSet<Position>,
+    pub pin: Option<PinHash>,
+    pub(crate) seen_events: usize
 }
 
 impl Player{
@@ -18,6 +32,15 @@ impl Player{
             player_code: 0,
             score: 0,
             coordinates: None,
+            kind: PlayerKind::Human,
+            health: HEALTH,
+            attack: ATTACK,
+            defense: DEFENSE,
+            team: 0,
+            items: Vec::new(),
+            explored: HashSet::new(),
+            pin: None,
+            seen_events: 0,
         }
     }
 
@@ -27,6 +50,15 @@ impl Player{
             player_code: code,
             score: 0,
             coordinates: None,
+            kind: PlayerKind::Human,
+            health: HEALTH,
+            attack: ATTACK,
+            defense: DEFENSE,
+            team: 0,
+            items: Vec::new(),
+            explored: HashSet::new(),
+            pin: None,
+            seen_events: 0,
         }
     }
 }
@@ -52,15 +84,74 @@ impl Ord for Player {
 impl Eq for Player {}
 
 impl Player{
+    pub fn score(&self) -> u32 {
+        return self.score;
+    }
+
     pub fn win_points(&mut self, points: u32) {
         self.score = self.score + points;
     }
 
+    pub fn set_stats(&mut self, health: u32, attack: u32, defense: u32) {
+        self.health = health;
+        self.attack = attack;
+        self.defense = defense;
+    }
+
+    /// Takes health from the player. Returns false if the player has no health left.
+    pub fn take_damage(&mut self, damage: u32) -> bool {
+        self.health = self.health.saturating_sub(damage);
+        return self.health > 0;
+    }
+
+    /// Returns the items in the player's inventory, in the order they were picked up.
+    pub fn items(&self) -> &[Item] {
+        return &self.items;
+    }
+
+    pub fn add_item(&mut self, item: Item) {
+        self.items.push(item);
+    }
+
+    pub fn has_item(&self, item: Item) -> bool {
+        return self.items.contains(&item);
+    }
+
+    /// Empties the inventory and returns what was in it.
+    pub fn take_items(&mut self) -> Vec<Item> {
+        return std::mem::replace(&mut self.items, Vec::new());
+    }
+
+    /// Takes an item from the inventory. Returns false if the player doesn't have it.
+    pub fn take_item(&mut self, item: Item) -> bool {
+        match self.items.iter().position(|other| *other == item) {
+            Some(i) => {
+                self.items.remove(i);
+                return true;
+            },
+            None => return false,
+        }
+    }
+
+    /// Remembers a field the player has seen.
+    pub fn explore(&mut self, position: Position) {
+        self.explored.insert(position);
+    }
+
+    pub fn has_explored(&self, position: Position) -> bool {
+        return self.explored.contains(&position);
+    }
+
+    /// Returns the fields the player has seen, in no particular order.
+    pub fn explored(&self) -> Vec<Position> {
+        return self.explored.iter().cloned().collect();
+    }
+
     pub fn spawn(&mut self, coordinates: Position) {
         self.coordinates = Some(coordinates);
     }
 
-    pub fn set_move(&mut self, direction: &str) {
+    pub fn set_move(&mut self, direction: Direction) {
         self.coordinates = get_coordinates(self.coordinates, direction)
     }
 
@@ -80,35 +171,58 @@ impl Player{
     }
 }
 
-fn get_coordinates(coordinates: Option<Position>, direction: &str) -> Option<Position> {
-    match coordinates {
-        Some(position) => 
-            match change_coordinates(position, direction) {
-                Ok(new_coordinates) => Some(new_coordinates),
-                Err(_) => None,
-            },
-        None => None,
+/// A PIN kept as a salted hash, so the PIN can't be read from the game or its save files at a glance.
+/// This is obfuscation only: the hash is fast, and anybody with a save file can try every short PIN in no time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PinHash{
+    pub salt: u64,
+    pub hash: u64,
+}
+
+impl PinHash{
+    /// Hashes a PIN with a new random salt.
+    pub fn new(pin: &str) -> PinHash {
+        return PinHash::with_salt(pin, rand::random::<u64>());
+    }
+
+    pub fn with_salt(pin: &str, salt: u64) -> PinHash {
+        return PinHash{ salt: salt, hash: pin_hash(pin, salt) };
+    }
+
+    /// Checks if a PIN hashes to this hash.
+    pub fn matches(&self, pin: &str) -> bool {
+        return pin_hash(pin, self.salt) == self.hash;
     }
 }
 
-pub fn change_coordinates(coordinates:Position, direction: &str) -> Result<Position, GameError> {
-    let x = coordinates.0;
-    let y = coordinates.1;
+const PIN_HASH_ROUNDS: usize = 10000;
 
-    if (direction == directions::UP && x == 0) || (direction == directions::LEFT && y == 0) {
-        return Err(GameError::Outside);
+/// FNV-1a over the salt and the PIN, repeated a number of times. It is not a password hash and doesn't keep a PIN secret.
+/// A hasher of the standard library can't be used, its results may change between Rust releases.
+fn pin_hash(pin: &str, salt: u64) -> u64 {
+    let mut hash: u64 = salt;
+    for _ in 0..PIN_HASH_ROUNDS {
+        let mut round: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in hash.to_le_bytes().iter().chain(salt.to_le_bytes().iter()).chain(pin.as_bytes()) {
+            round ^= *byte as u64;
+            round = round.wrapping_mul(0x0100_0000_01b3);
+        }
+        hash = round;
+    }
+    return hash;
+}
+
+fn get_coordinates(coordinates: Option<Position>, direction: Direction) -> Option<Position> {
+    match coordinates {
+        Some(position) => position.step(direction),
+        None => None,
     }
+}
 
-    if direction == directions::DOWN{
-        return Ok(Position(x+1, y));
-    }else if direction == directions::UP{
-        return Ok(Position(x-1, y));
-    }else if direction == directions::LEFT{
-        return Ok(Position(x, y-1));
-    }else if direction == directions::RIGHT{
-        return Ok(Position(x, y+1));
-    }else{
-        return Err(GameError::InvalidDirection);
+pub fn change_coordinates(coordinates:Position, direction: Direction) -> Result<Position, GameError> {
+    match coordinates.step(direction) {
+        Some(position) => return Ok(position),
+        None => return Err(GameError::Outside),
     }
 }
 
@@ -120,24 +234,34 @@ pub fn change_coordinates(coordinates:Position, direction: &str) -> Result<Posit
 #[test]
 fn test_get_coordinates(){
     let position = Position(5, 6);
-    assert_eq!(get_coordinates(None, directions::DOWN), None);
-    assert_eq!(get_coordinates(Some(position), directions::UP), Some(Position(4, 6)));
-    assert_eq!(get_coordinates(Some(position), "jfgs"), None);
-    assert_eq!(get_coordinates(Some(Position(0, 0)), directions::UP), None);
+    assert_eq!(get_coordinates(None, Direction::Down), None);
+    assert_eq!(get_coordinates(Some(position), Direction::Up), Some(Position(4, 6)));
+    assert_eq!(get_coordinates(Some(Position(0, 0)), Direction::Up), None);
 }
 
 #[test]
 fn test_directions(){
     let position = Position(5, 6);
-    assert_eq!(change_coordinates(position, directions::DOWN), Ok(Position(6, 6)));
-    assert_eq!(change_coordinates(position, directions::UP), Ok(Position(4, 6)));
-    assert_eq!(change_coordinates(position, directions::LEFT), Ok(Position(5, 5)));
-    assert_eq!(change_coordinates(position, directions::RIGHT), Ok(Position(5, 7)));
+    assert_eq!(change_coordinates(position, Direction::Down), Ok(Position(6, 6)));
+    assert_eq!(change_coordinates(position, Direction::Up), Ok(Position(4, 6)));
+    assert_eq!(change_coordinates(position, Direction::Left), Ok(Position(5, 5)));
+    assert_eq!(change_coordinates(position, Direction::Right), Ok(Position(5, 7)));
 }
 
 #[test]
 fn test_directions_error(){
-    assert_eq!(change_coordinates(Position(0, 0), "fjhd"), Err(GameError::InvalidDirection));
-    assert_eq!(change_coordinates(Position(0, 0), directions::UP), Err(GameError::Outside));
+    assert_eq!("fjhd".parse::<Direction>(), Err(GameError::InvalidDirection));
+    assert_eq!(change_coordinates(Position(0, 0), Direction::Up), Err(GameError::Outside));
+    assert_eq!(change_coordinates(Position(0, 0), Direction::Left), Err(GameError::Outside));
 
 }
+
+#[test]
+fn test_pin_hash(){
+    let pin = PinHash::with_salt("1234", 7);
+    assert!(pin.matches("1234"));
+    assert!(!pin.matches("1235"));
+    assert!(!pin.matches(""));
+    assert_eq!(pin, PinHash::with_salt("1234", 7));
+    assert_ne!(pin.hash, PinHash::with_salt("1234", 8).hash);
+}
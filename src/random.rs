@@ -0,0 +1,57 @@
+use rand::{RngCore, SeedableRng, Error};
+use rand::rngs::StdRng;
+
+/// The random generator of a game. It counts the 32-bit words it has drawn, so a saved game can
+/// restore it from its seed without drawing anything while it is saved.
+pub struct GameRng {
+    rng: StdRng,
+    seed: u64,
+    draws: u64,
+}
+
+/// Constructors
+impl GameRng {
+    pub fn seed_from_u64(seed: u64) -> GameRng {
+        return GameRng::restore(seed, 0);
+    }
+
+    /// Restores a generator seeded with `seed` that has drawn `draws` words.
+    pub fn restore(seed: u64, draws: u64) -> GameRng {
+        let mut rng = StdRng::seed_from_u64(seed);
+        for _ in 0..draws {
+            rng.next_u32();
+        }
+        return GameRng{ rng: rng, seed: seed, draws: draws };
+    }
+}
+
+/// Class methods
+impl GameRng {
+    /// Returns the seed and the number of words drawn since, as `restore` takes them.
+    pub fn state(&self) -> (u64, u64) {
+        return (self.seed, self.draws);
+    }
+}
+
+/// The standard generator hands out its values word by word, so counting the words is enough to get back to the same place.
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.draws += 1;
+        return self.rng.next_u32();
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.draws += 2;
+        return self.rng.next_u64();
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.draws += dest.len().div_ceil(4) as u64;
+        self.rng.fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        return Ok(());
+    }
+}
@@ -0,0 +1,295 @@
+use crate::game::Game;
+use crate::engine::{Action, Event, Phase};
+use crate::direction::Direction;
+use crate::items::Item;
+use crate::project_errors::ReplayError;
+use crate::save;
+use std::fs::File;
+use std::io::Write;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Replay file format:
+///    labyrinth-replay <version>
+///    <the game when the recording started, as written by Game::save>
+///    actions
+///    roll <code> <value>           - a player rolled the dice
+///    move <code> <direction>       - a player moved, followed by what the move caused:
+///    fight <attacker> <defender> <attack roll> <defense roll>
+///                                  - a battle with the combat rule on, followed by its result:
+///    wounded <code> <damage>       - a player lost health in a battle
+///    pushed <code>                 - the defender was pushed back
+///    battle <winner> <loser>       - a player was killed in a battle
+///    shielded <code>               - a player's shield broke instead of the player being killed
+///    picked <code> <item>          - a player picked up an item
+///    teleported <code>             - a teleport pad took a player to its pair
+///    broken <code>                 - a player broke a breakable wall instead of moving
+///    stuck <code>                  - a player stepped in mud and lost the moves left
+///    swapped <code> <teammate>     - a player walked into a teammate and they swapped places
+///    portal <code>                 - a player walked through the portal
+///    timeup                        - the time limit of a timed game is over, it ends with the move
+///    use <code> <item>             - a player used an item
+///    skip <code>                   - a player gave up a move
+///    time <milliseconds>           - the time that passed since the last `time` line, as the frontend told the game
+pub const REPLAY_HEADER: &str = "labyrinth-replay";
+pub const REPLAY_VERSION: u32 = 1;
+const ACTIONS: &str = "actions";
+
+/// Writes the actions of a game to a replay file as they are applied.
+pub struct Recorder {
+    file: File,
+}
+
+/// A recorded game, played back as a list of frames.
+pub struct Replay {
+    frames: Vec<Frame>,
+}
+
+/// The game after an action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// The player who made the action, 0 for the first frame.
+    pub player: usize,
+    pub description: String,
+    pub map: String,
+    pub scores: Vec<(String, u32)>,
+}
+
+impl Recorder {
+    /// Creates the replay file and writes the current state of the game to it.
+    /// The recording should start after the players are placed on the map.
+    pub fn create(path: &str, game: &Game) -> Result<Recorder, ReplayError> {
+        let mut file = File::create(path)?;
+        write!(file, "{} {}\n{}{}\n", REPLAY_HEADER, REPLAY_VERSION, save::serialize(game), ACTIONS)?;
+        return Ok(Recorder{ file: file });
+    }
+
+    /// Writes an action applied by a player and what it caused.
+    /// A failed write doesn't stop the game, the replay just ends there.
+    pub fn record(&mut self, player: usize, action: &Action, events: &[Event]) {
+        for line in action_lines(player, action, events) {
+            writeln!(self.file, "{}", line).ok();
+        }
+    }
+}
+
+impl Replay {
+    /// Loads a replay file.
+    pub fn load(path: &str) -> Result<Replay, ReplayError> {
+        let content = std::fs::read_to_string(path)?;
+        return content.parse::<Replay>();
+    }
+
+    /// Returns the frames of the replay, starting with the game before the first action.
+    pub fn frames(&self) -> &[Frame] {
+        return &self.frames;
+    }
+}
+
+/// Plays the recorded actions again and checks that they cause the recorded results.
+impl FromStr for Replay {
+    type Err = ReplayError;
+
+    fn from_str(content: &str) -> Result<Replay, ReplayError> {
+        let lines: Vec<&str> = content.lines().collect();
+        match lines.first().map(|line| line.split_whitespace().collect::<Vec<&str>>()) {
+            Some(ref header) if header.len() == 2 && header[0] == REPLAY_HEADER => {
+                if header[1].parse::<u32>() != Ok(REPLAY_VERSION) {
+                    return Err(ReplayError::InvalidLine(1, format!("unsupported version `{}`", header[1])));
+                }
+            },
+            _ => return Err(ReplayError::InvalidLine(1, "not a labyrinth replay file".to_string())),
+        }
+        let actions = match lines.iter().position(|line| line.trim_end() == ACTIONS) {
+            Some(actions) => actions,
+            None => return Err(ReplayError::InvalidLine(lines.len(), format!("expected `{}`", ACTIONS))),
+        };
+        let mut game = save::deserialize(&(lines[1..actions].join("\n") + "\n"))?;
+        let mut frames = vec![frame(&game, 0, "The game begins.".to_string())];
+
+        let mut line = actions + 1;
+        while line < lines.len() {
+            let parts: Vec<&str> = lines[line].split_whitespace().collect();
+            line += 1;
+            if parts.is_empty() {
+                continue;
+            }
+            // The time passes between the frames, it doesn't make one.
+            if parts[0] == "time" {
+                match parts.get(1).map(|time| time.parse::<u64>()) {
+                    Some(Ok(time)) if parts.len() == 2 && game.apply(Action::Time(Duration::from_millis(time))).is_ok() => continue,
+                    _ => return Err(ReplayError::InvalidLine(line, "expected `time <milliseconds>` while the game is on".to_string())),
+                }
+            }
+            let player = match (parts.get(1).map(|code| code.parse::<usize>()), game.phase()) {
+                (Some(Ok(player)), Phase::Roll(current)) | (Some(Ok(player)), Phase::Move { player: current, .. })
+                    if player == current => player,
+                (_, Phase::Finished) => return Err(ReplayError::InvalidLine(line, "the game is already over".to_string())),
+                _ => return Err(ReplayError::InvalidLine(line, "not the player on turn".to_string())),
+            };
+            let (action, events) = match (parts[0], parts.len(), game.phase()) {
+                ("roll", 3, Phase::Roll(_)) => match parts[2].parse::<usize>() {
+                    Ok(value) if value >= 1 && value <= game.rules().dice_sides => {
+                        game.set_moves_left(value);
+                        (Action::Roll, vec![Event::DiceRolled { player: player, value: value }])
+                    },
+                    _ => return Err(ReplayError::InvalidLine(line, format!("invalid dice value `{}`", parts[2]))),
+                },
+                ("move", 3, Phase::Move { .. }) => {
+                    let action = match parts[2].parse::<Direction>() {
+                        Ok(direction) => Action::Move(direction),
+                        Err(_) => return Err(ReplayError::InvalidLine(line, format!("invalid direction `{}`", parts[2]))),
+                    };
+                    // The battle is fought with the recorded dice.
+                    if let Some(rolls) = lines.get(line).and_then(|next| fight_rolls(next, game.rules().dice_sides)) {
+                        game.queue_rolls(&rolls);
+                    }
+                    match game.apply(action.clone()) {
+                        Ok(events) => (action, events),
+                        Err(_) => return Err(ReplayError::InvalidLine(line, format!("invalid move `{}`", parts[2]))),
+                    }
+                },
+                ("use", 3, Phase::Move { .. }) => {
+                    let action = match parts[2].parse::<Item>() {
+                        Ok(item) => Action::Use(item),
+                        Err(_) => return Err(ReplayError::InvalidLine(line, format!("invalid item `{}`", parts[2]))),
+                    };
+                    match game.apply(action.clone()) {
+                        Ok(events) => (action, events),
+                        Err(_) => return Err(ReplayError::InvalidLine(line, format!("`{}` can't be used", parts[2]))),
+                    }
+                },
+                ("skip", 2, Phase::Move { .. }) => match game.apply(Action::Skip) {
+                    Ok(events) => (Action::Skip, events),
+                    Err(_) => return Err(ReplayError::InvalidLine(line, "the move can't be skipped".to_string())),
+                },
+                _ => return Err(ReplayError::InvalidLine(line, format!("unexpected `{}`", lines[line - 1].trim()))),
+            };
+            for expected in outcomes(&events) {
+                if lines.get(line).map(|content| content.trim()) != Some(expected.as_str()) {
+                    return Err(ReplayError::InvalidLine(line + 1, format!("expected `{}`", expected)));
+                }
+                line += 1;
+            }
+            let description = describe(&game, player, &action, &events);
+            frames.push(frame(&game, player, description));
+        }
+        return Ok(Replay{ frames: frames });
+    }
+}
+
+/// Returns the replay lines of an action and what it caused.
+/// Paths are recorded as the moves they are made of, see `Game::apply`.
+fn action_lines(player: usize, action: &Action, events: &[Event]) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    match action {
+        Action::Path(_) => return result,
+        Action::Roll => {
+            for event in events {
+                if let Event::DiceRolled { value, .. } = event {
+                    result.push(format!("roll {} {}", player, value));
+                }
+            }
+        },
+        Action::Move(direction) => result.push(format!("move {} {}", player, direction)),
+        Action::Use(item) => result.push(format!("use {} {}", player, item)),
+        Action::Skip => result.push(format!("skip {}", player)),
+        Action::Time(time) => result.push(format!("time {}", time.as_millis())),
+        Action::Start => (),
+    }
+    result.extend(outcomes(events));
+    return result;
+}
+
+/// Returns the replay lines of the battles, pickups, tiles and portals in the events.
+fn outcomes(events: &[Event]) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for event in events {
+        match event {
+            Event::Battle { winner, loser, .. } => result.push(format!("battle {} {}", winner, loser)),
+            Event::Fight { attacker, defender, attack_roll, defense_roll } =>
+                result.push(format!("fight {} {} {} {}", attacker, defender, attack_roll, defense_roll)),
+            Event::Wounded { player, damage, .. } => result.push(format!("wounded {} {}", player, damage)),
+            Event::Pushed { player, .. } => result.push(format!("pushed {}", player)),
+            Event::Shielded { player } => result.push(format!("shielded {}", player)),
+            Event::ItemPicked { player, item } => result.push(format!("picked {} {}", player, item)),
+            Event::Teleported { player, .. } => result.push(format!("teleported {}", player)),
+            Event::WallBroken { player, .. } => result.push(format!("broken {}", player)),
+            Event::Stuck(player) => result.push(format!("stuck {}", player)),
+            Event::Swapped { player, with } => result.push(format!("swapped {} {}", player, with)),
+            Event::Portal { player, .. } => result.push(format!("portal {}", player)),
+            Event::TimeUp => result.push("timeup".to_string()),
+            _ => (),
+        }
+    }
+    return result;
+}
+
+/// Returns the dice values of a `fight` line, if they are valid.
+fn fight_rolls(line: &str, dice_sides: usize) -> Option<Vec<usize>> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() != 5 || parts[0] != "fight" {
+        return None;
+    }
+    let rolls: Vec<usize> = parts[3..].iter().map(|roll| roll.parse::<usize>()).collect::<Result<Vec<usize>, _>>().ok()?;
+    if rolls.iter().any(|roll| *roll < 1 || *roll > dice_sides) {
+        return None;
+    }
+    return Some(rolls);
+}
+
+fn describe(game: &Game, player: usize, action: &Action, events: &[Event]) -> String {
+    let name = game.get_player_name(player);
+    let mut result = match action {
+        Action::Roll => format!("{} rolls {}.", name, game.moves_left()),
+        Action::Move(direction) => format!("{} moves {}.", name, direction),
+        Action::Path(path) => format!("{} moves {}.", name, path.iter().map(|direction| direction.name()).collect::<Vec<&str>>().join(", ")),
+        // The events tell what the item did.
+        Action::Use(_) => String::new(),
+        Action::Skip => format!("{} skips a move.", name),
+        Action::Start | Action::Time(_) => String::new(),
+    };
+    for event in events {
+        match (event, game.describe(event)) {
+            (Event::TurnStarted(_), _) | (Event::DiceRolled { .. }, _) | (_, None) => (),
+            (_, Some(text)) => result.push_str(&format!(" {}", text)),
+        }
+    }
+    return result.trim_start().to_string();
+}
+
+fn frame(game: &Game, player: usize, description: String) -> Frame {
+    Frame{
+        player: player,
+        description: description,
+        map: game.display_map(),
+        scores: game.players().iter().skip(1).map(|player| (player.name.clone(), player.score())).collect(),
+    }
+}
+
+
+
+// ----------------------------------------------------------------------------------
+// Private functions test
+
+#[test]
+fn test_action_lines() {
+    let events = vec![Event::Battle { winner: 1, loser: 2, points: 15 }, Event::Moved { player: 1, to: crate::Position(0, 1) }];
+    assert_eq!(action_lines(1, &Action::Move(Direction::Right), &events), vec!["move 1 right", "battle 1 2"]);
+    assert_eq!(action_lines(2, &Action::Roll, &[Event::DiceRolled { player: 2, value: 5 }]), vec!["roll 2 5"]);
+    assert_eq!(action_lines(2, &Action::Skip, &[Event::MoveSkipped(2)]), vec!["skip 2"]);
+    assert_eq!(outcomes(&[Event::Portal { player: 3, points: 50 }, Event::GameFinished(3)]), vec!["portal 3"]);
+    let events = vec![Event::Fight { attacker: 1, defender: 2, attack_roll: 5, defense_roll: 1 },
+        Event::Wounded { player: 2, damage: 5, health: 5 }, Event::Pushed { player: 2, to: crate::Position(0, 3) },
+        Event::Moved { player: 1, to: crate::Position(0, 2) }];
+    assert_eq!(action_lines(1, &Action::Move(Direction::Right), &events), vec!["move 1 right", "fight 1 2 5 1", "wounded 2 5", "pushed 2"]);
+    assert_eq!(action_lines(1, &Action::Path(vec![Direction::Right]), &events), Vec::<String>::new());
+}
+
+#[test]
+fn test_fight_rolls() {
+    assert_eq!(fight_rolls("fight 1 2 5 1", 6), Some(vec![5, 1]));
+    assert_eq!(fight_rolls("fight 1 2 7 1", 6), None);
+    assert_eq!(fight_rolls("fight 1 2 0 1", 6), None);
+    assert_eq!(fight_rolls("battle 1 2", 6), None);
+}
@@ -0,0 +1,287 @@
+use crate::project_errors::RulesError;
+use crate::modes::Mode;
+use crate::{generators, spawns, MIN_PLAYERS, MAX_PLAYERS, PLAYER_LIMIT, MAP_SIZE, BATTLE_SCORE, PORTAL_SCORE, DICE_SIDES, VIEW_RADIUS};
+use crate::{HEALTH, ATTACK, DEFENSE, TREASURE_SCORE, TARGET_SCORE, ROUNDS, TIME_LIMIT};
+use std::str::FromStr;
+
+/// The rules of a game. The defaults are the crate's constants.
+/// Rules are read from a config file with one `key = value` pair per line
+/// (lines starting with '#' are comments) and can be overridden with command line flags,
+/// e.g. the `battle_score` key is set with `--battle-score 20`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRules {
+    pub map_width: usize,
+    pub map_height: usize,
+    pub generator: String,
+    pub spawn: String,
+    pub battle_score: u32,
+    pub portal_score: u32,
+    pub dice_sides: usize,
+    pub min_players: usize,
+    pub max_players: usize,
+    pub view_radius: usize,
+    pub combat: bool,
+    pub health: u32,
+    pub attack: u32,
+    pub defense: u32,
+    pub items: usize,
+    pub treasure_score: u32,
+    pub locked_portal: bool,
+    /// The number of teams, 0 plays everyone for themselves.
+    pub teams: usize,
+    /// Walking into a teammate swaps the two players, otherwise the teammate blocks the way.
+    pub swap_teammates: bool,
+    /// How the game is won.
+    pub mode: Mode,
+    pub target_score: u32,
+    pub rounds: usize,
+    /// Minutes of a timed game.
+    pub time_limit: usize,
+}
+
+/// Keys of the rules with a short description, in the order they are shown to the players.
+pub const RULES: [(&str, &str); 23] = [
+    ("map_width", "Map width"),
+    ("map_height", "Map height"),
+    ("generator", "Maze generator"),
+    ("spawn", "Where the players and the portal start"),
+    ("battle_score", "Points for winning a battle"),
+    ("portal_score", "Points for finding the portal"),
+    ("dice_sides", "Sides of the dice"),
+    ("min_players", "Minimum players"),
+    ("max_players", "Maximum players (at most 34)"),
+    ("view_radius", "How far the players see (0 shows the whole map)"),
+    ("combat", "Battles with health and dice (false kills the attacked player)"),
+    ("health", "Health of the players"),
+    ("attack", "Attack of the players"),
+    ("defense", "Defense of the players"),
+    ("items", "Items scattered in the labyrinth"),
+    ("treasure_score", "Points for picking up a treasure"),
+    ("locked_portal", "The portal only opens with a key"),
+    ("teams", "Teams of players (0 plays everyone for themselves)"),
+    ("swap_teammates", "Walking into a teammate swaps places (false blocks the way)"),
+    ("mode", "How the game is won (portal, last-standing, points, exit-all, rounds, timed)"),
+    ("target_score", "Points that win the game in the points mode"),
+    ("rounds", "Rounds of the rounds mode"),
+    ("time_limit", "Minutes of the timed mode"),
+];
+
+/// The smallest map that fits all the players and the portal.
+pub const MIN_MAP_SIZE: usize = 3;
+
+impl Default for GameRules {
+    fn default() -> GameRules {
+        GameRules{
+            map_width: MAP_SIZE,
+            map_height: MAP_SIZE,
+            generator: "backtracker".to_string(),
+            spawn: "random".to_string(),
+            battle_score: BATTLE_SCORE,
+            portal_score: PORTAL_SCORE,
+            dice_sides: DICE_SIDES,
+            min_players: MIN_PLAYERS,
+            max_players: MAX_PLAYERS,
+            view_radius: VIEW_RADIUS,
+            combat: false,
+            health: HEALTH,
+            attack: ATTACK,
+            defense: DEFENSE,
+            items: 0,
+            treasure_score: TREASURE_SCORE,
+            locked_portal: false,
+            teams: 0,
+            swap_teammates: true,
+            mode: Mode::Portal,
+            target_score: TARGET_SCORE,
+            rounds: ROUNDS,
+            time_limit: TIME_LIMIT,
+        }
+    }
+}
+
+/// Constructors
+impl GameRules {
+    /// Default rules with a square map of the given size.
+    pub fn with_size(size: usize) -> GameRules {
+        let mut rules = GameRules::default();
+        rules.map_width = size;
+        rules.map_height = size;
+        return rules;
+    }
+
+    /// Loads the rules from a config file. Missing keys keep their default values.
+    pub fn load(path: &str) -> Result<GameRules, RulesError> {
+        let content = std::fs::read_to_string(path)?;
+        return content.parse::<GameRules>();
+    }
+}
+
+/// Class methods
+impl GameRules {
+    /// Returns the value of a rule as a string.
+    pub fn get(&self, key: &str) -> Option<String> {
+        match key {
+            "map_width" => return Some(self.map_width.to_string()),
+            "map_height" => return Some(self.map_height.to_string()),
+            "generator" => return Some(self.generator.clone()),
+            "spawn" => return Some(self.spawn.clone()),
+            "battle_score" => return Some(self.battle_score.to_string()),
+            "portal_score" => return Some(self.portal_score.to_string()),
+            "dice_sides" => return Some(self.dice_sides.to_string()),
+            "min_players" => return Some(self.min_players.to_string()),
+            "max_players" => return Some(self.max_players.to_string()),
+            "view_radius" => return Some(self.view_radius.to_string()),
+            "combat" => return Some(self.combat.to_string()),
+            "health" => return Some(self.health.to_string()),
+            "attack" => return Some(self.attack.to_string()),
+            "defense" => return Some(self.defense.to_string()),
+            "items" => return Some(self.items.to_string()),
+            "treasure_score" => return Some(self.treasure_score.to_string()),
+            "locked_portal" => return Some(self.locked_portal.to_string()),
+            "teams" => return Some(self.teams.to_string()),
+            "swap_teammates" => return Some(self.swap_teammates.to_string()),
+            "mode" => return Some(self.mode.name().to_string()),
+            "target_score" => return Some(self.target_score.to_string()),
+            "rounds" => return Some(self.rounds.to_string()),
+            "time_limit" => return Some(self.time_limit.to_string()),
+            _ => return None,
+        }
+    }
+
+    /// Sets a rule from a string. Returns an error message if the key or the value is invalid.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        match key {
+            "map_width" => self.map_width = parse_value(key, value)?,
+            "map_height" => self.map_height = parse_value(key, value)?,
+            "generator" => {
+                if generators::by_name(value).is_none() {
+                    return Err(format!("unknown generator `{}`", value));
+                }
+                self.generator = value.to_string();
+            },
+            "spawn" => {
+                if spawns::by_name(value).is_none() {
+                    return Err(format!("unknown spawn strategy `{}`", value));
+                }
+                self.spawn = value.to_string();
+            },
+            "battle_score" => self.battle_score = parse_value(key, value)?,
+            "portal_score" => self.portal_score = parse_value(key, value)?,
+            "dice_sides" => self.dice_sides = parse_value(key, value)?,
+            "min_players" => self.min_players = parse_value(key, value)?,
+            "max_players" => self.max_players = parse_value(key, value)?,
+            "view_radius" => self.view_radius = parse_value(key, value)?,
+            "combat" => self.combat = parse_value(key, value)?,
+            "health" => self.health = parse_value(key, value)?,
+            "attack" => self.attack = parse_value(key, value)?,
+            "defense" => self.defense = parse_value(key, value)?,
+            "items" => self.items = parse_value(key, value)?,
+            "treasure_score" => self.treasure_score = parse_value(key, value)?,
+            "locked_portal" => self.locked_portal = parse_value(key, value)?,
+            "teams" => self.teams = parse_value(key, value)?,
+            "swap_teammates" => self.swap_teammates = parse_value(key, value)?,
+            "mode" => match Mode::from_name(value) {
+                Some(mode) => self.mode = mode,
+                None => return Err(format!("unknown game mode `{}`", value)),
+            },
+            "target_score" => self.target_score = parse_value(key, value)?,
+            "rounds" => self.rounds = parse_value(key, value)?,
+            "time_limit" => self.time_limit = parse_value(key, value)?,
+            _ => return Err(format!("unknown rule `{}`", key)),
+        }
+        return Ok(());
+    }
+
+    /// Checks if a game can be played with these rules.
+    pub fn validate(&self) -> Result<(), RulesError> {
+        if self.map_width < MIN_MAP_SIZE || self.map_height < MIN_MAP_SIZE {
+            return Err(RulesError::Invalid(format!("the map must be at least {}x{}", MIN_MAP_SIZE, MIN_MAP_SIZE)));
+        }
+        if self.dice_sides == 0 {
+            return Err(RulesError::Invalid("the dice must have at least one side".to_string()));
+        }
+        if self.min_players < MIN_PLAYERS {
+            return Err(RulesError::Invalid(format!("a game needs at least {} players", MIN_PLAYERS)));
+        }
+        if self.min_players > self.max_players {
+            return Err(RulesError::Invalid("invalid player limits".to_string()));
+        }
+        if self.max_players > PLAYER_LIMIT {
+            return Err(RulesError::Invalid(format!("at most {} players are supported", PLAYER_LIMIT)));
+        }
+        if generators::min_empty_fields(self.map_width, self.map_height) < self.max_players + 1 {
+            return Err(RulesError::Invalid(format!("the map is too small for {} players and the portal", self.max_players)));
+        }
+        if self.teams == 1 || self.teams > self.max_players {
+            return Err(RulesError::Invalid("there must be at least 2 teams and at most one for every player".to_string()));
+        }
+        if self.target_score == 0 || self.rounds == 0 || self.time_limit == 0 {
+            return Err(RulesError::Invalid("the target score, the rounds and the time limit must be positive".to_string()));
+        }
+        if self.combat && self.health == 0 {
+            return Err(RulesError::Invalid("the players must have some health".to_string()));
+        }
+        return Ok(());
+    }
+
+    /// Returns the config file representation of the rules.
+    pub fn display(&self) -> String {
+        let mut result = String::new();
+        for (key, _) in RULES.iter() {
+            result.push_str(&format!("{} = {}\n", key, self.get(key).unwrap()));
+        }
+        return result;
+    }
+}
+
+impl FromStr for GameRules {
+    type Err = RulesError;
+
+    fn from_str(content: &str) -> Result<GameRules, RulesError> {
+        let mut rules = GameRules::default();
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => rules.set(key.trim(), value)
+                    .map_err(|message| RulesError::InvalidLine(i + 1, message))?,
+                _ => return Err(RulesError::InvalidLine(i + 1, "expected `key = value`".to_string())),
+            }
+        }
+        return Ok(rules);
+    }
+}
+
+/// Returns the rule key set by a command line flag, e.g. `--battle-score` sets `battle_score`.
+pub fn flag_key(flag: &str) -> Option<String> {
+    if !flag.starts_with("--") {
+        return None;
+    }
+    let key = flag[2..].replace('-', "_");
+    for (rule, _) in RULES.iter() {
+        if *rule == key {
+            return Some(key);
+        }
+    }
+    return None;
+}
+
+fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    return value.parse::<T>().map_err(|_| format!("invalid value for `{}`: `{}`", key, value));
+}
+
+
+
+// ----------------------------------------------------------------------------------
+// Private functions test
+
+#[test]
+fn test_parse_value() {
+    assert_eq!(parse_value::<usize>("map_width", "12"), Ok(12));
+    assert!(parse_value::<usize>("map_width", "twelve").is_err());
+    assert!(parse_value::<u32>("battle_score", "-1").is_err());
+}
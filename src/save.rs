@@ -0,0 +1,468 @@
+use crate::game::Game;
+use crate::map::Map;
+use crate::player::{Player, PinHash};
+use crate::project_errors::SaveError;
+use crate::rules::GameRules;
+use crate::ai::PlayerKind;
+use crate::items::Item;
+use crate::tiles::Tile;
+use crate::random::GameRng;
+use crate::{Position, PLAYERCODES, PLAYER_LIMIT};
+use std::time::Duration;
+
+/// Save file format:
+///    labyrinth-save <version>
+///    seed <the seed the game was created with>
+///    rng <seed> <draws>                      - the random generator: the seed it started from and the words drawn since,
+///                                             only the seed before version 13
+///    size <map width> <map height>           - a single number for square maps before version 3
+///    turn <code of the player on turn>
+///    moves <moves left from the dice>
+///    round <the round being played>        - since version 11
+///    time <seconds played>                  - since version 11
+///    rule <key> = <value>                   - one line per rule, as in a rules file (since version 2)
+///    player <code> <score> <x> <y> <kind> <name>
+///                                           - one line per player, `- -` for dead players, no kind before version 4
+///    explored <code> <fields>               - the fields a player has explored, one `0` or `1` per field
+///                                             row by row, only with the fog of war (since version 5)
+///    pin <code> <salt> <hash>               - the hashed PINs of the players who have one, both numbers in hex
+///                                             (since version 6, the PIN itself before version 12)
+///    stats <code> <health> <attack> <defense>
+///                                           - one line per player, only with the combat rule (since version 7)
+///    items <code> <item>...                 - the inventories of the players who have some items (since version 8)
+///    team <code> <team>                     - one line per player, only in a team game (since version 10)
+///    exits <code>...                        - the players who walked through the portal in the order they did,
+///                                             only if somebody did (since version 11)
+///    map
+///    <height lines with the map, as shown by Map::display>     - with the items since version 8, the tiles since version 9
+///    terrain                                - only if a player or an item hides a tile other than an empty field (since version 9)
+///    <height lines with the tiles under the players and the items>
+pub const SAVE_HEADER: &str = "labyrinth-save";
+pub const SAVE_VERSION: u32 = 13;
+
+/// Returns the save file representation of a game.
+pub fn serialize(game: &Game) -> String {
+    let mut result = format!("{} {}\n", SAVE_HEADER, SAVE_VERSION);
+    result.push_str(&format!("seed {}\n", game.seed()));
+    let (rng_seed, draws) = game.rng_state();
+    result.push_str(&format!("rng {} {}\n", rng_seed, draws));
+    result.push_str(&format!("size {} {}\n", game.map_width, game.map_height));
+    result.push_str(&format!("turn {}\n", game.turn()));
+    result.push_str(&format!("moves {}\n", game.moves_left()));
+    result.push_str(&format!("round {}\n", game.round()));
+    result.push_str(&format!("time {}\n", game.elapsed().as_secs()));
+    for rule in game.rules().display().lines() {
+        result.push_str(&format!("rule {}\n", rule));
+    }
+    for player in game.players().iter().skip(1) {
+        let coordinates = match player.coordinates {
+            Some(Position(x, y)) => format!("{} {}", x, y),
+            None => "- -".to_string(),
+        };
+        result.push_str(&format!("player {} {} {} {} {}\n", player.player_code, player.score(), coordinates,
+            player.kind.name(), player.name));
+    }
+    if game.rules().view_radius > 0 {
+        for player in game.players().iter().skip(1) {
+            let mut fields = String::new();
+            for x in 0..game.map_height {
+                for y in 0..game.map_width {
+                    fields.push(if player.has_explored(Position(x, y)) { '1' } else { '0' });
+                }
+            }
+            result.push_str(&format!("explored {} {}\n", player.player_code, fields));
+        }
+    }
+    for player in game.players().iter().skip(1) {
+        if let Some(pin) = &player.pin {
+            result.push_str(&format!("pin {} {:016x} {:016x}\n", player.player_code, pin.salt, pin.hash));
+        }
+    }
+    if game.rules().combat {
+        for player in game.players().iter().skip(1) {
+            result.push_str(&format!("stats {} {} {} {}\n", player.player_code, player.health, player.attack, player.defense));
+        }
+    }
+    for player in game.players().iter().skip(1) {
+        if !player.items().is_empty() {
+            let items: Vec<&str> = player.items().iter().map(|item| item.name()).collect();
+            result.push_str(&format!("items {} {}\n", player.player_code, items.join(" ")));
+        }
+    }
+    if game.rules().teams > 0 {
+        for player in game.players().iter().skip(1) {
+            result.push_str(&format!("team {} {}\n", player.player_code, player.team));
+        }
+    }
+    if !game.exits().is_empty() {
+        let exits: Vec<String> = game.exits().iter().map(|code| code.to_string()).collect();
+        result.push_str(&format!("exits {}\n", exits.join(" ")));
+    }
+    result.push_str("map\n");
+    result.push_str(&game.display_map());
+    let map = game.map();
+    if map.occupants().iter().any(|(position, _)| map.tile(*position) != Tile::Floor) {
+        result.push_str("terrain\n");
+        for x in 0..game.map_height {
+            for y in 0..game.map_width {
+                result.push(map.tile(Position(x, y)).symbol());
+                result.push(' ');
+            }
+            result.push('\n');
+        }
+    }
+    return result;
+}
+
+/// Reconstructs a game from its save file representation.
+pub fn deserialize(content: &str) -> Result<Game, SaveError> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut line: usize = 0;
+
+    let header = next_line(&lines, &mut line)?;
+    let version: u32 = match header.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        [SAVE_HEADER, version] => match version.parse::<u32>() {
+            Ok(number) if number >= 1 && number <= SAVE_VERSION => number,
+            _ => return Err(SaveError::UnsupportedVersion(version.to_string())),
+        },
+        _ => return Err(SaveError::InvalidFormat(line, "not a labyrinth save file".to_string())),
+    };
+
+    let seed: u64 = read_value(&lines, &mut line, "seed")?;
+    let rng_state: String = read_value(&lines, &mut line, "rng")?;
+    let rng = match rng_state.split_whitespace().map(|value| value.parse::<u64>()).collect::<Vec<_>>().as_slice() {
+        [Ok(rng_seed)] if version < 13 => GameRng::seed_from_u64(*rng_seed),
+        [Ok(rng_seed), Ok(draws)] if version >= 13 => GameRng::restore(*rng_seed, *draws),
+        _ => return Err(SaveError::InvalidFormat(line, "invalid value for `rng`".to_string())),
+    };
+    let size: String = read_value(&lines, &mut line, "size")?;
+    let (width, height) = match size.split_whitespace().map(|value| value.parse::<usize>()).collect::<Vec<_>>().as_slice() {
+        [Ok(size)] if version < 3 => (*size, *size),
+        [Ok(width), Ok(height)] if version >= 3 => (*width, *height),
+        _ => return Err(SaveError::InvalidFormat(line, "invalid value for `size`".to_string())),
+    };
+    if width == 0 || height == 0 {
+        return Err(SaveError::InvalidFormat(line, "the map size must be positive".to_string()));
+    }
+    let turn: usize = read_value(&lines, &mut line, "turn")?;
+    let moves_left: usize = read_value(&lines, &mut line, "moves")?;
+    let (round, played): (usize, u64) = if version >= 11 {
+        (read_value(&lines, &mut line, "round")?, read_value(&lines, &mut line, "time")?)
+    } else {
+        (1, 0)
+    };
+    if round == 0 {
+        return Err(SaveError::InvalidFormat(line, "invalid value for `round`".to_string()));
+    }
+
+    let mut rules = GameRules::with_size(width);
+    rules.map_height = height;
+    if version >= 2 {
+        while lines.get(line).map_or(false, |l| l.starts_with("rule ")) {
+            let rule = &next_line(&lines, &mut line)?["rule ".len()..];
+            rules = read_rule(rules, rule).map_err(|message| SaveError::InvalidFormat(line, message))?;
+        }
+        if rules.map_width != width || rules.map_height != height {
+            return Err(SaveError::InvalidFormat(line, "the rules don't match the map size".to_string()));
+        }
+    }
+
+    let mut players: Vec<Player> = vec![Player::new(String::new())];
+    while lines.get(line).map_or(false, |l| l.starts_with("player ")) {
+        let player = read_player(next_line(&lines, &mut line)?, players.len(), width, height, version >= 4)
+            .map_err(|message| SaveError::InvalidFormat(line, message))?;
+        players.push(player);
+    }
+    if players.len() < 2 || players.len() > PLAYER_LIMIT + 1 {
+        return Err(SaveError::InvalidFormat(line, format!("expected between 1 and {} players", PLAYER_LIMIT)));
+    }
+    if turn == 0 || turn >= players.len() {
+        return Err(SaveError::InvalidFormat(line, format!("invalid player on turn: {}", turn)));
+    }
+
+    while lines.get(line).map_or(false, |l| l.starts_with("explored ")) {
+        read_explored(next_line(&lines, &mut line)?, &mut players, width, height)
+            .map_err(|message| SaveError::InvalidFormat(line, message))?;
+    }
+
+    while lines.get(line).map_or(false, |l| l.starts_with("pin ")) {
+        let parts: Vec<&str> = next_line(&lines, &mut line)?.splitn(3, ' ').collect();
+        let pin = match parts.get(2) {
+            Some(pin) if version < 12 && !pin.is_empty() => Some(PinHash::new(pin)),
+            Some(hash) if version >= 12 => read_pin_hash(hash),
+            _ => None,
+        };
+        match (parts.get(1).map(|code| code.parse::<usize>()), pin) {
+            (Some(Ok(code)), Some(pin)) if code >= 1 && code < players.len() => players[code].pin = Some(pin),
+            _ => return Err(SaveError::InvalidFormat(line, "expected `pin <code> <salt> <hash>`".to_string())),
+        }
+    }
+
+    for player in players.iter_mut() {
+        player.set_stats(rules.health, rules.attack, rules.defense);
+    }
+    while lines.get(line).map_or(false, |l| l.starts_with("stats ")) {
+        let parts: Vec<&str> = next_line(&lines, &mut line)?.split_whitespace().collect();
+        let values: Vec<Option<u32>> = parts.iter().skip(1).map(|value| value.parse::<u32>().ok()).collect();
+        match values.as_slice() {
+            [Some(code), Some(health), Some(attack), Some(defense)] if *code >= 1 && (*code as usize) < players.len() =>
+                players[*code as usize].set_stats(*health, *attack, *defense),
+            _ => return Err(SaveError::InvalidFormat(line, "expected `stats <code> <health> <attack> <defense>`".to_string())),
+        }
+    }
+
+    while lines.get(line).map_or(false, |l| l.starts_with("items ")) {
+        let parts: Vec<&str> = next_line(&lines, &mut line)?.split_whitespace().collect();
+        let items: Option<Vec<Item>> = parts.iter().skip(2).map(|name| name.parse::<Item>().ok()).collect();
+        match (parts.get(1).map(|code| code.parse::<usize>()), items) {
+            (Some(Ok(code)), Some(items)) if code >= 1 && code < players.len() && !items.is_empty() => {
+                for item in items {
+                    players[code].add_item(item);
+                }
+            },
+            _ => return Err(SaveError::InvalidFormat(line, "expected `items <code> <item>...`".to_string())),
+        }
+    }
+
+    while lines.get(line).map_or(false, |l| l.starts_with("team ")) {
+        let parts: Vec<&str> = next_line(&lines, &mut line)?.split_whitespace().collect();
+        let values: Vec<Option<usize>> = parts.iter().skip(1).map(|value| value.parse::<usize>().ok()).collect();
+        match values.as_slice() {
+            [Some(code), Some(team)] if *code >= 1 && *code < players.len() && *team >= 1 && *team <= rules.teams =>
+                players[*code].team = *team,
+            _ => return Err(SaveError::InvalidFormat(line, "expected `team <code> <team>`".to_string())),
+        }
+    }
+    if rules.teams > 0 && players.iter().skip(1).any(|player| player.team == 0) {
+        return Err(SaveError::InvalidFormat(line, format!("expected a team between 1 and {} for every player", rules.teams)));
+    }
+
+    let mut exits: Vec<usize> = Vec::new();
+    if lines.get(line).map_or(false, |l| l.starts_with("exits ")) {
+        let parts: Vec<&str> = next_line(&lines, &mut line)?.split_whitespace().collect();
+        for part in parts.iter().skip(1) {
+            match part.parse::<usize>() {
+                Ok(code) if code >= 1 && code < players.len() && players[code].coordinates.is_none() && !exits.contains(&code) =>
+                    exits.push(code),
+                _ => return Err(SaveError::InvalidFormat(line, "expected `exits <code>...`".to_string())),
+            }
+        }
+    }
+
+    if next_line(&lines, &mut line)? != "map" {
+        return Err(SaveError::InvalidFormat(line, "expected `map`".to_string()));
+    }
+    let mut fields: Vec<Vec<char>> = Vec::new();
+    for _ in 0..height {
+        let row: Vec<char> = read_symbols(next_line(&lines, &mut line)?)
+            .map_err(|message| SaveError::InvalidFormat(line, message))?.into_iter()
+            // The compass was shown as `^` before the arrows took the symbol in version 9.
+            .map(|symbol| if version < 9 && symbol == '^' { Item::Compass.symbol() } else { symbol }).collect();
+        if row.len() != width {
+            return Err(SaveError::InvalidFormat(line, format!("expected {} fields, found {}", width, row.len())));
+        }
+        for symbol in row.iter() {
+            if Tile::from_symbol(*symbol).is_none() && !PLAYERCODES[1..players.len()].contains(symbol)
+                && Item::from_symbol(*symbol).is_none() {
+                return Err(SaveError::InvalidFormat(line, format!("unknown field `{}`", symbol)));
+            }
+        }
+        fields.push(row);
+    }
+
+    let mut map = Map::from_fields(&fields);
+    if lines.get(line).map_or(false, |l| l.trim_end() == "terrain") {
+        line += 1;
+        for x in 0..height {
+            let row: Vec<Option<Tile>> = read_symbols(next_line(&lines, &mut line)?)
+                .map_err(|message| SaveError::InvalidFormat(line, message))?.into_iter()
+                .map(Tile::from_symbol).collect();
+            if row.len() != width || row.iter().any(|tile| tile.is_none()) {
+                return Err(SaveError::InvalidFormat(line, format!("expected {} tiles", width)));
+            }
+            for (y, tile) in row.iter().enumerate() {
+                map.set_tile(Position(x, y), tile.unwrap());
+            }
+        }
+    }
+    for player in players.iter().skip(1) {
+        if let Some(position) = player.coordinates {
+            if map.player_at(position) != Some(player.player_code) {
+                return Err(SaveError::InvalidFormat(line, format!("player {} is not on the map", player.player_code)));
+            }
+        }
+    }
+
+    if let Some(portal) = map.portal() {
+        for player in players.iter().skip(1) {
+            if let Some(position) = player.coordinates {
+                if map.unreachable(position, &[portal]).is_some() {
+                    return Err(SaveError::InvalidFormat(line, format!("player {} can't reach the portal", player.player_code)));
+                }
+            }
+        }
+    }
+
+    let mut game = Game::from_parts(map, players, rules, seed, rng, turn, moves_left);
+    game.set_progress(round, exits, Duration::from_secs(played));
+    return Ok(game);
+}
+
+fn next_line<'a>(lines: &[&'a str], line: &mut usize) -> Result<&'a str, SaveError> {
+    match lines.get(*line) {
+        Some(content) => {
+            *line += 1;
+            return Ok(content.trim_end());
+        },
+        None => return Err(SaveError::InvalidFormat(*line + 1, "unexpected end of file".to_string())),
+    }
+}
+
+fn read_value<T: std::str::FromStr>(lines: &[&str], line: &mut usize, key: &str) -> Result<T, SaveError> {
+    let content = next_line(lines, line)?;
+    let mut parts = content.splitn(2, ' ');
+    if parts.next() != Some(key) {
+        return Err(SaveError::InvalidFormat(*line, format!("expected `{}`", key)));
+    }
+    match parts.next().map(|value| value.trim().parse::<T>()) {
+        Some(Ok(value)) => return Ok(value),
+        _ => return Err(SaveError::InvalidFormat(*line, format!("invalid value for `{}`", key))),
+    }
+}
+
+fn read_rule(rules: GameRules, rule: &str) -> Result<GameRules, String> {
+    let mut rules = rules;
+    let mut parts = rule.splitn(2, '=');
+    match (parts.next(), parts.next()) {
+        (Some(key), Some(value)) => rules.set(key.trim(), value)?,
+        _ => return Err("expected `rule <key> = <value>`".to_string()),
+    }
+    return Ok(rules);
+}
+
+/// Reads the `<salt> <hash>` of a hashed PIN.
+fn read_pin_hash(content: &str) -> Option<PinHash> {
+    let numbers: Vec<Result<u64, _>> = content.split(' ').map(|number| u64::from_str_radix(number, 16)).collect();
+    match numbers.as_slice() {
+        [Ok(salt), Ok(hash)] => return Some(PinHash{ salt: *salt, hash: *hash }),
+        _ => return None,
+    }
+}
+
+/// Splits a row of the map into its symbols, which are single characters separated by spaces.
+fn read_symbols(content: &str) -> Result<Vec<char>, String> {
+    let mut result: Vec<char> = Vec::new();
+    for symbol in content.split_whitespace() {
+        let mut chars = symbol.chars();
+        match (chars.next(), chars.next()) {
+            (Some(field), None) => result.push(field),
+            _ => return Err(format!("unknown field `{}`", symbol)),
+        }
+    }
+    return Ok(result);
+}
+
+fn read_player(content: &str, expected_code: usize, width: usize, height: usize, with_kind: bool) -> Result<Player, String> {
+    let fields = if with_kind { 7 } else { 6 };
+    let parts: Vec<&str> = content.splitn(fields, ' ').collect();
+    if parts.len() != fields || parts[fields - 1].is_empty() {
+        return Err("expected `player <code> <score> <x> <y> <kind> <name>`".to_string());
+    }
+    if parts[1].parse::<usize>() != Ok(expected_code) {
+        return Err(format!("expected player {}", expected_code));
+    }
+    let score = parts[2].parse::<u32>().map_err(|_| "invalid score".to_string())?;
+
+    let mut player = Player::with_code(parts[fields - 1].to_string(), expected_code);
+    player.win_points(score);
+    if with_kind {
+        player.kind = PlayerKind::from_name(parts[5]).ok_or(format!("unknown player kind `{}`", parts[5]))?;
+    }
+    if parts[3] != "-" || parts[4] != "-" {
+        match (parts[3].parse::<usize>(), parts[4].parse::<usize>()) {
+            (Ok(x), Ok(y)) if x < height && y < width => player.spawn(Position(x, y)),
+            _ => return Err("invalid coordinates".to_string()),
+        }
+    }
+    return Ok(player);
+}
+
+fn read_explored(content: &str, players: &mut Vec<Player>, width: usize, height: usize) -> Result<(), String> {
+    let parts: Vec<&str> = content.split(' ').collect();
+    if parts.len() != 3 {
+        return Err("expected `explored <code> <fields>`".to_string());
+    }
+    let player = match parts[1].parse::<usize>() {
+        Ok(code) if code >= 1 && code < players.len() => &mut players[code],
+        _ => return Err(format!("invalid player `{}`", parts[1])),
+    };
+    let fields: Vec<char> = parts[2].chars().collect();
+    if fields.len() != width * height || fields.iter().any(|field| *field != '0' && *field != '1') {
+        return Err(format!("expected {} explored fields", width * height));
+    }
+    for (i, field) in fields.iter().enumerate() {
+        if *field == '1' {
+            player.explore(Position(i / width, i % width));
+        }
+    }
+    return Ok(());
+}
+
+
+
+// ----------------------------------------------------------------------------------
+// Private functions test
+
+#[test]
+fn test_read_rule() {
+    let rules = read_rule(GameRules::default(), "battle_score = 20").ok().unwrap();
+    assert_eq!(rules.battle_score, 20);
+    assert!(read_rule(GameRules::default(), "battle_score 20").is_err());
+    assert!(read_rule(GameRules::default(), "speed = 20").is_err());
+}
+
+#[test]
+fn test_read_symbols() {
+    assert_eq!(read_symbols("1 . # 0"), Ok(vec!['1', '.', '#', '0']));
+    assert_eq!(read_symbols(""), Ok(vec![]));
+    assert!(read_symbols("1 .# 0").is_err());
+}
+
+#[test]
+fn test_read_player() {
+    let player = read_player("player 2 15 3 4 hunter John Smith", 2, 10, 5, true).ok().unwrap();
+    assert_eq!(player.name, "John Smith");
+    assert_eq!(player.player_code, 2);
+    assert_eq!(player.score(), 15);
+    assert_eq!(player.coordinates, Some(Position(3, 4)));
+    assert_eq!(player.kind, PlayerKind::HunterBot);
+}
+
+#[test]
+fn test_read_dead_player() {
+    let player = read_player("player 1 0 - - John", 1, 10, 5, false).ok().unwrap();
+    assert_eq!(player.coordinates, None);
+}
+
+#[test]
+fn test_read_bad_player() {
+    assert!(read_player("player 1 0 - -", 1, 10, 5, false).is_err());
+    assert!(read_player("player 2 0 - - John", 1, 10, 5, false).is_err());
+    assert!(read_player("player 1 zero - - John", 1, 10, 5, false).is_err());
+    assert!(read_player("player 1 0 5 2 John", 1, 10, 5, false).is_err());
+    assert!(read_player("player 1 0 2 10 John", 1, 10, 5, false).is_err());
+    assert!(read_player("player 1 0 - - robot John", 1, 10, 5, true).is_err());
+    assert!(read_player("player 1 0 - - human", 1, 10, 5, true).is_err());
+}
+
+#[test]
+fn test_read_explored() {
+    let mut players = vec![Player::new(String::new()), Player::with_code("John".to_string(), 1)];
+    read_explored("explored 1 100001", &mut players, 3, 2).ok().unwrap();
+    assert_eq!(players[1].has_explored(Position(0, 0)), true);
+    assert_eq!(players[1].has_explored(Position(0, 1)), false);
+    assert_eq!(players[1].has_explored(Position(1, 2)), true);
+    assert!(read_explored("explored 2 100001", &mut players, 3, 2).is_err());
+    assert!(read_explored("explored 1 10001", &mut players, 3, 2).is_err());
+    assert!(read_explored("explored 1 100021", &mut players, 3, 2).is_err());
+}
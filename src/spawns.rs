@@ -0,0 +1,251 @@
+use rand::RngCore;
+use rand::seq::SliceRandom;
+use std::cmp::Reverse;
+use crate::map::Map;
+use crate::project_errors::GameError;
+use crate::Position;
+
+/// Strategies for choosing the fields the players and the portal start on.
+/// The fields a hand-authored map places are kept, a strategy only chooses the rest.
+/// All the distances are measured in steps through the labyrinth, see `Map::distances`.
+/// The players and the portal only start on fields that can be reached from each other, see `free_fields`.
+pub trait SpawnStrategy {
+    /// Chooses the starting fields of the players and the field of the portal among the empty fields of the map.
+    /// `players` has the fields the map places and None for the players the strategy places,
+    /// `portal` is the field of the portal if the map has one.
+    /// Returns `GameError::NoRoom` if there aren't enough empty fields.
+    fn place(&self, map: &Map, players: &[Option<Position>], portal: Option<Position>, rng: &mut dyn RngCore) -> Result<Spawns, GameError>;
+}
+
+/// The fields chosen by a `SpawnStrategy`, one for every player in the same order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spawns {
+    pub players: Vec<Position>,
+    pub portal: Position,
+}
+
+/// Names of the spawn strategies, as accepted by `by_name`.
+pub const SPAWN_STRATEGIES: [&str; 4] = ["random", "fair", "spread", "corners"];
+
+/// Returns a spawn strategy by its name or None if there is no such strategy.
+pub fn by_name(name: &str) -> Option<Box<dyn SpawnStrategy>> {
+    match name {
+        "random" => return Some(Box::new(RandomSpawns)),
+        "fair" => return Some(Box::new(FairSpawns)),
+        "spread" => return Some(Box::new(SpreadSpawns)),
+        "corners" => return Some(Box::new(CornerSpawns)),
+        _ => return None,
+    }
+}
+
+/// Uniformly random empty fields - a player may start right next to the portal or to another player.
+pub struct RandomSpawns;
+
+/// All the players start at the same distance from the portal, as far from it as the map allows.
+pub struct FairSpawns;
+
+/// The players start as far from each other as possible, the portal is equally far from all of them.
+pub struct SpreadSpawns;
+
+/// The players start in different corners of the map, the portal is equally far from all of them.
+pub struct CornerSpawns;
+
+/// How many random portal fields `FairSpawns` tries.
+const PORTAL_CANDIDATES: usize = 20;
+
+impl SpawnStrategy for RandomSpawns {
+    fn place(&self, map: &Map, players: &[Option<Position>], portal: Option<Position>, rng: &mut dyn RngCore) -> Result<Spawns, GameError> {
+        let mut taken: Vec<Position> = fixed(players, portal);
+        let mut result: Vec<Position> = Vec::new();
+        for player in players.iter() {
+            let position = match player {
+                Some(position) => *position,
+                None => random_field(map, &taken, rng)?,
+            };
+            taken.push(position);
+            result.push(position);
+        }
+        let portal = match portal {
+            Some(portal) => portal,
+            None => random_field(map, &taken, rng)?,
+        };
+        return Ok(Spawns{ players: result, portal: portal });
+    }
+}
+
+impl SpawnStrategy for FairSpawns {
+    fn place(&self, map: &Map, players: &[Option<Position>], portal: Option<Position>, rng: &mut dyn RngCore) -> Result<Spawns, GameError> {
+        let missing = players.iter().filter(|player| player.is_none()).count();
+        // The best portal so far, with the distance of the players from it and how far the fixed players are from that distance.
+        let mut best: Option<(Position, usize, usize, Vec<Position>)> = None;
+        let attempts = if portal.is_some() { 1 } else { PORTAL_CANDIDATES };
+        for _ in 0..attempts {
+            let candidate = match portal {
+                Some(portal) => portal,
+                None => random_field(map, &fixed(players, None), rng)?,
+            };
+            let distances = map.distances(candidate);
+            let mut taken = fixed(players, None);
+            taken.push(candidate);
+            // The free fields by their distance from the portal.
+            let mut rings: Vec<Vec<Position>> = Vec::new();
+            for position in free_fields(map, &taken) {
+                if let Some(distance) = distances.get(position) {
+                    if rings.len() <= distance {
+                        rings.resize(distance + 1, Vec::new());
+                    }
+                    rings[distance].push(position);
+                }
+            }
+            let fixed_distances: Vec<usize> = players.iter().flatten().filter_map(|player| distances.get(*player)).collect();
+            for (distance, ring) in rings.into_iter().enumerate().skip(1) {
+                if ring.len() < missing {
+                    continue;
+                }
+                let unfairness = fixed_distances.iter().map(|other| distance.abs_diff(*other)).max().unwrap_or(0);
+                let better = match &best {
+                    Some((_, best_distance, best_unfairness, _)) =>
+                        unfairness < *best_unfairness || (unfairness == *best_unfairness && distance > *best_distance),
+                    None => true,
+                };
+                if better {
+                    best = Some((candidate, distance, unfairness, ring));
+                }
+            }
+        }
+        match best {
+            Some((portal, _, _, mut ring)) => {
+                ring.shuffle(rng);
+                let mut ring = ring.into_iter();
+                let result = players.iter().map(|player| player.unwrap_or_else(|| ring.next().unwrap())).collect();
+                return Ok(Spawns{ players: result, portal: portal });
+            },
+            None => return RandomSpawns.place(map, players, portal, rng),
+        }
+    }
+}
+
+impl SpawnStrategy for SpreadSpawns {
+    fn place(&self, map: &Map, players: &[Option<Position>], portal: Option<Position>, rng: &mut dyn RngCore) -> Result<Spawns, GameError> {
+        let mut taken: Vec<Position> = fixed(players, portal);
+        let mut distances: Vec<_> = players.iter().flatten().map(|player| map.distances(*player)).collect();
+        let mut result: Vec<Position> = Vec::new();
+        for player in players.iter() {
+            let position = match player {
+                Some(position) => *position,
+                None if distances.is_empty() => random_field(map, &taken, rng)?,
+                None => {
+                    // The free fields farthest from the nearest player.
+                    let fields = best_fields(free_fields(map, &taken), |field| {
+                        let steps = distances.iter().map(|from| from.get(field)).collect::<Option<Vec<usize>>>()?;
+                        return Some(Reverse(*steps.iter().min()?));
+                    });
+                    match fields.choose(rng) {
+                        Some(position) => *position,
+                        None => random_field(map, &taken, rng)?,
+                    }
+                },
+            };
+            if player.is_none() {
+                distances.push(map.distances(position));
+            }
+            taken.push(position);
+            result.push(position);
+        }
+        let portal = match portal {
+            Some(portal) => portal,
+            None => fair_portal(map, &result, rng)?,
+        };
+        return Ok(Spawns{ players: result, portal: portal });
+    }
+}
+
+impl SpawnStrategy for CornerSpawns {
+    fn place(&self, map: &Map, players: &[Option<Position>], portal: Option<Position>, rng: &mut dyn RngCore) -> Result<Spawns, GameError> {
+        // Opposite corners first, so that two players start as far from each other as possible.
+        let mut corners = vec![Position(0, 0), Position(map.height - 1, map.width - 1),
+            Position(0, map.width - 1), Position(map.height - 1, 0)];
+        // The players the map places already have their corners.
+        for player in players.iter().flatten() {
+            if let Some(corner) = corners.iter().cloned().min_by_key(|corner| manhattan(*corner, *player)) {
+                corners.retain(|other| *other != corner);
+            }
+        }
+        let mut taken: Vec<Position> = fixed(players, portal);
+        let mut result: Vec<Position> = Vec::new();
+        for player in players.iter() {
+            let position = match (player, corners.first().cloned()) {
+                (Some(position), _) => *position,
+                (None, Some(corner)) => {
+                    corners.remove(0);
+                    match free_fields(map, &taken).into_iter().min_by_key(|field| manhattan(corner, *field)) {
+                        Some(position) => position,
+                        None => random_field(map, &taken, rng)?,
+                    }
+                },
+                (None, None) => random_field(map, &taken, rng)?,
+            };
+            taken.push(position);
+            result.push(position);
+        }
+        let portal = match portal {
+            Some(portal) => portal,
+            None => fair_portal(map, &result, rng)?,
+        };
+        return Ok(Spawns{ players: result, portal: portal });
+    }
+}
+
+/// Returns the fields that are already taken, the portal first.
+fn fixed(players: &[Option<Position>], portal: Option<Position>) -> Vec<Position> {
+    return portal.into_iter().chain(players.iter().flatten().cloned()).collect();
+}
+
+/// Returns the empty fields that aren't taken, row by row.
+/// Only the fields reachable from the first taken field count, so that nobody starts in a closed-off part of the map.
+fn free_fields(map: &Map, taken: &[Position]) -> Vec<Position> {
+    let reachable = taken.first().map(|first| map.distances(*first));
+    let mut result: Vec<Position> = Vec::new();
+    for x in 0..map.height {
+        for y in 0..map.width {
+            let position = Position(x, y);
+            if map.empty(x, y) && !taken.contains(&position)
+                && reachable.as_ref().map_or(true, |distances| distances.get(position).is_some()) {
+                result.push(position);
+            }
+        }
+    }
+    return result;
+}
+
+/// Chooses a random free field. Returns `GameError::NoRoom` if there is none.
+fn random_field(map: &Map, taken: &[Position], rng: &mut dyn RngCore) -> Result<Position, GameError> {
+    return free_fields(map, taken).choose(rng).cloned().ok_or(GameError::NoRoom);
+}
+
+/// Returns the fields with the lowest score. Fields without a score are left out.
+fn best_fields<K: Ord + Copy, F: Fn(Position) -> Option<K>>(fields: Vec<Position>, score: F) -> Vec<Position> {
+    let scores: Vec<(Position, K)> = fields.into_iter().filter_map(|field| score(field).map(|key| (field, key))).collect();
+    match scores.iter().map(|(_, key)| *key).min() {
+        Some(best) => return scores.into_iter().filter(|(_, key)| *key == best).map(|(field, _)| field).collect(),
+        None => return Vec::new(),
+    }
+}
+
+/// Chooses a free field for the portal with the smallest difference between the nearest and the farthest player.
+fn fair_portal(map: &Map, players: &[Position], rng: &mut dyn RngCore) -> Result<Position, GameError> {
+    let distances: Vec<_> = players.iter().map(|player| map.distances(*player)).collect();
+    let fields = best_fields(free_fields(map, players), |field| {
+        let steps: Vec<usize> = distances.iter().map(|from| from.get(field)).collect::<Option<Vec<usize>>>()?;
+        let (nearest, farthest) = (*steps.iter().min()?, *steps.iter().max()?);
+        return Some((farthest - nearest, farthest));
+    });
+    match fields.choose(rng) {
+        Some(position) => return Ok(*position),
+        None => return random_field(map, players, rng),
+    }
+}
+
+fn manhattan(a: Position, b: Position) -> usize {
+    return a.0.abs_diff(b.0) + a.1.abs_diff(b.1);
+}
@@ -0,0 +1,114 @@
+use crate::direction::Direction;
+use crate::FIELDS;
+use termion::color;
+
+/// The terrain of a field. Every tile has its own symbol on the map and its own movement rule.
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+pub enum Tile {
+    Floor,
+    Wall,
+    /// Walking through it takes the player out of the labyrinth, which wins the game in the default mode.
+    Portal,
+    /// Swimming through it takes two moves.
+    Water,
+    /// A player who steps in it is stuck for the rest of the turn.
+    Mud,
+    /// Can only be entered moving in the arrow's direction.
+    Arrow(Direction),
+    /// Takes the player to the other pad of its pair. Pads are paired in the order they are written on the map,
+    /// see `Map::teleport_pair`.
+    Teleport,
+    /// Walking into it breaks it, the player stays where it was. A broken wall becomes floor.
+    BreakableWall,
+    /// Only players with a key can walk through it. The key isn't used up.
+    Door,
+}
+
+impl Tile {
+    /// All the tiles, as shown in the map legend.
+    pub const ALL: [Tile; 12] = [Tile::Floor, Tile::Wall, Tile::Portal, Tile::Water, Tile::Mud,
+        Tile::Arrow(Direction::Up), Tile::Arrow(Direction::Right), Tile::Arrow(Direction::Down), Tile::Arrow(Direction::Left),
+        Tile::Teleport, Tile::BreakableWall, Tile::Door];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tile::Floor => return "an empty field",
+            Tile::Wall => return "a wall",
+            Tile::Portal => return "a portal",
+            Tile::Water => return "water",
+            Tile::Mud => return "mud",
+            Tile::Arrow(Direction::Up) => return "a one-way arrow up",
+            Tile::Arrow(Direction::Right) => return "a one-way arrow right",
+            Tile::Arrow(Direction::Down) => return "a one-way arrow down",
+            Tile::Arrow(Direction::Left) => return "a one-way arrow left",
+            Tile::Teleport => return "a teleport pad",
+            Tile::BreakableWall => return "a breakable wall",
+            Tile::Door => return "a door",
+        }
+    }
+
+    /// Returns the symbol of the tile on the map.
+    pub fn symbol(&self) -> char {
+        match self {
+            Tile::Floor => return FIELDS[0],
+            Tile::Wall => return FIELDS[1],
+            Tile::Portal => return FIELDS[2],
+            Tile::Water => return '~',
+            Tile::Mud => return ',',
+            Tile::Arrow(Direction::Up) => return '^',
+            Tile::Arrow(Direction::Right) => return '>',
+            Tile::Arrow(Direction::Down) => return 'v',
+            Tile::Arrow(Direction::Left) => return '<',
+            Tile::Teleport => return '@',
+            Tile::BreakableWall => return '=',
+            Tile::Door => return '+',
+        }
+    }
+
+    /// Returns the tile with this symbol on the map, or None if the symbol isn't a tile.
+    pub fn from_symbol(symbol: char) -> Option<Tile> {
+        return Tile::ALL.iter().cloned().find(|tile| tile.symbol() == symbol);
+    }
+
+    /// Returns the terminal color of the tile's symbol.
+    pub fn color(&self) -> String {
+        match self {
+            Tile::Floor => return color::Fg(color::Blue).to_string(),
+            Tile::Wall => return color::Fg(color::Reset).to_string(),
+            Tile::Portal => return color::Fg(color::LightCyan).to_string(),
+            Tile::Water => return color::Fg(color::LightBlue).to_string(),
+            Tile::Mud => return color::Fg(color::Yellow).to_string(),
+            Tile::Arrow(_) => return color::Fg(color::LightGreen).to_string(),
+            Tile::Teleport => return color::Fg(color::LightMagenta).to_string(),
+            Tile::BreakableWall => return color::Fg(color::LightBlack).to_string(),
+            Tile::Door => return color::Fg(color::Red).to_string(),
+        }
+    }
+
+    /// Checks if a player can ever get on the tile. Breakable walls and doors can be broken or opened.
+    pub fn is_passable(&self) -> bool {
+        return *self != Tile::Wall;
+    }
+
+    /// Checks if a player moving in a direction can get on the tile.
+    pub fn can_enter(&self, direction: Direction) -> bool {
+        match self {
+            Tile::Wall => return false,
+            Tile::Arrow(arrow) => return *arrow == direction,
+            _ => return true,
+        }
+    }
+
+    /// Returns the number of moves it takes to get on the tile.
+    pub fn cost(&self) -> usize {
+        match self {
+            Tile::Water => return 2,
+            _ => return 1,
+        }
+    }
+
+    /// Checks if the players can't see through the tile.
+    pub fn blocks_sight(&self) -> bool {
+        return *self == Tile::Wall || *self == Tile::BreakableWall || *self == Tile::Door;
+    }
+}
@@ -1,5 +1,17 @@
 use crate::*;
-use termion::color;
+use crate::rules::{GameRules, RULES};
+use crate::ai::{PlayerKind, PLAYER_KINDS};
+use crate::replay::Replay;
+use crate::game::Game;
+use crate::map::{Map, get_player};
+use crate::network::{Server, Client, ServerMessage};
+use crate::project_errors::{NetworkError, InputError, GameError};
+use crate::input::{KeyBindings, Command, read_command, read_line, parse_number};
+use crate::direction::{Direction, parse_path};
+use crate::items::Item;
+use crate::tiles::Tile;
+use crate::modes::Mode;
+use termion::{color, style};
 
 
 
@@ -7,9 +19,13 @@ fn greeting_message() {
     print!("Hello, my friend, and wellcome!\n\n");
 }
 
-fn allowed_directions() {
-    print!("Allowed directions are up ({color}{}{reset}), down ({color}{}{reset}), left ({color}{}{reset}) and right ({color}{}{reset}).\n",
-        directions::UP, directions::DOWN, directions::LEFT, directions::RIGHT,
+fn allowed_directions(bindings: &KeyBindings) {
+    print!("Allowed directions are up ({color}{}{reset}), down ({color}{}{reset}), left ({color}{}{reset}) and right ({color}{}{reset}), \
+        a single keypress for every step. Press {color}{}{reset} to enter the whole path at once, like `ddsaw`, \
+        or the field to go to, like `3,5`.\n",
+        bindings.describe(Command::Move(Direction::Up)), bindings.describe(Command::Move(Direction::Down)),
+        bindings.describe(Command::Move(Direction::Left)), bindings.describe(Command::Move(Direction::Right)),
+        bindings.describe(Command::Path),
         color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
 }
 
@@ -19,55 +35,177 @@ fn map_legend() {
     print!("   {color}{}{reset} - an empty field\n", FIELDS[0], color = color::Fg(color::Blue), reset = reset);
     print!("   {color}{}{reset} - a wall\n", FIELDS[1], color = color::Fg(color::LightYellow), reset = reset);
     print!("   {color}{}{reset} - a portal\n", FIELDS[2], color = color::Fg(color::LightCyan), reset = reset);
-    print!("   {{{color}number{reset}}} - the current player\n", color = color::Fg(color::LightGreen), reset = reset);
-    print!("   {{{color}number{reset}}} - the other players\n", color = color::Fg(color::LightRed), reset = reset);
+    for tile in Tile::ALL[3..].iter() {
+        print!("   {}{}{} - {}\n", tile.color(), tile.symbol(), reset, tile.name());
+    }
+    for item in Item::ALL.iter() {
+        print!("   {}{}{} - a {}\n", item.color(), item.symbol(), reset, item.name());
+    }
+    print!("   {}1{}-{}9{}, {}A{}-{}Z{} (no O) - the players, each in their own color, yours is {}highlighted{}\n",
+        player_color(1), reset, player_color(9), reset, player_color(10), reset, player_color(PLAYER_LIMIT), reset,
+        style::Invert, style::NoInvert);
+    print!("   {} - a field you haven't explored yet\n", HIDDEN_FIELD);
 }
 
-pub fn help() {
-    print!("The game can be played by between {} and {} players. ", MIN_PLAYERS, MAX_PLAYERS);
+pub fn help(rules: &GameRules, bindings: &KeyBindings) {
+    print!("The game can be played by between {} and {} players. ", rules.min_players, rules.max_players);
     print!("Their main goal is to exit a labyrinth by walking through a portal.\n");
     print!("In the beginning of the game the players and the portal are randomly located on a map.\n");
     map_legend();
+    if rules.view_radius > 0 {
+        print!("The labyrinth is covered in fog. You see {color}{}{reset} fields around you, unless there is a wall in the way, \
+            and remember the walls and the portal you have already seen.\n",
+            rules.view_radius, color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
+    }
     print!("The players take turns to roll a dice and make moves. ");
-    allowed_directions();
+    allowed_directions(bindings);
+    print!("Every player can be controlled by a human or a bot: {color}random{reset} walks randomly, \
+        {color}greedy{reset} goes straight for the portal and {color}hunter{reset} chases the other players.\n",
+        color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
     print!("If one player walks on a field of the map with another player on it, a battle begins. ");
-    print!("During the battle the attacked player is killed and the attacking player wins {color}{}{reset} points. ",
-        BATTLE_SCORE, color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
+    if rules.combat {
+        print!("Both players roll the dice, the attacker adds its attack and the defender its defense. \
+            The lower total loses the difference in health (everybody starts with {color}{}{reset}). \
+            A defender who survives is pushed back, a player without health left is killed \
+            and the other one wins {color}{}{reset} points. ",
+            rules.health, rules.battle_score, color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
+    } else {
+        print!("During the battle the attacked player is killed and the attacking player wins {color}{}{reset} points. ",
+            rules.battle_score, color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
+    }
     print!("If a player is dead, he can't make anymore moves, but he participate in the final ranklist with their current points.\n");
-    print!("If a player walks on a field with a portal on it, he wins {color}{}{reset} points and the game ends.\n",
-        PORTAL_SCORE, color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
+    print!("If a player walks on a field with a portal on it, he wins {color}{}{reset} points and leaves the labyrinth. ",
+        rules.portal_score, color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
+    match rules.mode {
+        Mode::LastStanding => print!("The last player left in the labyrinth wins, leaving through the portal gives up the game.\n"),
+        Mode::Points => print!("The first player to get {color}{}{reset} points wins.\n",
+            rules.target_score, color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset)),
+        Mode::ExitAll => print!("The game goes on until everybody alive is out, the players are ranked by the order they got out.\n"),
+        Mode::Rounds => print!("The game ends after {color}{}{reset} rounds, the most points win.\n",
+            rules.rounds, color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset)),
+        Mode::Timed => print!("The game ends with the first round after {color}{}{reset} minutes, the most points win.\n",
+            rules.time_limit, color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset)),
+        Mode::Portal => print!("The first player to do so wins and the game ends.\n"),
+    }
+    print!("Custom maps may have special fields: swimming through water takes two moves, mud ends your turn, \
+        arrows can only be walked along, a teleport pad takes you to its pair, walking into a breakable wall breaks it \
+        and a door only opens for a player with a key.\n");
+    if rules.items > 0 || rules.locked_portal {
+        print!("Walk on an item to pick it up. A treasure is worth {color}{}{reset} points, a shield saves you from being killed once \
+            and a key opens the portal. Press {color}{}{reset} on your turn to drink a potion for {color}{}{reset} more moves \
+            or to ask a compass the way to the portal, neither takes a move.\n",
+            rules.treasure_score, bindings.describe(Command::Use), POTION_MOVES,
+            color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
+    }
+    if rules.locked_portal {
+        print!("The portal is locked, you can only walk through it with a key.\n");
+    }
+    if rules.teams > 0 {
+        print!("The players play in {color}{}{reset} teams. Walking into a teammate {} instead of a battle. \
+            The team of the winner wins, and the teams are ranked by the sum of their players' points.\n",
+            rules.teams, if rules.swap_teammates { "swaps your places" } else { "is blocked" },
+            color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
+    }
+    print!("Instead of a direction you can press {color}{}{reset} to save the game and resume it later from the menu, \
+        or {color}{}{reset} to quit.\n",
+        bindings.describe(Command::Save), bindings.describe(Command::Quit),
+        color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
     pause();
 }
 
-pub fn menu() {
+/// Shows the main menu. Returns the path to a saved game if the player wants to resume one.
+pub fn menu(game_rules: &mut GameRules, bindings: &KeyBindings) -> Result<Option<String>, InputError> {
     let rules: &str = "r";
+    let configure: &str = "c";
+    let load: &str = "l";
     greeting_message();
     print!("If you want to read rules, enter {color}{}{reset}.\n", rules, color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
+    print!("If you want to change the rules, enter {color}{}{reset}.\n", configure, color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
+    print!("If you want to resume a saved game, enter {color}{}{reset}.\n", load, color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
     print!("If you want to start the game, press Enter.\n>>");
     flush();
-    let input = read_str();
+    let input = read_line()?;
     if input == rules {
-        help();
+        help(game_rules, bindings);
+    } else if input == configure {
+        configure_rules(game_rules)?;
+    } else if input == load {
+        let path = ask(format!("Enter file name (default {}): ", DEFAULT_SAVE_FILE))?;
+        if path.is_empty() {
+            return Ok(Some(DEFAULT_SAVE_FILE.to_string()));
+        }
+        return Ok(Some(path));
     }
+    return Ok(None);
 }
 
 
 
+/// Asks for every rule, keeping the current value on an empty input.
+fn configure_rules(rules: &mut GameRules) -> Result<(), InputError> {
+    clear();
+    loop {
+        for (key, description) in RULES.iter() {
+            loop {
+                let input = ask(format!("{} [{}]: ", description, rules.get(key).unwrap()))?;
+                if input.is_empty() {
+                    break;
+                }
+                match rules.set(key, &input) {
+                    Ok(_) => break,
+                    Err(message) => print_error(format!("Sorry! {}\n", message)),
+                }
+            }
+        }
+        match rules.validate() {
+            Ok(_) => break,
+            Err(e) => print_error(format!("Sorry! {}", e)),
+        }
+    }
+    clear();
+    return Ok(());
+}
+
 pub fn clear(){
     use termion::{clear, cursor};
     print!("{}{}", clear::All, cursor::Goto(1, 1));
 }
 
+/// Reads a line, leaving the game if the input has ended.
 pub fn read_str() -> String {
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input).unwrap();
-    return input.replace("\n", "");
+    return read_line().unwrap_or_else(|e| leave(e));
+}
+
+/// Shows a prompt and reads the answer.
+pub fn ask(prompt: String) -> Result<String, InputError> {
+    print_message(prompt);
+    flush();
+    return read_line();
 }
 
-pub fn read_num() -> usize {
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input).unwrap();
-    return input.replace("\n", "").parse::<usize>().unwrap();
+/// Asks for a number until the answer is between `min` and `max`.
+pub fn ask_number(prompt: String, min: usize, max: usize) -> Result<usize, InputError> {
+    loop {
+        match parse_number(&ask(prompt.clone())?, min, max) {
+            Ok(number) => return Ok(number),
+            Err(e) => print_error(format!("Sorry! {}", e)),
+        }
+    }
+}
+
+/// Reads a line without showing it on the screen. Falls back to a visible input without a terminal.
+/// Ctrl-C and Ctrl-D end the input.
+pub fn read_secret() -> Result<String, InputError> {
+    use termion::input::TermRead;
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let result = stdin.lock().read_passwd(&mut stdout.lock());
+    print!("\n");
+    match result {
+        Ok(Some(input)) => return Ok(input.trim().to_string()),
+        Ok(None) => return Err(InputError::Eof),
+        Err(_) => return read_line(),
+    }
 }
 
 pub fn flush() {
@@ -82,11 +220,28 @@ pub fn pause() {
     clear();
 }
 
+/// Leaves the game when the players' input can't be read any more.
+pub fn leave(error: InputError) -> ! {
+    if let InputError::Eof = error {
+        quit();
+    }
+    print_error(error.to_string());
+    std::process::exit(1);
+}
+
+/// Leaves the game without saving it.
+pub fn quit() -> ! {
+    clear();
+    print_message("Bye!\n".to_string());
+    std::process::exit(0);
+}
+
 pub fn sleep() {
     std::thread::sleep(std::time::Duration::from_millis(300));
 }
 
-fn add_player(players: &mut Vec<String>, username: String) -> bool {
+/// Adds a username to the players unless it is already taken. Returns false if it is.
+pub fn add_player(players: &mut Vec<String>, username: String) -> bool {
     for player in players.iter() {
         if *player == username {
             return false;
@@ -106,37 +261,48 @@ pub fn print_message(message: String) {
     flush();
 }
 
-fn read_players(num: usize) -> Vec<String> {
+fn read_players(num: usize) -> Result<Vec<(String, PlayerKind)>, InputError> {
     let mut players: Vec<String> = Vec::new();
+    let mut kinds: Vec<PlayerKind> = Vec::new();
     let mut i: usize = 0;
     while i < num {
-        print_message(format!("Enter Player {} username: ", i+1));
-        let username = read_str();
-        if add_player(&mut players, username) {
+        let username = ask(format!("Enter Player {} username: ", i+1))?;
+        if username.is_empty() {
+            print_error("Sorry! The username can't be empty. Try again.\n".to_string());
+        } else if add_player(&mut players, username) {
+            kinds.push(read_kind()?);
             i += 1;
         } else {
             print_error("Sorry! This username is already taken. Try again.\n".to_string());
         }
     }
-    return players
+    return Ok(players.into_iter().zip(kinds.into_iter()).collect());
 }
 
-pub fn ask_info() -> (Vec<String>, usize, usize) {
-    print_message("Enter number of players: ".to_string());
-    let mut number_of_players: usize = read_num();
-    while number_of_players > MAX_PLAYERS || number_of_players < MIN_PLAYERS {
-        if number_of_players > MAX_PLAYERS {
-            print_error(format!("Sorry! Maximum players allowed: {}\n", MAX_PLAYERS));
-        } else {
-            print_error(format!("Sorry! Minimum players allowed: {}\n", MIN_PLAYERS));
+/// Asks who controls a player - a human or one of the bots.
+fn read_kind() -> Result<PlayerKind, InputError> {
+    let names: Vec<&str> = PLAYER_KINDS.iter().map(|(_, name)| *name).collect();
+    loop {
+        let input = ask(format!("Controlled by ({}) [{}]: ", names.join(", "), PlayerKind::Human.name()))?;
+        if input.is_empty() {
+            return Ok(PlayerKind::Human);
+        }
+        match PlayerKind::from_name(&input) {
+            Some(kind) => return Ok(kind),
+            None => print_error("Sorry! There is no such player. Try again.\n".to_string()),
         }
-        print_message("Enter number of players: ".to_string());
-        number_of_players = read_num();
     }
+}
 
-    let map_size: usize = MAP_SIZE; 
+/// Asks for the number of players, their usernames and who controls them.
+pub fn ask_info(rules: &GameRules) -> Result<Vec<(String, PlayerKind)>, InputError> {
+    let number_of_players = ask_number_of_players(rules)?;
+    return read_players(number_of_players);
+}
 
-    return (read_players(number_of_players), number_of_players, map_size);
+/// Asks for the number of players until it is within the limits of the rules.
+pub fn ask_number_of_players(rules: &GameRules) -> Result<usize, InputError> {
+    return ask_number("Enter number of players: ".to_string(), rules.min_players, rules.max_players);
 }
 
 fn dice_animation() {
@@ -150,27 +316,160 @@ fn dice_animation() {
     }
 }
 
-pub fn roll_a_dice() -> usize {
+/// Plays the dice animation. The value itself is rolled by the game.
+pub fn roll_a_dice() {
     for _ in 0..3 {
         dice_animation();
     }
-    return 1 + rand::random::<usize>()%6;
 }
 
+/// Returns the terminal color of a player's symbol. Every player has their own color:
+/// the first six players get the light colors, the others bright colors of the 256-color palette.
+pub fn player_color(player_code: usize) -> String {
+    match player_code {
+        0 | 1 => return color::Fg(color::LightGreen).to_string(),
+        2 => return color::Fg(color::LightRed).to_string(),
+        3 => return color::Fg(color::LightYellow).to_string(),
+        4 => return color::Fg(color::LightMagenta).to_string(),
+        5 => return color::Fg(color::LightCyan).to_string(),
+        6 => return color::Fg(color::LightWhite).to_string(),
+        _ => {
+            // The colors of the 6x6x6 cube with a full component and not a gray, stepping by 7 spreads them out.
+            let bright: Vec<(u8, u8, u8)> = (0..216).map(|index| (index / 36, index / 6 % 6, index % 6))
+                .filter(|(r, g, b)| r.max(g).max(b) == &5 && !(r == g && g == b)).collect();
+            let (r, g, b) = bright[(player_code - 7) * 7 % bright.len()];
+            return color::Fg(color::AnsiValue::rgb(r, g, b)).to_string();
+        },
+    }
+}
+
+/// Prints a map with colored symbols. Every player has their own color, the symbol of `player_code` is highlighted.
 pub fn print_color(string: String, player_code: usize) {
     for symbol in string.chars() {
-        if symbol == FIELDS[0] {
-            print!("{}{}", color::Fg(color::Blue), symbol);
-        } else if symbol == FIELDS[2] {
-            print!("{}{}", color::Fg(color::LightCyan), symbol);
-        } else if PLAYERCODES.contains(&symbol) {
-            if symbol == PLAYERCODES[player_code] {
-                print!("{}{}", color::Fg(color::LightGreen), symbol);
-            } else {
-                print!("{}{}", color::Fg(color::LightRed), symbol);
-            }
+        if let Some(tile) = Tile::from_symbol(symbol) {
+            print!("{}{}", tile.color(), symbol);
+        } else if let Some(item) = Item::from_symbol(symbol) {
+            print!("{}{}", item.color(), symbol);
+        } else if get_player(symbol) != 0 && get_player(symbol) == player_code {
+            print!("{}{}{}{}", style::Invert, player_color(player_code), symbol, style::NoInvert);
+        } else if get_player(symbol) != 0 {
+            print!("{}{}", player_color(get_player(symbol)), symbol);
         } else {
             print!("{}{}", color::Fg(color::Reset), symbol);
         }
     }
-}
\ No newline at end of file
+}
+
+/// Shows a replay frame by frame. The players step forwards and backwards through it.
+pub fn watch_replay(replay: &Replay) {
+    let next: &str = "n";
+    let previous: &str = "p";
+    let quit: &str = "q";
+    let frames = replay.frames();
+    let mut i: usize = 0;
+    loop {
+        let frame = &frames[i];
+        clear();
+        print_message(format!("-- REPLAY {}/{} --\n", i + 1, frames.len()));
+        print_color(frame.map.clone(), frame.player);
+        print_message(format!("\n{}\n", frame.description));
+        for (name, score) in frame.scores.iter() {
+            print!("{}: {}\n", name, score);
+        }
+        print!("\nNext frame - {color}{}{reset} or Enter, previous frame - {color}{}{reset}, quit - {color}{}{reset}\n>>",
+            next, previous, quit, color = color::Fg(color::LightBlue), reset = color::Fg(color::Reset));
+        flush();
+        let input = read_str();
+        if input == quit {
+            break;
+        } else if input == previous {
+            i = i.saturating_sub(1);
+        } else if (input.is_empty() || input == next) && i + 1 < frames.len() {
+            i += 1;
+        }
+    }
+    clear();
+}
+
+/// Hosts a networked game: waits for the players to join and runs the game.
+/// Returns the final ranklist.
+pub fn host_game(address: &str, rules: GameRules, map: Option<Map>) -> Result<String, NetworkError> {
+    let mut server = Server::bind(address)?;
+    clear();
+    let number_of_players = ask_number_of_players(&rules).unwrap_or_else(|e| leave(e));
+    print_message(format!("Waiting for {} players on {}...\n", number_of_players, server.local_addr()?));
+    let names = server.lobby(number_of_players)?;
+    print_message("All the players have joined. The game begins!\n".to_string());
+    let game = match map {
+        Some(map) => Game::with_map(names, rules, map, rand::random::<u64>()),
+        None => Game::with_rules(names, rules, rand::random::<u64>()),
+    };
+    return server.run(game);
+}
+
+/// Joins a networked game and plays it until the end.
+/// The map is shown from the player's perspective, followed by the news since it was last shown.
+pub fn join_game(address: &str, bindings: &KeyBindings) -> Result<(), NetworkError> {
+    let mut client = Client::connect(address)?;
+    clear();
+    let player_code = loop {
+        print_message("Enter your username: ".to_string());
+        match client.join(&read_str())? {
+            Some(code) => break code,
+            None => print_error("Sorry! This username is already taken. Try again.\n".to_string()),
+        }
+    };
+    print_message("Waiting for the other players...\n".to_string());
+    let mut news: Vec<String> = Vec::new();
+    loop {
+        match client.receive()? {
+            ServerMessage::Map(map) => {
+                clear();
+                print_color(map, player_code);
+                for text in news.drain(..) {
+                    print_message(format!("{}\n", text));
+                }
+            },
+            ServerMessage::Info(text) => {
+                print_message(format!("{}\n", text));
+                news.push(text);
+            },
+            ServerMessage::YourMove(moves_left, items) => {
+                print!("Moves: {}\n", moves_left);
+                print_message(format!("Press a direction key ({} quits)\n", bindings.describe(Command::Quit)));
+                flush();
+                loop {
+                    match read_command(bindings) {
+                        Command::Move(direction) => break client.send_move(direction)?,
+                        Command::Path => {
+                            let input = ask(format!("Enter up to {} steps (e.g. ddsaw): ", moves_left)).unwrap_or_else(|e| leave(e));
+                            match parse_path(&input) {
+                                Ok(path) => break client.send_path(path)?,
+                                Err(e) => print_error(e.to_string()),
+                            }
+                        },
+                        Command::Use if items.is_empty() => print_error(GameError::NoItem.to_string()),
+                        Command::Use => {
+                            let names: Vec<&str> = items.iter().map(|item| item.name()).collect();
+                            let input = ask(format!("Use which item? ({}): ", names.join(", "))).unwrap_or_else(|e| leave(e));
+                            match input.parse::<Item>() {
+                                Ok(item) => break client.send_use(item)?,
+                                Err(e) => print_error(e.to_string()),
+                            }
+                        },
+                        Command::Save => print_error("Sorry! Network games can't be saved.\n".to_string()),
+                        Command::Quit => quit(),
+                    }
+                }
+            },
+            ServerMessage::Error(message) => print_error(message),
+            ServerMessage::Finished(ranklist) => {
+                print_error("GAME FINISHED!\n".to_string());
+                print_message(ranklist);
+                pause();
+                return Ok(());
+            },
+            message => return Err(NetworkError::Protocol(message.encode())),
+        }
+    }
+}
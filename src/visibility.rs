@@ -0,0 +1,74 @@
+use crate::map::Map;
+use crate::Position;
+
+/// Returns the fields seen from a position: the fields at most `radius` away (as the crow flies)
+/// that are not hidden behind a wall, a breakable wall or a door. The walls themselves are seen.
+pub fn visible_fields(map: &Map, from: Position, radius: usize) -> Vec<Position> {
+    let mut result: Vec<Position> = Vec::new();
+    let last_x = std::cmp::min(from.0 + radius, map.height - 1);
+    let last_y = std::cmp::min(from.1 + radius, map.width - 1);
+    for x in from.0.saturating_sub(radius)..last_x + 1 {
+        for y in from.1.saturating_sub(radius)..last_y + 1 {
+            let (dx, dy) = (from.0.abs_diff(x), from.1.abs_diff(y));
+            if dx * dx + dy * dy <= radius * radius && line_of_sight(map, from, Position(x, y)) {
+                result.push(Position(x, y));
+            }
+        }
+    }
+    return result;
+}
+
+/// Checks that there are no walls between two fields.
+fn line_of_sight(map: &Map, from: Position, to: Position) -> bool {
+    let fields = line(from, to);
+    for position in fields.iter().skip(1).take(fields.len().saturating_sub(2)) {
+        if map.tile(*position).blocks_sight() {
+            return false;
+        }
+    }
+    return true;
+}
+
+/// Returns the fields on the straight line between two fields, both included (Bresenham's algorithm).
+fn line(from: Position, to: Position) -> Vec<Position> {
+    let (mut x, mut y) = (from.0 as isize, from.1 as isize);
+    let (to_x, to_y) = (to.0 as isize, to.1 as isize);
+    let (dx, dy) = ((to_x - x).abs(), -(to_y - y).abs());
+    let (step_x, step_y) = ((to_x - x).signum(), (to_y - y).signum());
+    let mut error = dx + dy;
+    let mut result: Vec<Position> = vec![from];
+    while x != to_x || y != to_y {
+        let double_error = 2 * error;
+        if double_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if double_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+        result.push(Position(x as usize, y as usize));
+    }
+    return result;
+}
+
+
+
+// ----------------------------------------------------------------------------------
+// Private functions test
+
+#[test]
+fn test_line() {
+    assert_eq!(line(Position(1, 1), Position(1, 1)), vec![Position(1, 1)]);
+    assert_eq!(line(Position(0, 0), Position(0, 3)), vec![Position(0, 0), Position(0, 1), Position(0, 2), Position(0, 3)]);
+    assert_eq!(line(Position(2, 2), Position(0, 0)), vec![Position(2, 2), Position(1, 1), Position(0, 0)]);
+    assert_eq!(line(Position(0, 0), Position(1, 3)), vec![Position(0, 0), Position(0, 1), Position(1, 2), Position(1, 3)]);
+}
+
+#[test]
+fn test_line_of_sight() {
+    let map: Map = ". . # .\n. . . .\n".parse().ok().unwrap();
+    assert_eq!(line_of_sight(&map, Position(0, 0), Position(0, 2)), true);
+    assert_eq!(line_of_sight(&map, Position(0, 0), Position(0, 3)), false);
+    assert_eq!(line_of_sight(&map, Position(1, 0), Position(1, 3)), true);
+}
@@ -0,0 +1,186 @@
+use project::game::Game;
+use project::map::Map;
+use project::rules::GameRules;
+use project::ai::PlayerKind;
+use project::engine::{Action, Phase};
+use project::project_errors::GameError;
+use project::Position;
+use project::direction::Direction;
+
+fn names() -> Vec<String> {
+    return vec!["first".to_string(), "second".to_string()];
+}
+
+fn game(map: &str, first: PlayerKind, second: PlayerKind) -> Game {
+    let map: Map = map.parse().ok().unwrap();
+    let mut game = Game::with_map(names(), GameRules::default(), map, 3);
+    game.set_player_kind(1, first);
+    game.set_player_kind(2, second);
+    game.spawn_all().ok().unwrap();
+    return game;
+}
+
+/// Plays a whole game with the engine, the way the terminal frontend does.
+/// Returns the number of actions applied or None if the game didn't finish.
+fn play(game: &mut Game, max_actions: usize) -> Option<usize> {
+    for i in 0..max_actions {
+        let action = match game.phase() {
+            Phase::Setup => Action::Start,
+            Phase::Roll(_) => Action::Roll,
+            Phase::Move { player, .. } => match game.next_move(player) {
+                Some(direction) => Action::Move(direction),
+                None => panic!("Only bots play here!"),
+            },
+            Phase::Finished => return Some(i),
+        };
+        if game.apply(action).is_err() {
+            game.apply(Action::Skip).ok().unwrap();
+        }
+    }
+    return None;
+}
+
+#[test]
+fn test_greedy_bot_goes_to_portal() {
+    let mut test_game = game("1 . . # 2\n# # . # .\n. . . . .\n. # # # 0\n",
+        PlayerKind::GreedyBot, PlayerKind::GreedyBot);
+    assert_eq!(test_game.next_move(1), Some(Direction::Right));
+    assert_eq!(test_game.next_move(2), Some(Direction::Down));
+    test_game.make_move(1, Direction::Right).ok();
+    test_game.make_move(1, Direction::Right).ok();
+    assert_eq!(test_game.next_move(1), Some(Direction::Down));
+}
+
+#[test]
+fn test_bots_go_around_locked_doors() {
+    let mut test_game = game("1 + 0\n. # .\n. . 2\n", PlayerKind::GreedyBot, PlayerKind::Human);
+    for _ in 0..10 {
+        assert_eq!(test_game.next_move(1), Some(Direction::Down));
+    }
+}
+
+#[test]
+fn test_bots_only_know_what_they_have_seen() {
+    let map: Map = "2 . . . 1 . 0\n".parse().ok().unwrap();
+    let mut rules = GameRules::default();
+    rules.view_radius = 2;
+    let mut test_game = Game::with_map(names(), rules, map, 3);
+    test_game.set_player_kind(1, PlayerKind::HunterBot);
+    test_game.spawn_all().ok().unwrap();
+    // The hunter doesn't see the other player, so it goes to the portal it sees.
+    assert_eq!(test_game.next_move(1), Some(Direction::Right));
+}
+
+#[test]
+fn test_hunter_bot_chases_players() {
+    let mut test_game = game(". . 1 . .\n. # # # .\n. . 2 . 0\n",
+        PlayerKind::HunterBot, PlayerKind::Human);
+    let first = match test_game.next_move(1) {
+        Some(direction) => direction,
+        None => panic!("Something wrong!"),
+    };
+    assert!(first == Direction::Left || first == Direction::Right);
+    // Human players move from the frontend.
+    assert_eq!(test_game.next_move(2), None);
+    test_game.make_move(1, first).ok();
+    assert_eq!(test_game.next_move(1), Some(first));
+}
+
+#[test]
+fn test_hunter_bot_goes_to_portal_alone() {
+    let mut test_game = game("1 . 2\n# # .\n0 . .\n", PlayerKind::HunterBot, PlayerKind::Human);
+    assert_eq!(test_game.next_move(1), Some(Direction::Right));
+    test_game.make_move(1, Direction::Right).ok();
+    assert_eq!(test_game.make_move(1, Direction::Right), Err(GameError::AnotherPlayer));
+    test_game.battle(1, Direction::Right).ok().unwrap();
+    assert_eq!(test_game.next_move(1), Some(Direction::Down));
+}
+
+/// A game of bots with the rules of a game mode.
+fn mode_game(map: &str, mode: &str, kind: PlayerKind) -> Game {
+    let map: Map = map.parse().ok().unwrap();
+    let mut rules = GameRules::default();
+    rules.set("mode", mode).ok().unwrap();
+    let mut game = Game::with_map(names(), rules, map, 3);
+    game.set_player_kind(1, kind);
+    game.set_player_kind(2, kind);
+    game.spawn_all().ok().unwrap();
+    return game;
+}
+
+#[test]
+fn test_bots_avoid_portal_in_last_standing() {
+    for kind in [PlayerKind::RandomBot, PlayerKind::GreedyBot, PlayerKind::HunterBot].iter() {
+        let mut test_game = mode_game("1 0 .\n. # .\n. # 2\n", "last-standing", *kind);
+        for _ in 0..10 {
+            assert_eq!(test_game.next_move(1), Some(Direction::Down));
+        }
+    }
+    let mut test_game = mode_game("1 0 $\n. # .\n. . 2\n", "last-standing", PlayerKind::GreedyBot);
+    assert_eq!(test_game.next_move(2), Some(Direction::Up));
+}
+
+#[test]
+fn test_greedy_bot_collects_points() {
+    let mut test_game = mode_game("1 . 0\n. # .\n$ . 2\n", "points", PlayerKind::GreedyBot);
+    assert_eq!(test_game.next_move(1), Some(Direction::Down));
+    assert_eq!(test_game.next_move(2), Some(Direction::Left));
+    // Without treasures it goes for the other player.
+    let mut test_game = mode_game("1 . 0\n. # #\n. . 2\n", "points", PlayerKind::GreedyBot);
+    assert_eq!(test_game.next_move(1), Some(Direction::Down));
+}
+
+#[test]
+fn test_random_bot_avoids_walls() {
+    let mut test_game = game(". . . 2\n. 1 # .\n# # # .\n", PlayerKind::RandomBot, PlayerKind::Human);
+    for _ in 0..20 {
+        let direction = test_game.next_move(1);
+        assert!(direction == Some(Direction::Up) || direction == Some(Direction::Left));
+    }
+    assert_eq!(test_game.get_player_position(1), Some(Position(1, 1)));
+}
+
+#[test]
+fn test_bots_deterministic() {
+    let mut rules = GameRules::with_size(15);
+    rules.max_players = 2;
+    let mut first = Game::with_rules(names(), rules.clone(), 17);
+    let mut second = Game::with_rules(names(), rules, 17);
+    for test_game in [&mut first, &mut second].iter_mut() {
+        test_game.set_player_kind(1, PlayerKind::RandomBot);
+        test_game.set_player_kind(2, PlayerKind::RandomBot);
+        test_game.spawn_all().ok().unwrap();
+    }
+    assert_eq!(play(&mut first, 200), play(&mut second, 200));
+    assert_eq!(first.display(), second.display());
+}
+
+#[test]
+fn test_bot_games_finish() {
+    // A random walker left alone may wander for a long time, so only goal-directed bots play here.
+    let kinds = [PlayerKind::GreedyBot, PlayerKind::HunterBot, PlayerKind::HunterBot];
+    for seed in 0..20 {
+        for generator in ["backtracker", "braided-prim"].iter() {
+            let mut rules = GameRules::with_size(15);
+            rules.generator = generator.to_string();
+            let players: Vec<String> = (1..4).map(|i| format!("bot {}", i)).collect();
+            let mut test_game = Game::with_rules(players, rules, seed);
+            for (i, kind) in kinds.iter().enumerate() {
+                test_game.set_player_kind(i + 1, *kind);
+            }
+            test_game.spawn_all().ok().unwrap();
+            assert!(play(&mut test_game, 10000).is_some(), "seed {} {}", seed, generator);
+        }
+    }
+}
+
+#[test]
+fn test_save_and_load_player_kinds() {
+    let path = std::env::temp_dir().join("labyrinth_test_ai.save").to_str().unwrap().to_string();
+    let mut test_game = game("1 . 2\n. . .\n. . 0\n", PlayerKind::HunterBot, PlayerKind::GreedyBot);
+    test_game.save(&path).ok().unwrap();
+    let loaded = Game::load(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded.player_kind(1), PlayerKind::HunterBot);
+    assert_eq!(loaded.player_kind(2), PlayerKind::GreedyBot);
+}
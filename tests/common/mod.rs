@@ -0,0 +1,40 @@
+// Helpers shared by the tests that play games on hand-authored maps.
+// Every test file uses only some of them.
+#![allow(dead_code)]
+
+use project::game::Game;
+use project::map::Map;
+use project::rules::GameRules;
+use project::engine::{Action, Event};
+use project::project_errors::GameError;
+
+/// The names of the first `players` players.
+pub fn names(players: usize) -> Vec<String> {
+    return ["first", "second", "third"][..players].iter().map(|name| name.to_string()).collect();
+}
+
+/// The default rules with a one-sided dice, so every player rolls 1.
+pub fn rules() -> GameRules {
+    let mut rules = GameRules::default();
+    rules.dice_sides = 1;
+    return rules;
+}
+
+/// A game on a hand-authored map, not started yet.
+pub fn game(map: &str, rules: GameRules, players: usize) -> Game {
+    let map: Map = map.parse().ok().unwrap();
+    return Game::with_map(names(players), rules, map, 5);
+}
+
+/// A started game on a hand-authored map.
+pub fn started_game(map: &str, rules: GameRules, players: usize) -> Game {
+    let mut test_game = game(map, rules, players);
+    test_game.apply(Action::Start).ok().unwrap();
+    return test_game;
+}
+
+/// Rolls the dice and applies an action of the player on turn.
+pub fn play(game: &mut Game, action: Action) -> Result<Vec<Event>, GameError> {
+    game.apply(Action::Roll).ok().unwrap();
+    return game.apply(action);
+}
@@ -0,0 +1,63 @@
+use project::direction::{Direction, parse_path};
+use project::project_errors::GameError;
+use project::map::Map;
+use project::Position;
+
+#[test]
+fn test_parse_direction() {
+    for input in ["w", "up", "UP", " k ", "\x1b[A"].iter() {
+        assert_eq!(input.parse::<Direction>(), Ok(Direction::Up));
+    }
+    assert_eq!("s".parse::<Direction>(), Ok(Direction::Down));
+    assert_eq!("h".parse::<Direction>(), Ok(Direction::Left));
+    assert_eq!("Right".parse::<Direction>(), Ok(Direction::Right));
+    assert_eq!("\x1b[D".parse::<Direction>(), Ok(Direction::Left));
+    assert_eq!("north".parse::<Direction>(), Err(GameError::InvalidDirection));
+    assert_eq!("".parse::<Direction>(), Err(GameError::InvalidDirection));
+    for direction in Direction::ALL.iter() {
+        assert_eq!(direction.to_string().parse::<Direction>(), Ok(*direction));
+    }
+}
+
+#[test]
+fn test_parse_path() {
+    use Direction::*;
+    assert_eq!(parse_path("ddsaw"), Ok(vec![Right, Right, Down, Left, Up]));
+    assert_eq!(parse_path("right right, DOWN"), Ok(vec![Right, Right, Down]));
+    assert_eq!(parse_path("dd up"), Ok(vec![Right, Right, Up]));
+    assert_eq!(parse_path("ddx"), Err(GameError::InvalidDirection));
+    assert_eq!(parse_path(" , "), Err(GameError::InvalidDirection));
+}
+
+#[test]
+fn test_rotations() {
+    for direction in Direction::ALL.iter() {
+        assert_eq!(direction.opposite().opposite(), *direction);
+        assert_eq!(direction.clockwise().clockwise(), direction.opposite());
+        assert_eq!(direction.clockwise().counterclockwise(), *direction);
+    }
+    assert_eq!(Direction::Up.clockwise(), Direction::Right);
+    assert_eq!(Direction::Up.counterclockwise(), Direction::Left);
+    assert_eq!(Direction::Left.opposite(), Direction::Right);
+}
+
+#[test]
+fn test_step() {
+    let position = Position(1, 1);
+    assert_eq!(position.step(Direction::Up), Some(Position(0, 1)));
+    assert_eq!(position.step(Direction::Down), Some(Position(2, 1)));
+    assert_eq!(position.step(Direction::Left), Some(Position(1, 0)));
+    assert_eq!(position.step(Direction::Right), Some(Position(1, 2)));
+    assert_eq!(Position(0, 3).step(Direction::Up), None);
+    assert_eq!(Position(3, 0).step(Direction::Left), None);
+}
+
+#[test]
+fn test_map_neighbour() {
+    let map: Map = ". . .\n. # .\n".parse().ok().unwrap();
+    assert_eq!(map.neighbour(Position(1, 2), Direction::Right), None);
+    assert_eq!(map.neighbour(Position(1, 2), Direction::Down), None);
+    assert_eq!(map.neighbour(Position(0, 0), Direction::Up), None);
+    // Walls are on the map, whether a player can step on them is up to the game.
+    assert_eq!(map.neighbour(Position(0, 1), Direction::Down), Some(Position(1, 1)));
+}
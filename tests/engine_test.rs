@@ -0,0 +1,274 @@
+use project::game::Game;
+use project::map::Map;
+use project::rules::GameRules;
+use project::engine::{Action, Event, Phase};
+use project::project_errors::GameError;
+use project::Position;
+use project::direction::Direction;
+
+mod common;
+use common::{game, rules, started_game};
+
+fn path_game(map: &str, dice_sides: usize) -> Game {
+    let mut rules = GameRules::default();
+    rules.dice_sides = dice_sides;
+    let mut test_game = started_game(map, rules, 3);
+    test_game.apply(Action::Roll).ok().unwrap();
+    return test_game;
+}
+
+/// A game with the combat rule where both players always roll 1.
+fn combat_game(map: &str, attack: u32, defense: u32, health: u32) -> Game {
+    let mut rules = rules();
+    rules.combat = true;
+    rules.attack = attack;
+    rules.defense = defense;
+    rules.health = health;
+    let mut test_game = started_game(map, rules, 3);
+    test_game.apply(Action::Roll).ok().unwrap();
+    return test_game;
+}
+
+fn move_player(game: &mut Game, direction: Direction) -> Vec<Event> {
+    return game.apply(Action::Move(direction)).ok().unwrap();
+}
+
+#[test]
+fn test_start() {
+    let mut test_game = game("1 . 2\n. # .\n3 . 0\n", rules(), 3);
+    assert_eq!(test_game.phase(), Phase::Setup);
+    assert_eq!(test_game.apply(Action::Roll), Err(GameError::InvalidAction));
+    assert_eq!(test_game.apply(Action::Start), Ok(vec![Event::GameStarted, Event::TurnStarted(1)]));
+    assert_eq!(test_game.phase(), Phase::Roll(1));
+    assert_eq!(test_game.apply(Action::Start), Err(GameError::InvalidAction));
+}
+
+#[test]
+fn test_roll_and_move() {
+    let mut test_game = game("1 . 2\n. # .\n3 . 0\n", rules(), 3);
+    test_game.apply(Action::Start).ok().unwrap();
+    assert_eq!(test_game.apply(Action::Move(Direction::Right)), Err(GameError::InvalidAction));
+    assert_eq!(test_game.apply(Action::Roll), Ok(vec![Event::DiceRolled { player: 1, value: 1 }]));
+    assert_eq!(test_game.phase(), Phase::Move { player: 1, moves_left: 1 });
+    assert_eq!(test_game.apply(Action::Roll), Err(GameError::InvalidAction));
+    assert_eq!(move_player(&mut test_game, Direction::Right), vec![
+        Event::Moved { player: 1, to: Position(0, 1) },
+        Event::TurnEnded(1),
+        Event::TurnStarted(2),
+    ]);
+    assert_eq!(test_game.phase(), Phase::Roll(2));
+}
+
+#[test]
+fn test_invalid_move() {
+    let mut test_game = game("1 . 2\n. # .\n3 . 0\n", rules(), 3);
+    test_game.apply(Action::Start).ok().unwrap();
+    test_game.apply(Action::Roll).ok().unwrap();
+    assert_eq!(test_game.apply(Action::Move(Direction::Up)), Err(GameError::Outside));
+    assert_eq!("x".parse::<Direction>(), Err(GameError::InvalidDirection));
+    assert_eq!(test_game.phase(), Phase::Move { player: 1, moves_left: 1 });
+    assert_eq!(test_game.get_player_position(1), Some(Position(0, 0)));
+    assert_eq!(test_game.apply(Action::Skip), Ok(vec![Event::MoveSkipped(1), Event::TurnEnded(1), Event::TurnStarted(2)]));
+}
+
+#[test]
+fn test_battle_skips_dead_player() {
+    let mut test_game = game("1 2 .\n. # .\n3 . 0\n", rules(), 3);
+    test_game.apply(Action::Start).ok().unwrap();
+    test_game.apply(Action::Roll).ok().unwrap();
+    assert_eq!(move_player(&mut test_game, Direction::Right), vec![
+        Event::Battle { winner: 1, loser: 2, points: 15 },
+        Event::Moved { player: 1, to: Position(0, 1) },
+        Event::TurnEnded(1),
+        Event::TurnStarted(3),
+    ]);
+    assert_eq!(test_game.in_game(2), false);
+    assert_eq!(test_game.phase(), Phase::Roll(3));
+}
+
+#[test]
+fn test_portal_finishes_game() {
+    let mut test_game = game("1 . 2\n. # .\n. 3 0\n", rules(), 3);
+    test_game.apply(Action::Start).ok().unwrap();
+    for direction in [Direction::Right, Direction::Down].iter() {
+        test_game.apply(Action::Roll).ok().unwrap();
+        move_player(&mut test_game, *direction);
+    }
+    test_game.apply(Action::Roll).ok().unwrap();
+    assert_eq!(move_player(&mut test_game, Direction::Right), vec![
+        Event::Portal { player: 3, points: 50 },
+        Event::GameFinished(3),
+    ]);
+    assert_eq!(test_game.phase(), Phase::Finished);
+    assert_eq!(test_game.winner(), Some(3));
+    assert_eq!(test_game.apply(Action::Roll), Err(GameError::InvalidAction));
+}
+
+#[test]
+fn test_moves_left() {
+    let map: Map = ". . . . . . .\n1 . . . . . 0\n2 . . . . . .\n".parse().ok().unwrap();
+    let mut rules = GameRules::default();
+    rules.dice_sides = 3;
+    let mut test_game = Game::with_map(vec!["first".to_string(), "second".to_string()], rules, map, 1);
+    test_game.apply(Action::Start).ok().unwrap();
+    let dice = match test_game.apply(Action::Roll).ok().unwrap()[0] {
+        Event::DiceRolled { value, .. } => value,
+        _ => panic!("Something wrong!"),
+    };
+    for moves_left in (2..dice + 1).rev() {
+        assert_eq!(test_game.phase(), Phase::Move { player: 1, moves_left: moves_left });
+        assert_eq!(move_player(&mut test_game, Direction::Right).len(), 1);
+    }
+    assert_eq!(move_player(&mut test_game, Direction::Right).len(), 3);
+    assert_eq!(test_game.get_player_position(1), Some(Position(1, dice)));
+    assert_eq!(test_game.phase(), Phase::Roll(2));
+}
+
+#[test]
+fn test_resume_saved_turn() {
+    let path = std::env::temp_dir().join("labyrinth_test_engine.save").to_str().unwrap().to_string();
+    let map: Map = "1 . . . . . 0\n2 . . . . . .\n".parse().ok().unwrap();
+    let mut rules = GameRules::default();
+    rules.dice_sides = 6;
+    let mut test_game = Game::with_map(vec!["first".to_string(), "second".to_string()], rules, map, 9);
+    test_game.apply(Action::Start).ok().unwrap();
+    test_game.apply(Action::Roll).ok().unwrap();
+    test_game.save(&path).ok().unwrap();
+    let loaded = Game::load(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded.phase(), test_game.phase());
+}
+
+#[test]
+fn test_path() {
+    let mut test_game = path_game("1 . . . 2\n. # # # .\n3 . . . 0\n", 1);
+    let moves_left = test_game.moves_left();
+    let path = vec![Direction::Right; moves_left];
+    let events = test_game.apply(Action::Path(path)).ok().unwrap();
+    assert_eq!(events[0], Event::Moved { player: 1, to: Position(0, 1) });
+    assert_eq!(events[moves_left - 1], Event::Moved { player: 1, to: Position(0, moves_left) });
+    assert_eq!(test_game.phase(), Phase::Roll(2));
+}
+
+#[test]
+fn test_invalid_path() {
+    let mut test_game = path_game("1 # . . 2\n. . # # .\n3 . . . 0\n", 2);
+    let moves_left = test_game.moves_left();
+    assert_eq!(test_game.apply(Action::Path(vec![Direction::Right; moves_left + 1])), Err(GameError::PathTooLong));
+    assert_eq!(test_game.apply(Action::Path(vec![Direction::Up])), Err(GameError::Outside));
+    assert_eq!(test_game.apply(Action::Path(vec![Direction::Right])), Err(GameError::InvalidCoordinates));
+    assert_eq!(test_game.apply(Action::Path(Vec::new())), Err(GameError::InvalidDirection));
+    // A rejected path doesn't move the player at all.
+    assert_eq!(test_game.get_player_position(1), Some(Position(0, 0)));
+    assert_eq!(test_game.phase(), Phase::Move { player: 1, moves_left: moves_left });
+}
+
+#[test]
+fn test_path_stops_at_battle() {
+    let mut test_game = path_game("1 2 . . .\n. # # # .\n3 . . . 0\n", 3);
+    let moves_left = test_game.moves_left();
+    let events = test_game.apply(Action::Path(vec![Direction::Right; moves_left])).ok().unwrap();
+    assert_eq!(events[0], Event::Battle { winner: 1, loser: 2, points: 15 });
+    assert_eq!(events[1], Event::Moved { player: 1, to: Position(0, 1) });
+    assert_eq!(test_game.get_player_position(1), Some(Position(0, 1)));
+    if moves_left > 1 {
+        assert_eq!(events.len(), 2);
+        assert_eq!(test_game.phase(), Phase::Move { player: 1, moves_left: moves_left - 1 });
+    }
+}
+
+#[test]
+fn test_path_to_portal() {
+    let mut test_game = path_game("1 0 . . .\n. # # # .\n3 . . . 2\n", 1);
+    let events = test_game.apply(Action::Path(vec![Direction::Right])).ok().unwrap();
+    assert_eq!(events, vec![Event::Portal { player: 1, points: 50 }, Event::GameFinished(1)]);
+    assert_eq!(test_game.phase(), Phase::Finished);
+}
+
+#[test]
+fn test_path_to() {
+    let test_game = path_game("1 . . . 2\n. # # # .\n3 . . . 0\n", 1);
+    assert_eq!(test_game.path_to(1, Position(2, 2)), Ok(vec![Direction::Down, Direction::Down, Direction::Right, Direction::Right]));
+    assert_eq!(test_game.path_to(1, Position(1, 1)), Err(GameError::InvalidField));
+    assert_eq!(test_game.path_to(1, Position(0, 0)), Err(GameError::InvalidCoordinates));
+    assert_eq!(test_game.path_to(1, Position(3, 0)), Err(GameError::Outside));
+}
+
+#[test]
+fn test_combat_pushes_defender_back() {
+    let mut test_game = combat_game("1 2 .\n. # .\n3 . 0\n", 5, 1, 10);
+    assert_eq!(move_player(&mut test_game, Direction::Right), vec![
+        Event::Fight { attacker: 1, defender: 2, attack_roll: 1, defense_roll: 1 },
+        Event::Wounded { player: 2, damage: 4, health: 6 },
+        Event::Pushed { player: 2, to: Position(0, 2) },
+        Event::Moved { player: 1, to: Position(0, 1) },
+        Event::TurnEnded(1),
+        Event::TurnStarted(2),
+    ]);
+    assert_eq!(test_game.in_game(2), true);
+    assert_eq!(test_game.get_player_stats(2), (6, 5, 1));
+    assert_eq!(test_game.get_player_position(1), Some(Position(0, 1)));
+}
+
+#[test]
+fn test_combat_without_room_to_push() {
+    let mut test_game = combat_game("1 2 #\n. # .\n3 . 0\n", 5, 1, 10);
+    assert_eq!(move_player(&mut test_game, Direction::Right), vec![
+        Event::Fight { attacker: 1, defender: 2, attack_roll: 1, defense_roll: 1 },
+        Event::Wounded { player: 2, damage: 4, health: 6 },
+        Event::TurnEnded(1),
+        Event::TurnStarted(2),
+    ]);
+    assert_eq!(test_game.get_player_position(1), Some(Position(0, 0)));
+    assert_eq!(test_game.get_player_position(2), Some(Position(0, 1)));
+}
+
+#[test]
+fn test_combat_kills_defender() {
+    let mut test_game = combat_game("1 2 .\n. # .\n3 . 0\n", 5, 1, 4);
+    assert_eq!(move_player(&mut test_game, Direction::Right), vec![
+        Event::Fight { attacker: 1, defender: 2, attack_roll: 1, defense_roll: 1 },
+        Event::Battle { winner: 1, loser: 2, points: 15 },
+        Event::Moved { player: 1, to: Position(0, 1) },
+        Event::TurnEnded(1),
+        Event::TurnStarted(3),
+    ]);
+    assert_eq!(test_game.in_game(2), false);
+}
+
+#[test]
+fn test_combat_attacker_loses() {
+    let mut test_game = combat_game("1 2 .\n. # .\n3 . 0\n", 0, 3, 10);
+    assert_eq!(move_player(&mut test_game, Direction::Right), vec![
+        Event::Fight { attacker: 1, defender: 2, attack_roll: 1, defense_roll: 1 },
+        Event::Wounded { player: 1, damage: 3, health: 7 },
+        Event::TurnEnded(1),
+        Event::TurnStarted(2),
+    ]);
+    assert_eq!(test_game.get_player_stats(1), (7, 0, 3));
+}
+
+#[test]
+fn test_combat_draw() {
+    let mut test_game = combat_game("1 2 .\n. # .\n3 . 0\n", 2, 2, 10);
+    assert_eq!(move_player(&mut test_game, Direction::Right), vec![
+        Event::Fight { attacker: 1, defender: 2, attack_roll: 1, defense_roll: 1 },
+        Event::TurnEnded(1),
+        Event::TurnStarted(2),
+    ]);
+    assert_eq!(test_game.get_player_stats(1), (10, 2, 2));
+    assert_eq!(test_game.get_player_stats(2), (10, 2, 2));
+}
+
+#[test]
+fn test_combat_kills_attacker() {
+    let mut test_game = combat_game("1 2 .\n. # .\n3 . 0\n", 0, 3, 3);
+    assert_eq!(move_player(&mut test_game, Direction::Right), vec![
+        Event::Fight { attacker: 1, defender: 2, attack_roll: 1, defense_roll: 1 },
+        Event::Battle { winner: 2, loser: 1, points: 15 },
+        Event::TurnEnded(1),
+        Event::TurnStarted(2),
+    ]);
+    assert_eq!(test_game.in_game(1), false);
+    assert_eq!(test_game.get_player_position(2), Some(Position(0, 1)));
+}
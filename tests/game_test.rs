@@ -0,0 +1,231 @@
+use project::game::Game;
+use project::Position;
+use project::direction::Direction;
+use project::project_errors::GameError;
+use project::map::Map;
+use project::rules::GameRules;
+
+fn names() -> Vec<String> {
+    return vec!["first".to_string(), "second".to_string()];
+}
+
+fn game(seed: u64) -> Game {
+    let mut game = Game::with_seed(names(), 2, 10, seed);
+    game.spawn_all().ok().unwrap();
+    return game;
+}
+
+#[test]
+fn test_same_seed_same_map() {
+    assert_eq!(game(42).display(), game(42).display());
+    assert_eq!(game(42).get_player_position(1), game(42).get_player_position(1));
+    assert_eq!(game(42).get_player_position(2), game(42).get_player_position(2));
+}
+
+#[test]
+fn test_different_seed_different_map() {
+    assert_ne!(game(1).display(), game(2).display());
+}
+
+#[test]
+fn test_seed() {
+    assert_eq!(game(7).seed(), 7);
+}
+
+#[test]
+fn test_same_seed_same_dice() {
+    let mut first = game(42);
+    let mut second = game(42);
+    for _ in 0..20 {
+        let dice = first.roll_dice();
+        assert!(dice >= 1 && dice <= 6);
+        assert_eq!(dice, second.roll_dice());
+    }
+}
+
+#[test]
+fn test_same_seed_same_game() {
+    let mut first = game(1234);
+    let mut second = game(1234);
+    let moves = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+    for (i, direction) in moves.iter().cycle().take(40).enumerate() {
+        let player = 1 + i % 2;
+        if first.in_game(player) {
+            let dice = first.roll_dice();
+            assert_eq!(dice, second.roll_dice());
+            assert_eq!(first.make_move(player, *direction), second.make_move(player, *direction));
+        }
+    }
+    assert_eq!(first.display(), second.display());
+}
+
+fn save_path(name: &str) -> String {
+    return std::env::temp_dir().join(name).to_str().unwrap().to_string();
+}
+
+#[test]
+fn test_save_and_load() {
+    let path = save_path("labyrinth_test_save_and_load.save");
+    let mut original = game(99);
+    original.make_move(1, Direction::Down).ok();
+    original.set_moves_left(3);
+    original.end_turn();
+    original.set_moves_left(2);
+    original.save(&path).ok().unwrap();
+
+    let mut loaded = Game::load(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded.display(), original.display());
+    assert_eq!(loaded.seed(), 99);
+    assert_eq!(loaded.turn(), 2);
+    assert_eq!(loaded.moves_left(), 2);
+    assert_eq!(loaded.get_player_position(1), original.get_player_position(1));
+    for _ in 0..10 {
+        assert_eq!(loaded.roll_dice(), original.roll_dice());
+    }
+}
+
+#[test]
+fn test_save_keeps_the_dice() {
+    let path = save_path("labyrinth_test_save_keeps_the_dice.save");
+    let mut saved = game(7);
+    let mut unsaved = game(7);
+    assert_eq!(saved.roll_dice(), unsaved.roll_dice());
+    saved.save(&path).ok().unwrap();
+    let mut loaded = Game::load(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    // Saving doesn't change the game, so the same seed rolls the same dice with and without a save.
+    for _ in 0..10 {
+        let roll = unsaved.roll_dice();
+        assert_eq!(saved.roll_dice(), roll);
+        assert_eq!(loaded.roll_dice(), roll);
+    }
+}
+
+#[test]
+fn test_load_missing_file() {
+    assert!(Game::load(&save_path("labyrinth_test_missing.save")).is_err());
+}
+
+#[test]
+fn test_load_invalid_file() {
+    let path = save_path("labyrinth_test_invalid.save");
+    std::fs::write(&path, "labyrinth-save 1\nseed 1\nrng 2\nsize 2\nturn 1\nmoves 0\nmap\n. .\n. .\n").ok();
+    assert!(Game::load(&path).is_err());
+    std::fs::write(&path, "labyrinth-save 2\n").ok();
+    assert!(Game::load(&path).is_err());
+    std::fs::write(&path, "labyrinth-save 3\nseed 1\nrng 2\nsize 2 2\nturn 1\nmoves 0\n\
+        player 1 0 0 0 first\nplayer 2 0 1 1 second\nmap\n1 .#\n. 2\n").ok();
+    assert!(Game::load(&path).is_err());
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_game_with_map() {
+    let map: Map = "1 . . .\n# # # .\n. . . .\n0 # # 2\n".parse().ok().unwrap();
+    let mut game = Game::with_map(names(), GameRules::default(), map, 5);
+    game.spawn_all().ok().unwrap();
+    assert_eq!(game.get_player_position(1), Some(Position(0, 0)));
+    assert_eq!(game.get_player_position(2), Some(Position(3, 3)));
+    assert_eq!(game.display_map(), "1 . . . \n# # # . \n. . . . \n0 # # 2 \n");
+}
+
+#[test]
+fn test_game_with_rules() {
+    let mut rules = GameRules::with_size(7);
+    rules.dice_sides = 2;
+    rules.battle_score = 3;
+    let map: Map = "1 2 .\n. . .\n. . 0\n".parse().ok().unwrap();
+    let mut game = Game::with_map(names(), rules, map, 8);
+    assert_eq!(game.rules().map_width, 3);
+    assert_eq!(game.rules().map_height, 3);
+    game.spawn_all().ok().unwrap();
+    for _ in 0..20 {
+        let dice = game.roll_dice();
+        assert!(dice == 1 || dice == 2);
+    }
+    assert_eq!(game.make_move(1, Direction::Right), Err(GameError::AnotherPlayer));
+    assert_eq!(game.battle(1, Direction::Right).ok().unwrap().0, 2);
+    assert!(game.finish().starts_with("--- 1 ---\nfirst\nscore: 3\n"));
+}
+
+#[test]
+fn test_game_map_size_from_rules() {
+    let mut rules = GameRules::with_size(13);
+    rules.map_height = 5;
+    let mut game = Game::with_rules(names(), rules, 3);
+    game.spawn_all().ok().unwrap();
+    assert_eq!(game.map_width, 13);
+    assert_eq!(game.map_height, 5);
+    assert_eq!(game.display_map().lines().count(), 5);
+    assert_eq!(game.display_map().lines().next().unwrap().len(), 26);
+}
+
+#[test]
+fn test_save_and_load_rectangular() {
+    let path = save_path("labyrinth_test_save_rectangular.save");
+    let mut rules = GameRules::with_size(15);
+    rules.map_height = 4;
+    let mut original = Game::with_rules(names(), rules, 6);
+    original.spawn_all().ok().unwrap();
+    original.save(&path).ok().unwrap();
+    let loaded = Game::load(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded.display(), original.display());
+    assert_eq!(loaded.rules(), original.rules());
+}
+
+#[test]
+fn test_save_and_load_combat_stats() {
+    let path = save_path("labyrinth_test_save_combat.save");
+    let map: Map = "1 2 .\n. # .\n. . 0\n".parse().ok().unwrap();
+    let mut rules = GameRules::default();
+    rules.combat = true;
+    rules.dice_sides = 1;
+    rules.attack = 5;
+    let mut original = Game::with_map(names(), rules, map, 6);
+    original.spawn_all().ok().unwrap();
+    assert_eq!(original.get_player_stats(2), (10, 5, 1));
+    original.fight(1, Direction::Right).ok().unwrap();
+    original.save(&path).ok().unwrap();
+    let loaded = Game::load(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded.get_player_stats(1), (10, 5, 1));
+    assert_eq!(loaded.get_player_stats(2), (6, 5, 1));
+    assert_eq!(loaded.get_player_position(2), Some(Position(0, 2)));
+}
+
+#[test]
+fn test_load_unreachable_portal() {
+    let path = save_path("labyrinth_test_save_unreachable.save");
+    let map: Map = "1 . .\n. # .\n2 . 0\n".parse().ok().unwrap();
+    let mut original = Game::with_map(names(), GameRules::default(), map, 6);
+    original.spawn_all().ok().unwrap();
+    original.save(&path).ok().unwrap();
+    let content = std::fs::read_to_string(&path).ok().unwrap();
+    // Walls around the first player.
+    std::fs::write(&path, content.replace("1 . . \n. # . \n", "1 # . \n# # . \n")).ok().unwrap();
+    let loaded = Game::load(&path);
+    std::fs::remove_file(&path).ok();
+    assert!(loaded.err().unwrap().to_string().contains("player 1 can't reach the portal"));
+}
+
+#[test]
+fn test_many_players() {
+    let path = save_path("labyrinth_test_save_many_players.save");
+    let names: Vec<String> = (1..=12).map(|i| format!("player {}", i)).collect();
+    let mut rules = GameRules::with_size(15);
+    rules.max_players = 12;
+    let mut original = Game::with_rules(names, rules, 4);
+    original.spawn_all().ok().unwrap();
+    for code in 1..=12 {
+        assert!(original.get_player_position(code).is_some());
+    }
+    let map = original.display_map();
+    assert!(map.contains('9') && map.contains('A') && map.contains('C'));
+    original.save(&path).ok().unwrap();
+    let loaded = Game::load(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded.display(), original.display());
+    assert_eq!(loaded.get_player_position(11), original.get_player_position(11));
+}
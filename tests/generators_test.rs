@@ -0,0 +1,122 @@
+use project::map::Map;
+use project::generators::{self, MazeGenerator, Braided, RecursiveBacktracker, GENERATORS};
+use project::Position;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+fn generate<G: MazeGenerator + ?Sized>(size: usize, generator: &G, seed: u64) -> Map {
+    return Map::with_generator(size, size, generator, &mut StdRng::seed_from_u64(seed));
+}
+
+fn count_empty(map: &Map) -> usize {
+    let mut count = 0;
+    for x in 0..map.height {
+        for y in 0..map.width {
+            if map.empty(x, y) {
+                count += 1;
+            }
+        }
+    }
+    return count;
+}
+
+fn open_neighbours(map: &Map, x: usize, y: usize) -> usize {
+    let mut neighbours = vec![Position(x + 1, y), Position(x, y + 1)];
+    if x > 0 {
+        neighbours.push(Position(x - 1, y));
+    }
+    if y > 0 {
+        neighbours.push(Position(x, y - 1));
+    }
+    return neighbours.iter().filter(|p| p.0 < map.height && p.1 < map.width && map.empty(p.0, p.1)).count();
+}
+
+#[test]
+fn test_generators_connected() {
+    for name in GENERATORS.iter() {
+        let generator = generators::by_name(name).unwrap();
+        for size in [1, 2, 5, 10, 21].iter() {
+            for seed in 0..5 {
+                assert!(generate(*size, &generator, seed).is_connected(), "{} {} {}", name, size, seed);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_generators_perfect_maze() {
+    // A perfect maze is a tree - the passages between the 6x6 cells are one less than the cells.
+    for name in GENERATORS.iter() {
+        let map = generate(11, &generators::by_name(name).unwrap(), 3);
+        assert_eq!(count_empty(&map), 36 + 35, "{}", name);
+    }
+}
+
+#[test]
+fn test_braided_has_no_dead_ends() {
+    for name in GENERATORS.iter() {
+        let map = generate(15, &Braided(generators::by_name(name).unwrap()), 8);
+        assert!(map.is_connected());
+        for x in (0..map.height).step_by(2) {
+            for y in (0..map.width).step_by(2) {
+                assert!(open_neighbours(&map, x, y) >= 2, "{} ({}, {})", name, x, y);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_generators_deterministic() {
+    for name in GENERATORS.iter() {
+        let generator = generators::by_name(name).unwrap();
+        assert_eq!(generate(15, &generator, 11).display(), generate(15, &generator, 11).display());
+    }
+}
+
+#[test]
+fn test_generators_large_maps() {
+    for name in GENERATORS.iter() {
+        let map = generate(200, &generators::by_name(&format!("braided-{}", name)).unwrap(), 1);
+        assert!(map.is_connected());
+    }
+}
+
+#[test]
+fn test_generator_by_name() {
+    assert!(generators::by_name("prim").is_some());
+    assert!(generators::by_name("braided-wilson").is_some());
+    assert!(generators::by_name("braided-").is_none());
+    assert!(generators::by_name("noise").is_none());
+}
+
+#[test]
+fn test_default_generator() {
+    let mut rng = StdRng::seed_from_u64(4);
+    assert_eq!(Map::new(9, 9, &mut rng).display(), generate(9, &RecursiveBacktracker, 4).display());
+}
+
+#[test]
+fn test_generators_rectangular() {
+    for name in GENERATORS.iter() {
+        let generator = generators::by_name(&format!("braided-{}", name)).unwrap();
+        for (width, height) in [(1, 9), (9, 1), (21, 5), (6, 15)].iter() {
+            let map = Map::with_generator(*width, *height, &generator, &mut StdRng::seed_from_u64(2));
+            assert_eq!(map.width, *width);
+            assert_eq!(map.height, *height);
+            assert_eq!(map.display().lines().count(), *height);
+            assert!(map.display().lines().all(|row| row.len() == 2 * width));
+            assert!(map.is_connected(), "{} {}x{}", name, width, height);
+        }
+    }
+}
+
+#[test]
+fn test_generators_even_sizes() {
+    for name in GENERATORS.iter() {
+        let map = generate(10, &generators::by_name(name).unwrap(), 6);
+        assert!(map.is_connected(), "{}", name);
+        assert!((0..10).any(|x| map.empty(x, 9)), "{}", name);
+        assert!((0..10).any(|y| map.empty(9, y)), "{}", name);
+        assert!(map.empty(9, 9), "{}", name);
+    }
+}
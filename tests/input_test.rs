@@ -0,0 +1,105 @@
+use project::input::{KeyBindings, Command, parse_key, key_name, read_line_from, parse_number, parse_field};
+use project::project_errors::{KeyBindingsError, InputError};
+use std::io::Cursor;
+use project::direction::Direction;
+use project::Position;
+use termion::event::Key;
+
+#[test]
+fn test_default_bindings() {
+    let bindings = KeyBindings::default();
+    assert_eq!(bindings.command(Key::Up), Some(Command::Move(Direction::Up)));
+    assert_eq!(bindings.command(Key::Char('s')), Some(Command::Move(Direction::Down)));
+    assert_eq!(bindings.command(Key::Char('h')), Some(Command::Move(Direction::Left)));
+    assert_eq!(bindings.command(Key::Char('l')), Some(Command::Move(Direction::Right)));
+    assert_eq!(bindings.command(Key::Char('p')), Some(Command::Path));
+    assert_eq!(bindings.command(Key::Ctrl('s')), Some(Command::Save));
+    assert_eq!(bindings.command(Key::Esc), Some(Command::Quit));
+    assert_eq!(bindings.command(Key::Ctrl('c')), Some(Command::Quit));
+    assert_eq!(bindings.command(Key::Ctrl('d')), Some(Command::Quit));
+    assert_eq!(bindings.command(Key::Char('x')), None);
+    assert_eq!(bindings.describe(Command::Move(Direction::Up)), "Up/w/k");
+}
+
+#[test]
+fn test_parse_bindings() {
+    let content = "# arrows and IJKL only\nup = Up, i\ndown = Down, k\nleft = Left, j\nright = Right, l\nsave = F2\n";
+    let bindings: KeyBindings = content.parse().ok().unwrap();
+    assert_eq!(bindings.command(Key::Char('i')), Some(Command::Move(Direction::Up)));
+    assert_eq!(bindings.command(Key::Char('k')), Some(Command::Move(Direction::Down)));
+    assert_eq!(bindings.command(Key::Char('w')), None);
+    assert_eq!(bindings.keys(Command::Save), vec![Key::F(2)]);
+    // Commands missing in the file keep their default keys.
+    assert_eq!(bindings.keys(Command::Quit), vec![Key::Esc, Key::Ctrl('c'), Key::Ctrl('d')]);
+}
+
+#[test]
+fn test_parse_invalid_bindings() {
+    match "up = w\njump = Space\n".parse::<KeyBindings>() {
+        Err(KeyBindingsError::InvalidLine(2, _)) => (),
+        _ => panic!("Something wrong!"),
+    }
+    match "up = Shift-w\n".parse::<KeyBindings>() {
+        Err(KeyBindingsError::InvalidLine(1, _)) => (),
+        _ => panic!("Something wrong!"),
+    }
+    match "quit = \n".parse::<KeyBindings>() {
+        Err(KeyBindingsError::InvalidLine(1, _)) => (),
+        _ => panic!("Something wrong!"),
+    }
+    // Taking the only key of a command leaves it without keys.
+    match "up = Ctrl-s\n".parse::<KeyBindings>() {
+        Err(KeyBindingsError::Invalid(_)) => (),
+        _ => panic!("Something wrong!"),
+    }
+}
+
+#[test]
+fn test_key_names() {
+    for key in [Key::Up, Key::Esc, Key::Char('w'), Key::Char(' '), Key::Char('\n'), Key::Ctrl('s'), Key::F(12)].iter() {
+        assert_eq!(parse_key(&key_name(*key)), Some(*key));
+    }
+    assert_eq!(parse_key("ctrl-C"), Some(Key::Ctrl('c')));
+    assert_eq!(parse_key("F13"), None);
+    assert_eq!(parse_key(""), None);
+}
+
+#[test]
+fn test_read_line() {
+    let mut input = Cursor::new("  two \r\n\n3");
+    assert_eq!(read_line_from(&mut input).ok().unwrap(), "two");
+    assert_eq!(read_line_from(&mut input).ok().unwrap(), "");
+    assert_eq!(read_line_from(&mut input).ok().unwrap(), "3");
+    match read_line_from(&mut input) {
+        Err(InputError::Eof) => (),
+        _ => panic!("Something wrong!"),
+    }
+}
+
+#[test]
+fn test_parse_number() {
+    assert_eq!(parse_number(" 3 ", 2, 4).ok(), Some(3));
+    assert_eq!(parse_number("2", 2, 4).ok(), Some(2));
+    match parse_number("two", 2, 4) {
+        Err(InputError::NotANumber(input)) => assert_eq!(input, "two"),
+        _ => panic!("Something wrong!"),
+    }
+    match parse_number("5", 2, 4) {
+        Err(InputError::OutOfRange { min: 2, max: 4 }) => (),
+        _ => panic!("Something wrong!"),
+    }
+    match parse_number("-1", 2, 4) {
+        Err(InputError::NotANumber(_)) => (),
+        _ => panic!("Something wrong!"),
+    }
+}
+
+#[test]
+fn test_parse_field() {
+    assert_eq!(parse_field("3,5"), Some(Position(2, 4)));
+    assert_eq!(parse_field(" 1 2 "), Some(Position(0, 1)));
+    assert_eq!(parse_field("0,1"), None);
+    assert_eq!(parse_field("3"), None);
+    assert_eq!(parse_field("dds"), None);
+    assert_eq!(parse_field("1,2,3"), None);
+}
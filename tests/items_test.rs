@@ -0,0 +1,207 @@
+use project::game::Game;
+use project::rules::GameRules;
+use project::engine::{Action, Event, Phase};
+use project::project_errors::GameError;
+use project::items::Item;
+use project::direction::Direction;
+use project::{Position, POTION_MOVES, TREASURE_SCORE};
+
+mod common;
+use common::{names, started_game, play};
+
+/// A started game where every player rolls 1.
+fn items_game(map: &str, rules: GameRules) -> Game {
+    let mut rules = rules;
+    rules.dice_sides = 1;
+    return started_game(map, rules, 2);
+}
+
+#[test]
+fn test_item_symbols() {
+    for item in Item::ALL.iter() {
+        assert_eq!(Item::from_symbol(item.symbol()), Some(*item));
+        assert_eq!(item.name().parse::<Item>(), Ok(*item));
+        assert_eq!(item.symbol().to_string().parse::<Item>(), Ok(*item));
+    }
+    assert_eq!(" Potion ".parse::<Item>(), Ok(Item::Potion));
+    assert_eq!("sword".parse::<Item>(), Err(GameError::InvalidItem));
+    assert_eq!(Item::from_symbol('.'), None);
+}
+
+#[test]
+fn test_pick_up_treasure() {
+    let mut test_game = items_game("1 $ ! %\n# # # .\n2 . . 0\n", GameRules::default());
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events[0], Event::Moved { player: 1, to: Position(0, 1) });
+    assert_eq!(events[1], Event::ItemPicked { player: 1, item: Item::Treasure });
+    assert_eq!(test_game.get_player_items(1), &[Item::Treasure]);
+    assert_eq!(test_game.display_map(), ". 1 ! % \n# # # . \n2 . . 0 \n");
+    assert!(test_game.finish().contains(&format!("first\nscore: {}\n", TREASURE_SCORE)));
+}
+
+#[test]
+fn test_use_potion() {
+    let mut test_game = items_game("1 ! . .\n# # # .\n2 . . 0\n", GameRules::default());
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(test_game.apply(Action::Use(Item::Potion)), Err(GameError::InvalidAction));
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    let events = play(&mut test_game, Action::Use(Item::Potion)).ok().unwrap();
+    assert_eq!(events, vec![Event::ItemUsed { player: 1, item: Item::Potion }]);
+    assert_eq!(test_game.phase(), Phase::Move { player: 1, moves_left: 1 + POTION_MOVES });
+    assert!(test_game.get_player_items(1).is_empty());
+    assert_eq!(test_game.apply(Action::Use(Item::Potion)), Err(GameError::NoItem));
+}
+
+#[test]
+fn test_use_compass() {
+    let mut test_game = items_game("1 * . .\n# # # .\n2 . . 0\n", GameRules::default());
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    let events = play(&mut test_game, Action::Use(Item::Compass)).ok().unwrap();
+    assert_eq!(events, vec![Event::Compass { player: 1, direction: Some(Direction::Right), steps: 4 }]);
+    assert_eq!(test_game.describe(&events[0]).unwrap(), "first's compass points right, the portal is 4 steps away.");
+    // The compass isn't used up.
+    assert_eq!(test_game.get_player_items(1), &[Item::Compass]);
+    assert_eq!(test_game.moves_left(), 1);
+}
+
+#[test]
+fn test_compass_and_doors() {
+    // Without a key there is no way through the door.
+    let mut test_game = items_game("1 * + 0\n# # . .\n2 . . .\n", GameRules::default());
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    let events = play(&mut test_game, Action::Use(Item::Compass)).ok().unwrap();
+    assert_eq!(events, vec![Event::Compass { player: 1, direction: None, steps: 0 }]);
+
+    // The way picks up the key first, which is shorter than going around the door.
+    let mut test_game = items_game("1 * + . 0\n& # # # .\n. . . . 2\n", GameRules::default());
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    let events = play(&mut test_game, Action::Use(Item::Compass)).ok().unwrap();
+    assert_eq!(events, vec![Event::Compass { player: 1, direction: Some(Direction::Left), steps: 7 }]);
+}
+
+#[test]
+fn test_items_not_usable() {
+    let mut test_game = items_game("1 % . .\n# # # .\n2 . . 0\n", GameRules::default());
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    test_game.apply(Action::Roll).ok().unwrap();
+    assert_eq!(test_game.apply(Action::Use(Item::Shield)), Err(GameError::NotUsable));
+    assert_eq!(test_game.apply(Action::Use(Item::Compass)), Err(GameError::NoItem));
+}
+
+#[test]
+fn test_shield_saves_from_battle() {
+    let mut test_game = items_game("1 . 2 %\n# # # .\n0 . . .\n", GameRules::default());
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events[0], Event::Shielded { player: 2 });
+    assert!(test_game.in_game(2));
+    assert!(test_game.get_player_items(2).is_empty());
+    assert_eq!(test_game.get_player_position(1), Some(Position(0, 2)));
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert!(events.contains(&Event::Battle { winner: 1, loser: 2, points: 15 }));
+}
+
+#[test]
+fn test_items_dropped() {
+    let mut test_game = items_game("1 & 2 .\n# # # .\n. . . 0\n", GameRules::default());
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    let events = play(&mut test_game, Action::Move(Direction::Left)).ok().unwrap();
+    assert_eq!(events[0], Event::Battle { winner: 2, loser: 1, points: 15 });
+    assert!(test_game.get_player_items(1).is_empty());
+    // The key is left on the nearest empty field.
+    assert_eq!(test_game.map().items(), vec![(Position(0, 0), Item::Key)]);
+
+    let mut rules = GameRules::default();
+    rules.set("mode", "exit-all").ok().unwrap();
+    let mut test_game = items_game("1 ! 0\n. # .\n2 . .\n", rules);
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events[0], Event::Portal { player: 1, points: 50 });
+    assert_eq!(test_game.map().items(), vec![(Position(0, 1), Item::Potion)]);
+}
+
+#[test]
+fn test_shield_saves_from_combat() {
+    let mut rules = GameRules::default();
+    rules.combat = true;
+    rules.attack = 10;
+    rules.defense = 0;
+    rules.health = 1;
+    let mut test_game = items_game("1 . 2 %\n# # # .\n0 . . .\n", rules);
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events[1], Event::Shielded { player: 2 });
+    assert_eq!(events[2], Event::Wounded { player: 2, damage: 10, health: 1 });
+    assert!(test_game.in_game(2));
+}
+
+#[test]
+fn test_locked_portal() {
+    let mut rules = GameRules::default();
+    rules.locked_portal = true;
+    let mut test_game = items_game("1 0 &\n# # .\n2 . .\n", rules.clone());
+    assert_eq!(play(&mut test_game, Action::Move(Direction::Right)), Err(GameError::PortalLocked));
+    assert_eq!(test_game.phase(), Phase::Move { player: 1, moves_left: 1 });
+    assert_eq!(test_game.apply(Action::Path(vec![Direction::Right])), Err(GameError::PortalLocked));
+
+    let mut test_game = items_game("1 & 0\n# # .\n2 . .\n", rules);
+    // The map has its own key, no more keys are scattered.
+    assert_eq!(test_game.map().items(), vec![(Position(0, 1), Item::Key)]);
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert!(events.contains(&Event::GameFinished(1)));
+}
+
+#[test]
+fn test_scatter_items() {
+    let mut rules = GameRules::with_size(9);
+    rules.items = 6;
+    rules.locked_portal = true;
+    let mut test_game = Game::with_rules(names(2), rules, 7);
+    test_game.apply(Action::Start).ok().unwrap();
+    let items = test_game.map().items();
+    assert_eq!(items.len(), 8);
+    assert_eq!(items.iter().filter(|(_, item)| *item == Item::Key).count(), 2);
+
+    let mut test_game = Game::with_rules(names(2), GameRules::with_size(9), 7);
+    test_game.apply(Action::Start).ok().unwrap();
+    assert!(test_game.map().items().is_empty());
+}
+
+#[test]
+fn test_save_and_load_items() {
+    let path = std::env::temp_dir().join("labyrinth_test_items.save");
+    let path = path.to_str().unwrap();
+    let mut test_game = items_game("1 $ ! %\n# # # .\n2 . . 0\n", GameRules::default());
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    test_game.save(path).ok().unwrap();
+    let loaded = Game::load(path).ok().unwrap();
+    std::fs::remove_file(path).ok();
+    assert_eq!(loaded.get_player_items(1), &[Item::Treasure]);
+    assert!(loaded.get_player_items(2).is_empty());
+    assert_eq!(loaded.display_map(), test_game.display_map());
+}
+
+#[test]
+fn test_load_old_compass() {
+    let path = std::env::temp_dir().join("labyrinth_test_old_compass.save");
+    let path = path.to_str().unwrap();
+    std::fs::write(path, "labyrinth-save 8\nseed 5\nrng 5\nsize 3 2\nturn 1\nmoves 0\n\
+        player 1 0 0 0 human first\nplayer 2 0 0 2 human second\nmap\n1 ^ 2\n. . 0\n").ok().unwrap();
+    let loaded = Game::load(path).ok().unwrap();
+    std::fs::remove_file(path).ok();
+    assert_eq!(loaded.display_map(), format!("1 {} 2 \n. . 0 \n", Item::Compass.symbol()));
+}
@@ -1,10 +1,13 @@
-use project::{map::Map, player::Player};
-use project::project_errors::GameError;
-use project::{Position, directions};
+use project::{map::{Map, Occupant}, player::Player};
+use project::project_errors::{GameError, MapError};
+use project::Position;
+use project::direction::Direction;
+use project::items::Item;
+use project::tiles::Tile;
 
 fn map() -> Map {
     let matrix_array: &[&[i8]] = &[&[0, 1, 1, 0], &[0, 0, 1, 0], &[1, 0, 0, 0], &[1, 1, 0, 0 ]];
-    return Map::from_matrix(4, matrix_array);
+    return Map::from_matrix(matrix_array);
 }
 
 fn player() -> Player {
@@ -68,7 +71,7 @@ fn test_move_player() {
     let expected: String = ". # # . \n1 . # . \n# . . . \n# # . . \n".to_string();
     match test_map.spawn_player(&mut test_player, Position(0, 0)) {
         Ok(_) => {
-            match test_map.move_player(&mut test_player, directions::DOWN) {
+            match test_map.move_player(&mut test_player, Direction::Down) {
                 Ok(_) => assert_eq!(test_map.display(), expected),
                 Err(_) => panic!("Something wrong!"),
             }
@@ -83,7 +86,7 @@ fn test_move_player_error_invalid_coordinates() {
     let mut test_player = player();
     match test_map.spawn_player(&mut test_player, Position(0, 0)) {
         Ok(_) => {
-            match test_map.move_player(&mut test_player, directions::RIGHT) {
+            match test_map.move_player(&mut test_player, Direction::Right) {
                 Ok(_) => panic!("Something wrong!"),
                 Err(e) => assert_eq!(e, GameError::InvalidCoordinates),
             }
@@ -100,7 +103,7 @@ fn test_move_player_error_another_player() {
     match test_map.spawn_player(&mut test_player1, Position(0, 0)) {
         Ok(_) => {
             test_map.spawn_player(&mut test_player2, Position(1, 0)).ok().unwrap();
-            match test_map.move_player(&mut test_player1, directions::DOWN) {
+            match test_map.move_player(&mut test_player1, Direction::Down) {
                 Ok(_) => panic!("Something wrong!"),
                 Err(e) => assert_eq!(e, GameError::AnotherPlayer),
             }
@@ -115,7 +118,7 @@ fn test_move_player_error_outside() {
     let mut test_player = player();
     match test_map.spawn_player(&mut test_player, Position(0, 0)) {
         Ok(_) => {
-            match test_map.move_player(&mut test_player, directions::UP) {
+            match test_map.move_player(&mut test_player, Direction::Up) {
                 Ok(_) => panic!("Something wrong!"),
                 Err(e) => assert_eq!(e, GameError::Outside),
             }
@@ -133,4 +136,131 @@ fn test_kill_player() {
     test_map.kill_player(&mut test_player);
     let expected: String = ". # # . \n. . # . \n# . . . \n# # . . \n".to_string();
     assert_eq!(map().display(), expected);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_occupants_over_tiles() {
+    let mut test_map: Map = "~ , $\n# . 0\n".parse().ok().unwrap();
+    let mut test_player = player();
+    test_map.spawn_player(&mut test_player, Position(0, 0)).ok().unwrap();
+    assert_eq!(test_map.occupant(Position(0, 0)), Some(Occupant::Player(1)));
+    assert_eq!(test_map.player_at(Position(0, 0)), Some(1));
+    assert_eq!(test_map.tile(Position(0, 0)), Tile::Water);
+    assert_eq!(test_map.display(), "1 , $ \n# . 0 \n");
+    test_map.move_player(&mut test_player, Direction::Right).ok().unwrap();
+    assert_eq!(test_map.display(), "~ 1 $ \n# . 0 \n");
+    test_map.move_player(&mut test_player, Direction::Right).ok().unwrap();
+    assert_eq!(test_map.item(Position(0, 2)), None);
+    assert_eq!(test_map.occupants(), vec![(Position(0, 2), Occupant::Player(1))]);
+    test_map.kill_player(&mut test_player);
+    assert_eq!(test_map.display(), "~ , . \n# . 0 \n");
+    assert_eq!(test_map.portal(), Some(Position(1, 2)));
+    // Players can't stand on walls or on the portal.
+    assert_eq!(test_map.spawn_player(&mut test_player, Position(1, 2)), Err(GameError::InvalidCoordinates));
+    assert_eq!(test_map.spawn_player(&mut test_player, Position(1, 0)), Err(GameError::InvalidCoordinates));
+}
+
+#[test]
+fn test_map_from_str() {
+    let test_map: Map = ". # # .\n1 . # .\n# . . 0\n# # . 2\n".parse().ok().unwrap();
+    let expected: String = ". # # . \n. . # . \n# . . 0 \n# # . . \n".to_string();
+    assert_eq!(test_map.display(), expected);
+    assert_eq!(test_map.spawn_point(1), Some(Position(1, 0)));
+    assert_eq!(test_map.spawn_point(2), Some(Position(3, 3)));
+    assert_eq!(test_map.spawn_point(3), None);
+    assert_eq!(test_map.has_portal(), true);
+}
+
+#[test]
+fn test_map_from_str_items() {
+    let test_map: Map = "1 $ .\n# ! &\n% * 0\n".parse().ok().unwrap();
+    assert_eq!(test_map.display(), ". $ . \n# ! & \n% * 0 \n");
+    assert_eq!(test_map.item(Position(0, 1)), Some(Item::Treasure));
+    assert_eq!(test_map.item(Position(0, 2)), None);
+    assert_eq!(test_map.items().len(), 5);
+}
+
+#[test]
+fn test_map_from_str_compact_and_comments() {
+    let test_map: Map = "; a small map\n.##.\n..#.\n\n#...\n##..\n".parse().ok().unwrap();
+    assert_eq!(test_map.display(), map().display());
+    assert_eq!(test_map.has_portal(), false);
+}
+
+#[test]
+fn test_map_from_display() {
+    let test_map: Map = map().display().parse().ok().unwrap();
+    assert_eq!(test_map.display(), map().display());
+}
+
+fn parse_error(content: &str) -> (usize, usize) {
+    match content.parse::<Map>() {
+        Err(MapError::Parse { line, column, .. }) => return (line, column),
+        _ => panic!("Something wrong!"),
+    }
+}
+
+#[test]
+fn test_map_from_str_errors() {
+    assert_eq!(parse_error(". .\n. x\n"), (2, 3));
+    assert_eq!(parse_error(". .\n. . .\n"), (2, 5));
+    assert_eq!(parse_error(". . .\n. .\n. . .\n"), (2, 4));
+    assert_eq!(parse_error("0 .\n. 0\n"), (2, 3));
+    assert_eq!(parse_error("1 .\n. 1\n"), (2, 3));
+    assert_eq!(parse_error("; nothing\n"), (1, 1));
+    // The starting fields and the portal must be reachable from each other.
+    assert_eq!(parse_error("1 # 2\n. # .\n"), (1, 5));
+    assert_eq!(parse_error("; walled off\n1 . #\n# # 0\n"), (3, 5));
+    assert!("1 . #\n# # .\n".parse::<Map>().is_ok());
+}
+
+#[test]
+fn test_map_load_missing_file() {
+    match Map::load("/nonexistent/labyrinth.map") {
+        Err(MapError::Io(_)) => (),
+        _ => panic!("Something wrong!"),
+    }
+}
+
+#[test]
+fn test_map_load_example() {
+    let test_map = Map::load("maps/example.map").ok().unwrap();
+    assert_eq!(test_map.width, 10);
+    assert_eq!(test_map.height, 10);
+    assert_eq!(test_map.spawn_point(4), Some(Position(9, 9)));
+}
+
+fn wide_map() -> Map {
+    return "1 . . . . . .\n# # . # . # 0\n".parse().ok().unwrap();
+}
+
+#[test]
+fn test_rectangular_map_from_str() {
+    let test_map = wide_map();
+    assert_eq!(test_map.width, 7);
+    assert_eq!(test_map.height, 2);
+    assert_eq!(test_map.display(), ". . . . . . . \n# # . # . # 0 \n");
+    assert_eq!(test_map.get_field(Position(1, 6)), '0');
+    assert_eq!(test_map.is_connected(), true);
+}
+
+#[test]
+fn test_rectangular_map_moves() {
+    let mut test_map = wide_map();
+    let mut test_player = player();
+    test_map.spawn_player(&mut test_player, Position(0, 6)).ok().unwrap();
+    assert_eq!(test_map.move_player(&mut test_player, Direction::Right), Err(GameError::Outside));
+    assert_eq!(test_map.move_player(&mut test_player, Direction::Down), Err(GameError::Portal));
+    test_map.kill_player(&mut test_player);
+    test_map.spawn_player(&mut test_player, Position(1, 2)).ok().unwrap();
+    assert_eq!(test_map.move_player(&mut test_player, Direction::Down), Err(GameError::Outside));
+    assert_eq!(test_map.move_player(&mut test_player, Direction::Up), Ok(()));
+}
+
+#[test]
+fn test_rectangular_matrix() {
+    let test_map = Map::from_matrix(&[&[0, 0, 1], &[1, 0, 0]]);
+    assert_eq!(test_map.width, 3);
+    assert_eq!(test_map.height, 2);
+    assert_eq!(test_map.display(), ". . # \n# . . \n");
+}
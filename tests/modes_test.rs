@@ -0,0 +1,152 @@
+use project::game::Game;
+use project::rules::GameRules;
+use project::engine::{Action, Event, Phase};
+use project::replay::Replay;
+use project::modes::{Mode, MODES};
+use project::direction::Direction;
+use std::time::Duration;
+
+mod common;
+use common::{rules, started_game, play};
+
+fn mode_rules(mode: &str) -> GameRules {
+    let mut rules = rules();
+    rules.set("mode", mode).ok().unwrap();
+    return rules;
+}
+
+fn save_path(name: &str) -> String {
+    return std::env::temp_dir().join(name).to_str().unwrap().to_string();
+}
+
+#[test]
+fn test_mode_rules() {
+    let mut rules = GameRules::default();
+    assert_eq!(rules.mode, Mode::Portal);
+    assert!(rules.set("mode", "exit-all").is_ok());
+    assert!(rules.set("mode", "marathon").is_err());
+    assert_eq!(rules.mode, Mode::ExitAll);
+    for (mode, name) in MODES.iter() {
+        assert_eq!(mode.name(), *name);
+        assert_eq!(Mode::from_name(name), Some(*mode));
+    }
+    assert_eq!(Mode::from_name("portals"), None);
+    rules.rounds = 0;
+    assert!(rules.validate().is_err());
+}
+
+#[test]
+fn test_exit_all() {
+    let mut test_game = started_game("1 0 2\n. # .\n3 . .\n", mode_rules("exit-all"), 3);
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events, vec![Event::Portal { player: 1, points: 50 }, Event::TurnEnded(1), Event::TurnStarted(2)]);
+    assert_eq!(test_game.describe(&events[0]).unwrap(), "first walks through the portal and leaves the labyrinth with 50 points!");
+    assert_eq!(test_game.display_map(), ". 0 2 \n. # . \n3 . . \n");
+    let events = play(&mut test_game, Action::Move(Direction::Left)).ok().unwrap();
+    assert_eq!(events, vec![Event::Portal { player: 2, points: 50 }, Event::TurnEnded(2), Event::TurnStarted(3)]);
+    play(&mut test_game, Action::Move(Direction::Up)).ok().unwrap();
+    play(&mut test_game, Action::Move(Direction::Up)).ok().unwrap();
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events, vec![Event::Portal { player: 3, points: 50 }, Event::GameFinished(1)]);
+    assert_eq!(test_game.phase(), Phase::Finished);
+    assert_eq!(test_game.exits(), &[1, 2, 3]);
+    // Everybody has the same score, the order they got out decides.
+    let ranklist = test_game.finish();
+    assert!(ranklist.starts_with("--- 1 ---\nfirst\n"));
+    assert!(ranklist.contains("--- 3 ---\nthird\n"));
+}
+
+#[test]
+fn test_last_standing() {
+    let mut test_game = started_game("1 2 0\n. # .\n. . .\n", mode_rules("last-standing"), 2);
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events.last(), Some(&Event::GameFinished(1)));
+    assert_eq!(test_game.describe(&Event::GameFinished(1)).unwrap(), "The game is over! first wins!");
+    assert_eq!(test_game.winner(), Some(1));
+}
+
+#[test]
+fn test_first_to_points() {
+    let mut rules = mode_rules("points");
+    rules.target_score = 60;
+    let mut test_game = started_game("1 2 . 0\n. # # .\n. . . .\n", rules, 2);
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events[0], Event::Battle { winner: 1, loser: 2, points: 15 });
+    assert_eq!(test_game.phase(), Phase::Roll(1));
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events, vec![Event::Portal { player: 1, points: 50 }, Event::GameFinished(1)]);
+}
+
+#[test]
+fn test_fixed_rounds() {
+    let mut rules = mode_rules("rounds");
+    rules.rounds = 2;
+    let mut test_game = started_game("1 . 0\n. # .\n2 . .\n", rules, 2);
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    assert_eq!(test_game.round(), 2);
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    let events = play(&mut test_game, Action::Skip).ok().unwrap();
+    assert_eq!(events, vec![Event::MoveSkipped(2), Event::TurnEnded(2), Event::TurnStarted(1), Event::GameFinished(0)]);
+    assert_eq!(test_game.describe(&events[3]).unwrap(), "The game is over, nobody wins.");
+    assert_eq!(test_game.winner(), None);
+    assert_eq!(test_game.phase(), Phase::Finished);
+}
+
+#[test]
+fn test_timed_rounds() {
+    let path = save_path("labyrinth_test_timed.save");
+    let mut rules = mode_rules("timed");
+    rules.time_limit = 1;
+    let mut test_game = started_game("1 . 0\n. # .\n2 . .\n", rules, 2);
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    // The time only passes when the frontend says so.
+    assert_eq!(test_game.elapsed(), Duration::from_secs(0));
+    assert_eq!(test_game.apply(Action::Time(Duration::from_secs(120))), Ok(vec![]));
+    test_game.save(&path).ok().unwrap();
+    let mut loaded = Game::load(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded.elapsed(), Duration::from_secs(120));
+    assert_eq!(loaded.round(), 1);
+    let events = play(&mut loaded, Action::Skip).ok().unwrap();
+    assert_eq!(events, vec![Event::MoveSkipped(2), Event::TurnEnded(2), Event::TurnStarted(1), Event::TimeUp, Event::GameFinished(0)]);
+    assert_eq!(loaded.phase(), Phase::Finished);
+}
+
+#[test]
+fn test_replay_timed_game() {
+    let path = save_path("labyrinth_test_timed.replay");
+    let mut rules = mode_rules("timed");
+    rules.time_limit = 1;
+    let mut test_game = started_game("1 . 0\n. # .\n2 . .\n", rules, 2);
+    test_game.record(&path).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    test_game.apply(Action::Time(Duration::from_millis(61500))).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    let content = std::fs::read_to_string(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert!(content.ends_with("skip 1\ntime 61500\nroll 2 1\nskip 2\ntimeup\n"));
+    // The replay passes the recorded time to the game, however fast it is played.
+    let replay: Replay = content.parse().ok().unwrap();
+    assert!(replay.frames().last().unwrap().description.contains("Time is up! The game is over, nobody wins."));
+    // Without the time the game goes on.
+    assert!(content.replace("time 61500\n", "").parse::<Replay>().is_err());
+    assert!(content.replace("time 61500\n", "time 1500\n").parse::<Replay>().is_err());
+}
+
+#[test]
+fn test_save_and_load_exits() {
+    let path = save_path("labyrinth_test_exits.save");
+    let mut test_game = started_game("1 0 2\n. # .\n3 . .\n", mode_rules("exit-all"), 3);
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    test_game.save(&path).ok().unwrap();
+    let loaded = Game::load(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded.exits(), &[1]);
+    assert_eq!(loaded.round(), 2);
+    assert_eq!(loaded.turn(), 2);
+    assert!(!loaded.in_game(1));
+}
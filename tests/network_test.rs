@@ -0,0 +1,170 @@
+use project::game::Game;
+use project::map::Map;
+use project::rules::GameRules;
+use project::network::{Server, Client, ServerMessage, ClientMessage};
+use project::project_errors::NetworkError;
+use project::direction::Direction;
+use project::items::Item;
+use std::thread;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+fn game(names: Vec<String>) -> Game {
+    let map: Map = "1 . 0 . 2\n".parse().ok().unwrap();
+    let mut rules = GameRules::default();
+    rules.dice_sides = 1;
+    return Game::with_map(names, rules, map, 1);
+}
+
+/// Receives messages until the server asks for a move or the game finishes.
+/// Returns all the messages received.
+fn wait_for_turn(client: &mut Client) -> Vec<ServerMessage> {
+    let mut messages: Vec<ServerMessage> = Vec::new();
+    loop {
+        let message = client.receive().ok().unwrap();
+        messages.push(message.clone());
+        match message {
+            ServerMessage::YourMove(..) | ServerMessage::Finished(_) => return messages,
+            _ => (),
+        }
+    }
+}
+
+/// Reads a message from the server without a `Client`.
+fn read_message(reader: &mut BufReader<TcpStream>) -> ServerMessage {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok().unwrap();
+    return line.trim_end().parse().ok().unwrap();
+}
+
+#[test]
+fn test_messages() {
+    let messages = [
+        ServerMessage::Welcome(2),
+        ServerMessage::Taken,
+        ServerMessage::Map("1 . #\n. 0 2\n".to_string()),
+        ServerMessage::Info("first rolls 4.".to_string()),
+        ServerMessage::YourMove(3, vec![]),
+        ServerMessage::YourMove(2, vec![Item::Potion, Item::Compass]),
+        ServerMessage::Error("Invalid direction!\n".to_string()),
+        ServerMessage::Finished("--- 1 ---\nfirst\nscore: 50\n".to_string()),
+    ];
+    for message in messages.iter() {
+        assert!(!message.encode().contains('\n'));
+        assert_eq!(message.encode().parse::<ServerMessage>().ok().unwrap(), *message);
+    }
+    for message in [ClientMessage::Join("John Smith".to_string()), ClientMessage::Move(Direction::Right),
+        ClientMessage::Path(vec![Direction::Right, Direction::Down, Direction::Down])].iter() {
+        assert_eq!(message.encode().parse::<ClientMessage>().ok().unwrap(), *message);
+    }
+    match "hello there".parse::<ServerMessage>() {
+        Err(NetworkError::Protocol(_)) => (),
+        _ => panic!("Something wrong!"),
+    }
+    assert!("move many".parse::<ServerMessage>().is_err());
+    assert!("move 2 sword".parse::<ServerMessage>().is_err());
+    assert!("path".parse::<ClientMessage>().is_err());
+}
+
+#[test]
+fn test_lobby_and_game() {
+    let mut server = Server::bind("127.0.0.1:0").ok().unwrap();
+    let address = server.local_addr().ok().unwrap();
+    let host = thread::spawn(move || {
+        let names = server.lobby(2).ok().unwrap();
+        assert_eq!(names, vec!["first".to_string(), "second".to_string()]);
+        return server.run(game(names)).ok().unwrap();
+    });
+
+    let mut first = Client::connect(address).ok().unwrap();
+    assert_eq!(first.join("first").ok().unwrap(), Some(1));
+    let mut second = Client::connect(address).ok().unwrap();
+    assert_eq!(second.join("first").ok().unwrap(), None);
+    assert_eq!(second.join("second").ok().unwrap(), Some(2));
+
+    // The first player tries to leave the map, then moves towards the portal.
+    let messages = wait_for_turn(&mut first);
+    assert!(messages.contains(&ServerMessage::Info("second joined the game (2/2).".to_string())));
+    assert!(messages.contains(&ServerMessage::Map("1 . 0 . 2 \n".to_string())));
+    assert_eq!(messages.last(), Some(&ServerMessage::YourMove(1, vec![])));
+    first.send_move(Direction::Up).ok().unwrap();
+    assert_eq!(first.receive().ok().unwrap(), ServerMessage::Error("Coordinates outside the field!\n".to_string()));
+    assert_eq!(wait_for_turn(&mut first).last(), Some(&ServerMessage::YourMove(1, vec![])));
+    first.send_move(Direction::Right).ok().unwrap();
+
+    let messages = wait_for_turn(&mut second);
+    assert!(messages.contains(&ServerMessage::Map(". 1 0 . 2 \n".to_string())));
+    assert!(messages.contains(&ServerMessage::Info("second rolls 1.".to_string())));
+    second.send_move(Direction::Left).ok().unwrap();
+
+    wait_for_turn(&mut first);
+    first.send_move(Direction::Right).ok().unwrap();
+    let ranklist = host.join().ok().unwrap();
+    let messages = wait_for_turn(&mut first);
+    assert!(messages.contains(&ServerMessage::Info("Portal has been found! first wins 50 points!".to_string())));
+    assert_eq!(messages.last(), Some(&ServerMessage::Finished(ranklist.clone())));
+    assert_eq!(wait_for_turn(&mut second).last(), Some(&ServerMessage::Finished(ranklist)));
+}
+
+#[test]
+fn test_player_disconnects() {
+    let mut server = Server::bind("127.0.0.1:0").ok().unwrap();
+    let address = server.local_addr().ok().unwrap();
+    let host = thread::spawn(move || {
+        let names = server.lobby(2)?;
+        return server.run(game(names));
+    });
+    // The first player talks to the server without a `Client` to send invalid messages.
+    let mut first = TcpStream::connect(address).ok().unwrap();
+    let mut reader = BufReader::new(first.try_clone().ok().unwrap());
+    writeln!(first, "hello").ok().unwrap();
+    assert_eq!(read_message(&mut reader), ServerMessage::Error("Unexpected message: hello\n".to_string()));
+    writeln!(first, "join first").ok().unwrap();
+    assert_eq!(read_message(&mut reader), ServerMessage::Welcome(1));
+    let mut second = Client::connect(address).ok().unwrap();
+    assert_eq!(second.join("second").ok().unwrap(), Some(2));
+
+    while read_message(&mut reader) != ServerMessage::YourMove(1, vec![]) {}
+    writeln!(first, "fly away").ok().unwrap();
+    assert_eq!(read_message(&mut reader), ServerMessage::Error("Unexpected message: fly away\n".to_string()));
+    while read_message(&mut reader) != ServerMessage::YourMove(1, vec![]) {}
+    drop(reader);
+    drop(first);
+
+    // The game goes on without the first player.
+    let messages = wait_for_turn(&mut second);
+    assert!(messages.contains(&ServerMessage::Info("first has left the game.".to_string())));
+    second.send_move(Direction::Left).ok().unwrap();
+    wait_for_turn(&mut second);
+    second.send_move(Direction::Left).ok().unwrap();
+    let ranklist = host.join().ok().unwrap().ok().unwrap();
+    assert_eq!(wait_for_turn(&mut second).last(), Some(&ServerMessage::Finished(ranklist)));
+}
+
+#[test]
+fn test_player_too_slow() {
+    let mut server = Server::bind("127.0.0.1:0").ok().unwrap();
+    server.set_move_timeout(Duration::from_millis(200));
+    let address = server.local_addr().ok().unwrap();
+    let host = thread::spawn(move || {
+        let names = server.lobby(2)?;
+        return server.run(game(names));
+    });
+    let mut first = Client::connect(address).ok().unwrap();
+    assert_eq!(first.join("first").ok().unwrap(), Some(1));
+    let mut second = Client::connect(address).ok().unwrap();
+    assert_eq!(second.join("second").ok().unwrap(), Some(2));
+
+    // The first player never answers, so the second player gets their turn.
+    wait_for_turn(&mut first);
+    let messages = wait_for_turn(&mut second);
+    assert!(messages.contains(&ServerMessage::Info("-- PLAYER 2 --\nIt's second's turn.".to_string())));
+    second.send_move(Direction::Left).ok().unwrap();
+    wait_for_turn(&mut second);
+    second.send_move(Direction::Left).ok().unwrap();
+    let ranklist = host.join().ok().unwrap().ok().unwrap();
+    assert_eq!(wait_for_turn(&mut second).last(), Some(&ServerMessage::Finished(ranklist)));
+    let messages = wait_for_turn(&mut first);
+    assert!(messages.contains(&ServerMessage::Error("The other side didn't answer in time.\n".to_string())));
+}
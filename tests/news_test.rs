@@ -0,0 +1,95 @@
+use project::game::Game;
+use project::map::Map;
+use project::rules::GameRules;
+use project::engine::{Action, Event};
+use project::direction::Direction;
+
+fn game() -> Game {
+    let map: Map = "1 . 2 . 3\n. # # # .\n. . 0 . .\n".parse().ok().unwrap();
+    let mut rules = GameRules::default();
+    rules.dice_sides = 1;
+    let names = vec!["first".to_string(), "second".to_string(), "third".to_string()];
+    let mut game = Game::with_map(names, rules, map, 2);
+    game.apply(Action::Start).ok().unwrap();
+    return game;
+}
+
+fn play(game: &mut Game, direction: Direction) {
+    game.apply(Action::Roll).ok().unwrap();
+    game.apply(Action::Move(direction)).ok().unwrap();
+}
+
+#[test]
+fn test_history() {
+    let mut test_game = game();
+    play(&mut test_game, Direction::Right);
+    assert_eq!(test_game.history()[0], Event::GameStarted);
+    assert_eq!(test_game.history().len(), 6);
+    assert_eq!(test_game.history()[2], Event::DiceRolled { player: 1, value: 1 });
+}
+
+#[test]
+fn test_news_since_last_turn() {
+    let mut test_game = game();
+    test_game.take_news(1);
+    play(&mut test_game, Direction::Right);
+    test_game.take_news(1);
+    play(&mut test_game, Direction::Left);
+    play(&mut test_game, Direction::Down);
+    let news = test_game.take_news(1);
+    assert!(news.contains(&Event::Battle { winner: 2, loser: 1, points: 15 }));
+    assert!(!news.contains(&Event::DiceRolled { player: 1, value: 1 }));
+    assert_eq!(test_game.take_news(1), vec![]);
+    assert_eq!(test_game.take_news(3).len(), test_game.history().len());
+}
+
+#[test]
+fn test_describe() {
+    let test_game = game();
+    assert_eq!(test_game.describe(&Event::Battle { winner: 2, loser: 1, points: 15 }),
+        Some("first was killed! second wins 15 points!".to_string()));
+    assert_eq!(test_game.describe(&Event::Portal { player: 3, points: 50 }),
+        Some("Portal has been found! third wins 50 points!".to_string()));
+    assert_eq!(test_game.describe(&Event::TurnStarted(2)), Some("It's second's turn.".to_string()));
+    assert_eq!(test_game.describe(&Event::GameStarted), None);
+}
+
+#[test]
+fn test_pins() {
+    let path = std::env::temp_dir().join("labyrinth_test_pins.save").to_str().unwrap().to_string();
+    let mut test_game = game();
+    test_game.set_pin(1, Some("1234".to_string()));
+    test_game.set_pin(3, Some("my secret".to_string()));
+    assert!(test_game.has_pin(1));
+    assert!(!test_game.has_pin(2));
+    assert!(test_game.check_pin(1, "1234"));
+    assert!(!test_game.check_pin(1, "4321"));
+    assert!(test_game.check_pin(2, ""));
+
+    test_game.save(&path).ok().unwrap();
+    let content = std::fs::read_to_string(&path).ok().unwrap();
+    let loaded = Game::load(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    // Only the hashes are saved.
+    assert!(!content.contains("1234"));
+    assert!(!content.contains("my secret"));
+    assert!(loaded.check_pin(1, "1234"));
+    assert!(!loaded.check_pin(1, "4321"));
+    assert!(loaded.check_pin(3, "my secret"));
+    assert!(!loaded.has_pin(2));
+
+    // Older saves have the PINs themselves.
+    let old: Vec<String> = content.lines().map(|line| match line {
+        line if line.starts_with("labyrinth-save ") => "labyrinth-save 11".to_string(),
+        line if line.starts_with("rng ") => line.rsplitn(2, ' ').last().unwrap().to_string(),
+        line if line.starts_with("pin 1 ") => "pin 1 1234".to_string(),
+        line if line.starts_with("pin 3 ") => "pin 3 my secret".to_string(),
+        line => line.to_string(),
+    }).collect();
+    std::fs::write(&path, old.join("\n") + "\n").ok().unwrap();
+    let loaded = Game::load(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert!(loaded.check_pin(1, "1234"));
+    assert!(loaded.check_pin(3, "my secret"));
+    assert!(!loaded.check_pin(3, "1234"));
+}
@@ -0,0 +1,72 @@
+use project::map::Map;
+use project::Position;
+use project::direction::Direction;
+
+fn map() -> Map {
+    return ". . . .\n. # # .\n. # 0 .\n. . . .\n".parse().ok().unwrap();
+}
+
+#[test]
+fn test_step() {
+    let test_map: Map = ". #\n. .\n".parse().ok().unwrap();
+    assert_eq!(test_map.step(Position(0, 0), Direction::Right), None);
+    assert_eq!(test_map.step(Position(0, 0), Direction::Up), None);
+    assert_eq!(test_map.step(Position(0, 0), Direction::Down), Some(Position(1, 0)));
+    assert_eq!(test_map.step(Position(1, 1), Direction::Right), None);
+    assert_eq!(test_map.is_passable(Position(0, 1)), false);
+    assert_eq!(test_map.is_passable(Position(2, 0)), false);
+}
+
+#[test]
+fn test_shortest_path() {
+    let test_map = map();
+    assert_eq!(test_map.shortest_path(Position(0, 0), Position(2, 2)),
+        Some(vec![Direction::Right, Direction::Right, Direction::Right, Direction::Down, Direction::Down, Direction::Left]));
+    assert_eq!(test_map.shortest_path(Position(0, 0), Position(0, 0)), Some(vec![]));
+    assert_eq!(test_map.shortest_path(Position(0, 0), Position(1, 1)), None);
+    assert_eq!(test_map.shortest_path(Position(0, 0), Position(9, 9)), None);
+    assert_eq!(test_map.shortest_path(Position(9, 9), Position(0, 0)), None);
+}
+
+#[test]
+fn test_find_path() {
+    let test_map = map();
+    let path = test_map.find_path(Position(3, 0), |position| test_map.get_field(position) == '0').unwrap();
+    assert_eq!(path, vec![Direction::Right, Direction::Right, Direction::Up]);
+    assert_eq!(test_map.find_path(Position(3, 0), |position| position.0 > 5), None);
+    assert_eq!(test_map.find_path(Position(0, 4), |_| true), None);
+}
+
+#[test]
+fn test_distances() {
+    let test_map = map();
+    let distances = test_map.distances(Position(0, 0));
+    assert_eq!(distances.get(Position(0, 0)), Some(0));
+    assert_eq!(distances.get(Position(3, 3)), Some(6));
+    assert_eq!(distances.get(Position(2, 2)), Some(6));
+    assert_eq!(distances.get(Position(1, 1)), None);
+    assert_eq!(distances.get(Position(4, 0)), None);
+    assert_eq!(distances.reachable().len(), 13);
+    assert!(test_map.distances(Position(4, 0)).reachable().is_empty());
+    assert!(test_map.reachable_within(Position(0, 9), 5).is_empty());
+}
+
+#[test]
+fn test_reachable_within() {
+    let test_map = map();
+    assert_eq!(test_map.reachable_within(Position(0, 0), 0), vec![Position(0, 0)]);
+    assert_eq!(test_map.reachable_within(Position(0, 0), 2),
+        vec![Position(0, 0), Position(0, 1), Position(0, 2), Position(1, 0), Position(2, 0)]);
+    assert_eq!(test_map.reachable_within(Position(0, 0), 100).len(), 13);
+}
+
+#[test]
+fn test_is_connected() {
+    assert_eq!(map().is_connected(), true);
+    let split: Map = ". # .\n# # .\n. . 0\n".parse().ok().unwrap();
+    assert_eq!(split.is_connected(), false);
+    let walls: Map = "# #\n# #\n".parse().ok().unwrap();
+    assert_eq!(walls.is_connected(), false);
+    assert_eq!(split.unreachable(Position(2, 2), &[Position(0, 2), Position(0, 0)]), Some(Position(0, 0)));
+    assert_eq!(split.unreachable(Position(2, 2), &[Position(0, 2), Position(2, 0)]), None);
+}
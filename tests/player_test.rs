@@ -1,5 +1,6 @@
 use project::player::Player;
 use project::Position;
+use project::direction::Direction;
 
 fn player() -> Player {
     return Player::new("test player".to_string());
@@ -47,7 +48,7 @@ fn test_spawn() {
 #[test]
 fn test_move_player_without_coordinates() {
     let mut test_player = player();
-    test_player.set_move("u");
+    test_player.set_move(Direction::Up);
     assert_eq!(test_player.coordinates, None);
 }
 
@@ -55,15 +56,16 @@ fn test_move_player_without_coordinates() {
 fn test_move_player() {
     let mut test_player = player();
     test_player.spawn(Position(3, 4));
-    test_player.set_move("u");
+    test_player.set_move(Direction::Up);
     assert_eq!(test_player.coordinates, Some(Position(2, 4)));
-    test_player.set_move("d");
+    test_player.set_move(Direction::Down);
     assert_eq!(test_player.coordinates, Some(Position(3, 4)));
-    test_player.set_move("l");
+    test_player.set_move(Direction::Left);
     assert_eq!(test_player.coordinates, Some(Position(3, 3)));
-    test_player.set_move("r");
+    test_player.set_move(Direction::Right);
     assert_eq!(test_player.coordinates, Some(Position(3, 4)));
-    test_player.set_move("fhgfs");
+    test_player.spawn(Position(0, 4));
+    test_player.set_move(Direction::Up);
     assert_eq!(test_player.coordinates, None);
 }
 
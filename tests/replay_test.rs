@@ -0,0 +1,200 @@
+use project::game::Game;
+use project::map::Map;
+use project::rules::GameRules;
+use project::ai::PlayerKind;
+use project::engine::{Action, Phase};
+use project::replay::Replay;
+use project::project_errors::ReplayError;
+use project::direction::Direction;
+use project::items::Item;
+
+/// The first player kills the second one with its second move.
+fn battle_moves() -> Vec<Direction> {
+    return vec![Direction::Right, Direction::Left, Direction::Right];
+}
+
+fn replay_path(name: &str) -> String {
+    return std::env::temp_dir().join(name).to_str().unwrap().to_string();
+}
+
+fn bot_game(seed: u64) -> Game {
+    let names = vec!["first".to_string(), "second".to_string(), "third".to_string()];
+    let mut game = Game::with_rules(names, GameRules::with_size(11), seed);
+    game.set_player_kind(1, PlayerKind::GreedyBot);
+    game.set_player_kind(2, PlayerKind::HunterBot);
+    game.set_player_kind(3, PlayerKind::RandomBot);
+    game.apply(Action::Start).ok().unwrap();
+    return game;
+}
+
+/// Plays the game to the end and returns the number of actions.
+fn play(game: &mut Game) -> usize {
+    let mut actions = 0;
+    loop {
+        let action = match game.phase() {
+            Phase::Roll(_) => Action::Roll,
+            Phase::Move { player, .. } => match game.next_move(player) {
+                Some(direction) => Action::Move(direction),
+                None => panic!("Something wrong!"),
+            },
+            _ => return actions,
+        };
+        if game.apply(action).is_err() {
+            game.apply(Action::Skip).ok().unwrap();
+        }
+        actions += 1;
+    }
+}
+
+fn recorded(name: &str, content: &str, moves: &[Direction]) -> String {
+    let path = replay_path(name);
+    let map: Map = content.parse().ok().unwrap();
+    let mut rules = GameRules::default();
+    rules.dice_sides = 1;
+    let mut game = Game::with_map(vec!["first".to_string(), "second".to_string()], rules, map, 1);
+    game.apply(Action::Start).ok().unwrap();
+    game.record(&path).ok().unwrap();
+    for direction in moves.iter() {
+        game.apply(Action::Roll).ok().unwrap();
+        game.apply(Action::Move(*direction)).ok().unwrap();
+    }
+    let replay = std::fs::read_to_string(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    return replay;
+}
+
+#[test]
+fn test_record_and_replay() {
+    let path = replay_path("labyrinth_test_replay.replay");
+    let mut test_game = bot_game(21);
+    let first_map = test_game.display_map();
+    test_game.record(&path).ok().unwrap();
+    play(&mut test_game);
+    let replay = Replay::load(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+
+    let frames = replay.frames();
+    assert_eq!(frames[0].map, first_map);
+    assert_eq!(frames[0].player, 0);
+    let last = frames.last().unwrap();
+    assert_eq!(last.map, test_game.display_map());
+    assert!(last.description.contains("Portal has been found!"));
+    assert_eq!(last.player, test_game.winner().unwrap());
+    let final_scores = test_game.finish();
+    for (name, score) in last.scores.iter() {
+        assert!(final_scores.contains(&format!("{}\nscore: {}\n", name, score)));
+    }
+}
+
+#[test]
+fn test_record_and_replay_combat() {
+    let path = replay_path("labyrinth_test_replay_combat.replay");
+    let names = vec!["first".to_string(), "second".to_string()];
+    let mut rules = GameRules::with_size(7);
+    rules.combat = true;
+    rules.health = 4;
+    let mut test_game = Game::with_rules(names, rules, 3);
+    test_game.set_player_kind(1, PlayerKind::HunterBot);
+    test_game.set_player_kind(2, PlayerKind::HunterBot);
+    test_game.apply(Action::Start).ok().unwrap();
+    test_game.record(&path).ok().unwrap();
+    play(&mut test_game);
+    let content = std::fs::read_to_string(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert!(content.contains("\nfight "));
+    let replay: Replay = content.parse().ok().unwrap();
+    let last = replay.frames().last().unwrap();
+    assert_eq!(last.map, test_game.display_map());
+    // The battles must be fought with the recorded dice.
+    let fight = content.lines().find(|line| line.starts_with("fight ")).unwrap();
+    let parts: Vec<&str> = fight.split(' ').collect();
+    let tampered = fight.replace(&format!(" {} {}", parts[3], parts[4]), &format!(" {} {}", parts[4], parts[3]));
+    if tampered != fight {
+        assert!(content.replacen(fight, &tampered, 1).parse::<Replay>().is_err());
+    }
+}
+
+#[test]
+fn test_replay_frames() {
+    let replay: Replay = recorded("labyrinth_test_replay_frames.replay", "1 . .\n# # .\n2 . 0\n",
+        &[Direction::Right, Direction::Right, Direction::Right]).parse().ok().unwrap();
+    let frames = replay.frames();
+    assert_eq!(frames.len(), 7);
+    assert_eq!(frames[1].description, "first rolls 1.");
+    assert_eq!(frames[2].description, "first moves right.");
+    assert_eq!(frames[2].map, ". 1 . \n# # . \n2 . 0 \n");
+    assert_eq!(frames[4].player, 2);
+    assert_eq!(frames[6].map, ". . 1 \n# # . \n. 2 0 \n");
+}
+
+#[test]
+fn test_replay_battle() {
+    let content = recorded("labyrinth_test_replay_battle.replay", "1 . . 2\n# # # .\n0 . . .\n", &battle_moves());
+    assert!(content.contains("move 1 right\nbattle 1 2\n"));
+    // Directions may also be written as keys.
+    assert!(content.replace(" right\n", " d\n").replace(" left\n", " a\n").parse::<Replay>().is_ok());
+    let replay: Replay = content.parse().ok().unwrap();
+    let frames = replay.frames();
+    assert_eq!(frames.len(), 7);
+    assert!(frames[6].description.contains("second was killed!"));
+    assert_eq!(frames[6].scores[0], ("first".to_string(), 15));
+    assert_eq!(frames[6].map, ". . 1 . \n# # # . \n0 . . . \n");
+}
+
+#[test]
+fn test_replay_items() {
+    let path = replay_path("labyrinth_test_replay_items.replay");
+    let map: Map = "1 * . .\n# # # .\n2 . . 0\n".parse().ok().unwrap();
+    let mut rules = GameRules::default();
+    rules.dice_sides = 1;
+    let mut game = Game::with_map(vec!["first".to_string(), "second".to_string()], rules, map, 1);
+    game.apply(Action::Start).ok().unwrap();
+    game.record(&path).ok().unwrap();
+    let actions = [Action::Move(Direction::Right), Action::Skip, Action::Use(Item::Compass)];
+    for action in actions.iter() {
+        game.apply(Action::Roll).ok().unwrap();
+        game.apply(action.clone()).ok().unwrap();
+    }
+    let content = std::fs::read_to_string(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert!(content.contains("move 1 right\npicked 1 compass\n"));
+    assert!(content.contains("use 1 compass\n"));
+    let replay: Replay = content.parse().ok().unwrap();
+    let last = replay.frames().last().unwrap();
+    assert_eq!(last.description, "first's compass points right, the portal is 4 steps away.");
+    assert!(content.replace("picked 1 compass\n", "").parse::<Replay>().is_err());
+    assert!(content.replace("use 1 compass\n", "use 1 potion\n").parse::<Replay>().is_err());
+}
+
+#[test]
+fn test_replay_tampered() {
+    let content = recorded("labyrinth_test_replay_tampered.replay", "1 . . 2\n# # # .\n0 . . .\n", &battle_moves());
+    let cases = [
+        content.replace("labyrinth-replay 1", "labyrinth-replay 9"),
+        content.replace("roll 1 1", "roll 1 7"),
+        content.replace("roll 2 1", "roll 1 1"),
+        content.replace("battle 1 2\n", ""),
+        content.replace("move 1 right\n", "move 1 down\n"),
+        content.replace("move 1 right\n", "move 1 north\n"),
+        content.replace("actions\n", ""),
+        content.clone() + "roll 2 1\nroll 2 1\n",
+    ];
+    for case in cases.iter() {
+        match case.parse::<Replay>() {
+            Err(ReplayError::InvalidLine(_, _)) => (),
+            _ => panic!("Something wrong!"),
+        }
+    }
+    match content.replace("map\n", "").parse::<Replay>() {
+        Err(ReplayError::Save(_)) => (),
+        _ => panic!("Something wrong!"),
+    }
+}
+
+#[test]
+fn test_replay_missing_file() {
+    match Replay::load("/nonexistent/game.replay") {
+        Err(ReplayError::Io(_)) => (),
+        _ => panic!("Something wrong!"),
+    }
+}
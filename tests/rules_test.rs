@@ -0,0 +1,140 @@
+use project::rules::GameRules;
+use project::options::Options;
+use project::project_errors::RulesError;
+use project::{BATTLE_SCORE, MAP_SIZE, ATTACK, PLAYER_LIMIT, MIN_PLAYERS};
+
+fn args(arguments: &[&str]) -> Vec<String> {
+    return arguments.iter().map(|argument| argument.to_string()).collect();
+}
+
+#[test]
+fn test_default_rules() {
+    let rules = GameRules::default();
+    assert_eq!(rules.map_width, MAP_SIZE);
+    assert_eq!(rules.battle_score, BATTLE_SCORE);
+    assert!(rules.validate().is_ok());
+}
+
+#[test]
+fn test_rules_from_str() {
+    let rules: GameRules = "# custom rules\nmap_width = 15\n map_height=15 \n\ndice_sides = 4\ngenerator = braided-prim\nspawn = fair\ncombat = true\nhealth = 20\nitems = 5\nlocked_portal = true\n"
+        .parse().ok().unwrap();
+    assert_eq!(rules.map_width, 15);
+    assert_eq!(rules.map_height, 15);
+    assert_eq!(rules.dice_sides, 4);
+    assert_eq!(rules.generator, "braided-prim");
+    assert_eq!(rules.spawn, "fair");
+    assert_eq!(rules.combat, true);
+    assert_eq!(rules.health, 20);
+    assert_eq!(rules.attack, ATTACK);
+    assert_eq!(rules.items, 5);
+    assert_eq!(rules.locked_portal, true);
+    assert_eq!(rules.battle_score, BATTLE_SCORE);
+}
+
+#[test]
+fn test_rules_from_str_errors() {
+    match "map_width = 15\nspeed = 2\n".parse::<GameRules>() {
+        Err(RulesError::InvalidLine(line, _)) => assert_eq!(line, 2),
+        _ => panic!("Something wrong!"),
+    }
+    assert!("dice_sides = many\n".parse::<GameRules>().is_err());
+    assert!("generator = noise\n".parse::<GameRules>().is_err());
+    assert!("spawn = anywhere\n".parse::<GameRules>().is_err());
+    assert!("combat = maybe\n".parse::<GameRules>().is_err());
+    assert!("dice_sides\n".parse::<GameRules>().is_err());
+}
+
+#[test]
+fn test_rules_display() {
+    let mut rules = GameRules::default();
+    rules.portal_score = 99;
+    assert_eq!(rules.display().parse::<GameRules>().ok().unwrap(), rules);
+}
+
+#[test]
+fn test_rules_validate() {
+    let mut rules = GameRules::default();
+    rules.map_width = 2;
+    rules.map_height = 2;
+    assert!(rules.validate().is_err());
+    rules = GameRules::default();
+    rules.dice_sides = 0;
+    assert!(rules.validate().is_err());
+    rules = GameRules::default();
+    rules.min_players = 4;
+    rules.max_players = 3;
+    assert!(rules.validate().is_err());
+    rules = GameRules::default();
+    rules.min_players = 1;
+    assert!(rules.validate().is_err());
+    rules.min_players = MIN_PLAYERS;
+    assert!(rules.validate().is_ok());
+    rules = GameRules::default();
+    rules.max_players = 10;
+    assert!(rules.validate().is_ok());
+    rules.max_players = PLAYER_LIMIT + 1;
+    assert!(rules.validate().err().unwrap().to_string().contains("at most 34 players"));
+    // A 3x3 maze has 7 empty fields.
+    rules = GameRules::with_size(3);
+    rules.max_players = 6;
+    assert!(rules.validate().is_ok());
+    rules.max_players = PLAYER_LIMIT;
+    assert!(rules.validate().is_err());
+    rules = GameRules::default();
+    rules.health = 0;
+    assert!(rules.validate().is_ok());
+    rules.combat = true;
+    assert!(rules.validate().is_err());
+}
+
+#[test]
+fn test_options_flags() {
+    let options = Options::parse(&args(&["--battle-score", "20", "--map", "maps/example.map", "--min-players", "3"])).ok().unwrap();
+    assert_eq!(options.rules.battle_score, 20);
+    assert_eq!(options.rules.min_players, 3);
+    assert_eq!(options.map_file, Some("maps/example.map".to_string()));
+}
+
+#[test]
+fn test_options_flags_override_rules_file() {
+    let path = std::env::temp_dir().join("labyrinth_test_rules.conf").to_str().unwrap().to_string();
+    std::fs::write(&path, "dice_sides = 4\nportal_score = 10\n").ok();
+    let options = Options::parse(&args(&["--dice-sides", "8", "--rules", &path])).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(options.rules.dice_sides, 8);
+    assert_eq!(options.rules.portal_score, 10);
+}
+
+#[test]
+fn test_options_errors() {
+    assert!(Options::parse(&args(&["--speed", "2"])).is_err());
+    assert!(Options::parse(&args(&["--battle-score"])).is_err());
+    assert!(Options::parse(&args(&["--battle-score", "lots"])).is_err());
+    assert!(Options::parse(&args(&["--rules", "/nonexistent/rules.conf"])).is_err());
+}
+
+#[test]
+fn test_options_replay() {
+    let options = Options::parse(&args(&["--record", "game.replay"])).ok().unwrap();
+    assert_eq!(options.record_file, Some("game.replay".to_string()));
+    assert_eq!(options.replay_file, None);
+    let options = Options::parse(&args(&["--replay", "game.replay"])).ok().unwrap();
+    assert_eq!(options.replay_file, Some("game.replay".to_string()));
+}
+
+#[test]
+fn test_options_network() {
+    let options = Options::parse(&args(&["--host", "0.0.0.0:7878", "--view-radius", "3"])).ok().unwrap();
+    assert_eq!(options.host, Some("0.0.0.0:7878".to_string()));
+    assert_eq!(options.rules.view_radius, 3);
+    let options = Options::parse(&args(&["--join", "127.0.0.1:7878"])).ok().unwrap();
+    assert_eq!(options.join, Some("127.0.0.1:7878".to_string()));
+}
+
+#[test]
+fn test_options_keys() {
+    let options = Options::parse(&args(&["--keys", "keys.conf", "--join", "127.0.0.1:7878"])).ok().unwrap();
+    assert_eq!(options.keys_file, Some("keys.conf".to_string()));
+    assert_eq!(Options::parse(&args(&[])).ok().unwrap().keys_file, None);
+}
@@ -0,0 +1,119 @@
+use project::map::Map;
+use project::game::Game;
+use project::rules::GameRules;
+use project::spawns::{self, Spawns, SPAWN_STRATEGIES};
+use project::generators;
+use project::project_errors::GameError;
+use project::Position;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+fn maze(size: usize, seed: u64) -> Map {
+    let generator = generators::by_name("backtracker").unwrap();
+    return Map::with_generator(size, size, &generator, &mut StdRng::seed_from_u64(seed));
+}
+
+fn place(strategy: &str, map: &Map, players: &[Option<Position>], seed: u64) -> Spawns {
+    let strategy = spawns::by_name(strategy).unwrap();
+    return strategy.place(map, players, map.portal(), &mut StdRng::seed_from_u64(seed)).ok().unwrap();
+}
+
+/// Checks that all the fields are empty and different.
+fn check_fields(map: &Map, spawns: &Spawns) {
+    let mut fields = spawns.players.clone();
+    fields.push(spawns.portal);
+    for (i, field) in fields.iter().enumerate() {
+        assert!(map.empty(field.0, field.1) || map.portal() == Some(*field));
+        assert!(!fields[i + 1..].contains(field));
+    }
+}
+
+#[test]
+fn test_strategies_place_everything() {
+    for name in SPAWN_STRATEGIES.iter() {
+        for seed in 0..5 {
+            let map = maze(11, seed);
+            let spawns = place(name, &map, &[None; 4], seed);
+            assert_eq!(spawns.players.len(), 4, "{} {}", name, seed);
+            check_fields(&map, &spawns);
+        }
+        assert!(spawns::by_name(name).is_some());
+    }
+    assert!(spawns::by_name("anywhere").is_none());
+}
+
+#[test]
+fn test_fixed_fields_are_kept() {
+    let map: Map = "1 . . . .\n. # # # .\n. . 0 . .\n. # # # .\n. . . . .\n".parse().ok().unwrap();
+    for name in SPAWN_STRATEGIES.iter() {
+        let spawns = place(name, &map, &[map.spawn_point(1), None, None], 3);
+        assert_eq!(spawns.players[0], Position(0, 0));
+        assert_eq!(spawns.portal, Position(2, 2));
+        check_fields(&map, &spawns);
+    }
+}
+
+#[test]
+fn test_fair_spawns() {
+    for seed in 0..5 {
+        let map = maze(15, seed);
+        let spawns = place("fair", &map, &[None; 4], seed);
+        let distances = map.distances(spawns.portal);
+        let steps: Vec<Option<usize>> = spawns.players.iter().map(|player| distances.get(*player)).collect();
+        assert!(steps.iter().all(|distance| *distance == steps[0] && distance.unwrap() > 0), "{:?}", steps);
+    }
+}
+
+#[test]
+fn test_spread_spawns() {
+    let map: Map = ". . . . . . .\n. . . . . . .\n. . . . . . .\n".parse().ok().unwrap();
+    let spawns = place("spread", &map, &[Some(Position(0, 0)), None], 1);
+    assert_eq!(spawns.players[1], Position(2, 6));
+    let distances = map.distances(spawns.players[0]);
+    assert_eq!(distances.get(spawns.portal), map.distances(spawns.players[1]).get(spawns.portal));
+}
+
+#[test]
+fn test_corner_spawns() {
+    let map: Map = "# . . . .\n. . . . .\n. . . . #\n".parse().ok().unwrap();
+    let spawns = place("corners", &map, &[None, None, None, None], 1);
+    assert_eq!(spawns.players, vec![Position(0, 1), Position(1, 4), Position(0, 4), Position(2, 0)]);
+}
+
+#[test]
+fn test_closed_off_fields() {
+    // The bottom row can't be reached from the portal.
+    let map: Map = "1 0 . .\n# # # #\n. . . .\n".parse().ok().unwrap();
+    for name in SPAWN_STRATEGIES.iter() {
+        for seed in 0..5 {
+            let spawns = place(name, &map, &[map.spawn_point(1), None], seed);
+            assert_eq!(spawns.players[1].0, 0, "{} {}", name, seed);
+        }
+    }
+}
+
+#[test]
+fn test_no_room() {
+    let map: Map = "1 0 .\n# # #\n. . .\n".parse().ok().unwrap();
+    for name in SPAWN_STRATEGIES.iter() {
+        let strategy = spawns::by_name(name).unwrap();
+        let spawns = strategy.place(&map, &[map.spawn_point(1), None, None], map.portal(), &mut StdRng::seed_from_u64(1));
+        assert_eq!(spawns, Err(GameError::NoRoom), "{}", name);
+    }
+    let names = vec!["first".to_string(), "second".to_string(), "third".to_string()];
+    let mut test_game = Game::with_map(names, GameRules::default(), map, 1);
+    assert_eq!(test_game.spawn_all(), Err(GameError::NoRoom));
+}
+
+#[test]
+fn test_game_uses_spawn_rule() {
+    let names = vec!["first".to_string(), "second".to_string()];
+    let mut rules = GameRules::with_size(11);
+    rules.spawn = "corners".to_string();
+    let mut test_game = Game::with_rules(names, rules, 4);
+    test_game.spawn_all().ok().unwrap();
+    let first = test_game.get_player_position(1).unwrap();
+    let second = test_game.get_player_position(2).unwrap();
+    assert!(first.0 + first.1 <= 2, "{:?}", first);
+    assert!(second.0 + second.1 >= 18, "{:?}", second);
+}
@@ -0,0 +1,139 @@
+use project::game::Game;
+use project::rules::GameRules;
+use project::engine::{Action, Event};
+use project::project_errors::GameError;
+use project::direction::Direction;
+use project::Position;
+
+mod common;
+use common::{rules, started_game, play};
+
+fn team_rules() -> GameRules {
+    let mut rules = rules();
+    rules.teams = 2;
+    return rules;
+}
+
+/// A started team game: the first and the third player are in team 1, the second one in team 2.
+fn team_game(map: &str, rules: GameRules) -> Game {
+    return started_game(map, rules, 3);
+}
+
+#[test]
+fn test_teams_in_turns() {
+    let test_game = team_game("1 3 . 0\n# # # .\n2 . . .\n", team_rules());
+    assert_eq!(test_game.get_player_team(1), 1);
+    assert_eq!(test_game.get_player_team(2), 2);
+    assert_eq!(test_game.get_player_team(3), 1);
+    assert!(test_game.teammates(1, 3));
+    assert!(!test_game.teammates(1, 2));
+    assert!(!test_game.teammates(1, 1));
+}
+
+#[test]
+fn test_swap_teammates() {
+    let mut test_game = team_game("1 3 . 0\n# # # .\n2 . . .\n", team_rules());
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events[0], Event::Moved { player: 1, to: Position(0, 1) });
+    assert_eq!(events[1], Event::Swapped { player: 1, with: 3 });
+    assert_eq!(test_game.describe(&events[1]).unwrap(), "first swaps places with third.");
+    assert_eq!(test_game.display_map(), "3 1 . 0 \n# # # . \n2 . . . \n");
+    assert!(test_game.in_game(3));
+}
+
+#[test]
+fn test_swap_on_tiles() {
+    // The third player can't step back against the arrow the first one stands on.
+    let mut test_game = team_game("1 > 3 .\n# # # .\n2 . . 0\n", team_rules());
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    assert_eq!(play(&mut test_game, Action::Move(Direction::Right)), Err(GameError::OneWay));
+    assert_eq!(test_game.display_map(), ". 1 3 . \n# # # . \n2 . . 0 \n");
+
+    // A teleport pad takes the player who lands on it to its pair.
+    let mut test_game = team_game("1 3 @ 0\n@ # # .\n2 . . .\n", team_rules());
+    play(&mut test_game, Action::Move(Direction::Down)).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events[..3], [Event::Moved { player: 3, to: Position(0, 2) }, Event::Swapped { player: 3, with: 1 },
+        Event::Teleported { player: 3, to: Position(1, 0) }]);
+    assert_eq!(test_game.display_map(), ". 1 @ 0 \n3 # # . \n2 . . . \n");
+}
+
+#[test]
+fn test_blocked_by_teammate() {
+    let mut rules = team_rules();
+    rules.swap_teammates = false;
+    let mut test_game = team_game("1 3 . 0\n# # # .\n2 . . .\n", rules);
+    assert_eq!(play(&mut test_game, Action::Move(Direction::Right)), Err(GameError::Teammate));
+    assert_eq!(test_game.get_player_position(1), Some(Position(0, 0)));
+}
+
+#[test]
+fn test_team_wins_and_scores() {
+    let mut test_game = team_game("1 2 . 0\n# # # .\n3 . . .\n", team_rules());
+    // The first player kills the second one, who is in the other team.
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events[0], Event::Battle { winner: 1, loser: 2, points: 15 });
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    play(&mut test_game, Action::Skip).ok().unwrap();
+    let events = play(&mut test_game, Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(test_game.describe(&events[0]).unwrap(), "Portal has been found! first wins 50 points! Team 1 wins the game!");
+    assert_eq!(test_game.winning_team(), Some(1));
+    assert_eq!(test_game.team_scores(), vec![(1, 65), (2, 0)]);
+    assert!(test_game.finish().starts_with("=== team 1 ===\nscore: 65\nfirst, third\n\n=== team 2 ===\nscore: 0\nsecond\n\n--- 1 ---\nfirst\n"));
+}
+
+#[test]
+fn test_more_teams_than_players() {
+    let mut rules = team_rules();
+    rules.teams = 4;
+    let test_game = started_game("1 2 . 0\n# # # .\n. . . .\n", rules, 2);
+    assert_eq!(test_game.get_player_team(2), 2);
+    // Teams 3 and 4 have nobody in them.
+    assert_eq!(test_game.team_scores(), vec![(1, 0), (2, 0)]);
+    assert!(!test_game.finish().contains("=== team 3 ==="));
+}
+
+#[test]
+fn test_no_teams() {
+    let test_game = team_game("1 3 . 0\n# # # .\n2 . . .\n", GameRules::default());
+    assert_eq!(test_game.get_player_team(1), 0);
+    assert!(!test_game.teammates(1, 3));
+    assert!(test_game.finish().starts_with("--- 1 ---\n"));
+}
+
+#[test]
+fn test_team_rules() {
+    let mut rules = team_rules();
+    assert!(rules.validate().is_ok());
+    rules.teams = 1;
+    assert!(rules.validate().is_err());
+    rules.teams = 5;
+    assert!(rules.validate().is_err());
+}
+
+#[test]
+fn test_save_and_load_teams() {
+    let path = std::env::temp_dir().join("labyrinth_test_teams.save");
+    let path = path.to_str().unwrap();
+    let mut test_game = team_game("1 3 . 0\n# # # .\n2 . . .\n", team_rules());
+    test_game.set_player_team(3, 2);
+    test_game.save(path).ok().unwrap();
+    let loaded = Game::load(path).ok().unwrap();
+    std::fs::remove_file(path).ok();
+    assert_eq!(loaded.get_player_team(1), 1);
+    assert_eq!(loaded.get_player_team(3), 2);
+    assert!(loaded.teammates(2, 3));
+    // Every player needs a team of the game.
+    test_game.save(path).ok().unwrap();
+    let content = std::fs::read_to_string(path).ok().unwrap();
+    for tampered in [content.replace("team 3 2", "team 3 0"), content.replace("team 3 2", "team 3 3"),
+        content.replace("team 3 2\n", "")].iter() {
+        std::fs::write(path, tampered).ok().unwrap();
+        assert!(Game::load(path).is_err());
+    }
+    std::fs::remove_file(path).ok();
+}
@@ -0,0 +1,182 @@
+use project::game::Game;
+use project::map::Map;
+use project::rules::GameRules;
+use project::engine::{Action, Event, Phase};
+use project::project_errors::GameError;
+use project::tiles::Tile;
+use project::direction::Direction;
+use project::Position;
+
+mod common;
+use common::started_game;
+
+fn tiles_game(map: &str) -> Game {
+    return started_game(map, GameRules::default(), 2);
+}
+
+/// Rolls the dice for the player on turn and gives them a number of moves.
+fn roll(game: &mut Game, moves: usize) {
+    game.apply(Action::Roll).ok().unwrap();
+    game.set_moves_left(moves);
+}
+
+#[test]
+fn test_tile_symbols() {
+    for tile in Tile::ALL.iter() {
+        assert_eq!(Tile::from_symbol(tile.symbol()), Some(*tile));
+    }
+    assert_eq!(Tile::from_symbol('1'), None);
+    assert!(!Tile::Wall.is_passable());
+    assert!(Tile::Door.is_passable());
+    assert!(Tile::Arrow(Direction::Up).can_enter(Direction::Up));
+    assert!(!Tile::Arrow(Direction::Up).can_enter(Direction::Down));
+    assert!(Tile::BreakableWall.blocks_sight());
+}
+
+#[test]
+fn test_water() {
+    let mut test_game = tiles_game("1 ~ . 0\n# # # .\n2 . . .\n");
+    roll(&mut test_game, 1);
+    assert_eq!(test_game.apply(Action::Move(Direction::Right)), Err(GameError::NotEnoughMoves));
+    test_game.set_moves_left(3);
+    let events = test_game.apply(Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events, vec![Event::Moved { player: 1, to: Position(0, 1) }]);
+    assert_eq!(test_game.moves_left(), 1);
+    test_game.apply(Action::Move(Direction::Right)).ok().unwrap();
+    // The water is still there after the player has left.
+    assert_eq!(test_game.display_map(), ". ~ 1 0 \n# # # . \n2 . . . \n");
+}
+
+#[test]
+fn test_mud() {
+    let mut test_game = tiles_game("1 , . 0\n# # # .\n2 . . .\n");
+    roll(&mut test_game, 3);
+    let events = test_game.apply(Action::Move(Direction::Right)).ok().unwrap();
+    assert!(events.contains(&Event::Stuck(1)));
+    assert_eq!(test_game.phase(), Phase::Roll(2));
+}
+
+#[test]
+fn test_arrows() {
+    let mut test_game = tiles_game("1 < . 0\n. # # .\n2 . . .\n");
+    roll(&mut test_game, 2);
+    assert_eq!(test_game.apply(Action::Move(Direction::Right)), Err(GameError::OneWay));
+    assert_eq!(test_game.apply(Action::Path(vec![Direction::Right, Direction::Right])), Err(GameError::OneWay));
+
+    let mut test_game = tiles_game("1 > . 0\n# # # .\n2 . . .\n");
+    roll(&mut test_game, 2);
+    test_game.apply(Action::Path(vec![Direction::Right, Direction::Right])).ok().unwrap();
+    assert_eq!(test_game.get_player_position(1), Some(Position(0, 2)));
+}
+
+#[test]
+fn test_teleport() {
+    let mut test_game = tiles_game("1 @ # 0\n# # # .\n2 . @ .\n");
+    assert_eq!(test_game.map().teleport_pair(Position(0, 1)), Some(Position(2, 2)));
+    assert_eq!(test_game.path_to(1, Position(0, 3)), Ok(vec![Direction::Right, Direction::Right, Direction::Up, Direction::Up]));
+    roll(&mut test_game, 2);
+    let events = test_game.apply(Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events[0], Event::Moved { player: 1, to: Position(0, 1) });
+    assert_eq!(events[1], Event::Teleported { player: 1, to: Position(2, 2) });
+    assert_eq!(test_game.display_map(), ". @ # 0 \n# # # . \n2 . 1 . \n");
+}
+
+#[test]
+fn test_battle_on_teleport() {
+    let mut test_game = tiles_game("1 . @ .\n# # # .\n@ 2 . 0\n");
+    roll(&mut test_game, 1);
+    test_game.apply(Action::Move(Direction::Right)).ok().unwrap();
+    roll(&mut test_game, 1);
+    test_game.apply(Action::Move(Direction::Left)).ok().unwrap();
+    assert_eq!(test_game.get_player_position(2), Some(Position(0, 2)));
+    // The winner takes the field of the defender like any other move, the pad takes it to its pair.
+    roll(&mut test_game, 1);
+    let events = test_game.apply(Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events[..3], [Event::Battle { winner: 1, loser: 2, points: 15 }, Event::Moved { player: 1, to: Position(0, 2) },
+        Event::Teleported { player: 1, to: Position(2, 0) }]);
+    assert_eq!(test_game.display_map(), ". . @ . \n# # # . \n1 . . 0 \n");
+}
+
+#[test]
+fn test_teleport_pairs_follow_tiles() {
+    let mut map: Map = "1 @ . @\n. . . 0\n".parse().ok().unwrap();
+    assert_eq!(map.teleport_pair(Position(0, 3)), Some(Position(0, 1)));
+    assert_eq!(map.teleport_pair(Position(0, 2)), None);
+    map.set_tile(Position(1, 0), Tile::Teleport);
+    assert_eq!(map.teleport_pair(Position(1, 0)), None);
+    map.set_tile(Position(0, 1), Tile::Floor);
+    assert_eq!(map.teleport_pair(Position(0, 3)), Some(Position(1, 0)));
+    assert_eq!(map.teleport_pair(Position(0, 1)), None);
+}
+
+#[test]
+fn test_taken_teleport() {
+    let mut test_game = tiles_game("1 . @ # 0\n# # 2 # .\n# # # @ .\n");
+    roll(&mut test_game, 1);
+    test_game.apply(Action::Skip).ok().unwrap();
+    roll(&mut test_game, 1);
+    test_game.apply(Action::Move(Direction::Up)).ok().unwrap();
+    assert_eq!(test_game.get_player_position(2), Some(Position(2, 3)));
+    roll(&mut test_game, 3);
+    // The pair is taken, the path stops on the pad.
+    let events = test_game.apply(Action::Path(vec![Direction::Right, Direction::Right, Direction::Right])).ok().unwrap();
+    assert_eq!(events, vec![Event::Moved { player: 1, to: Position(0, 1) }, Event::Moved { player: 1, to: Position(0, 2) }]);
+    assert_eq!(test_game.moves_left(), 1);
+}
+
+#[test]
+fn test_breakable_wall() {
+    let mut test_game = tiles_game("1 = . 0\n# # # .\n2 . . .\n");
+    roll(&mut test_game, 2);
+    assert_eq!(test_game.apply(Action::Path(vec![Direction::Right, Direction::Right])), Err(GameError::InvalidCoordinates));
+    let events = test_game.apply(Action::Move(Direction::Right)).ok().unwrap();
+    assert_eq!(events, vec![Event::WallBroken { player: 1, at: Position(0, 1) }]);
+    assert_eq!(test_game.get_player_position(1), Some(Position(0, 0)));
+    assert_eq!(test_game.map().tile(Position(0, 1)), Tile::Floor);
+    assert_eq!(test_game.moves_left(), 1);
+    test_game.apply(Action::Move(Direction::Right)).ok().unwrap();
+}
+
+#[test]
+fn test_door() {
+    let mut test_game = tiles_game("1 + 0\n# # .\n2 . .\n");
+    roll(&mut test_game, 2);
+    assert_eq!(test_game.apply(Action::Move(Direction::Right)), Err(GameError::DoorLocked));
+
+    let mut test_game = tiles_game("1 & + 0\n# # # .\n2 . . .\n");
+    roll(&mut test_game, 3);
+    test_game.apply(Action::Move(Direction::Right)).ok().unwrap();
+    test_game.apply(Action::Move(Direction::Right)).ok().unwrap();
+    let events = test_game.apply(Action::Move(Direction::Right)).ok().unwrap();
+    assert!(events.contains(&Event::GameFinished(1)));
+}
+
+#[test]
+fn test_path_through_door() {
+    let mut test_game = tiles_game("1 . + 0\n# # # .\n2 . . .\n");
+    roll(&mut test_game, 3);
+    assert_eq!(test_game.apply(Action::Path(vec![Direction::Right, Direction::Right, Direction::Right])), Err(GameError::DoorLocked));
+    assert_eq!(test_game.get_player_position(1), Some(Position(0, 0)));
+
+    // The key on the way opens the door.
+    let mut test_game = tiles_game("1 & + 0\n# # # .\n2 . . .\n");
+    roll(&mut test_game, 3);
+    let events = test_game.apply(Action::Path(vec![Direction::Right, Direction::Right, Direction::Right])).ok().unwrap();
+    assert!(events.contains(&Event::GameFinished(1)));
+}
+
+#[test]
+fn test_save_and_load_tiles() {
+    let path = std::env::temp_dir().join("labyrinth_test_tiles.save");
+    let path = path.to_str().unwrap();
+    let mut test_game = tiles_game("1 ~ , 0\n# # # .\n2 . . .\n");
+    roll(&mut test_game, 3);
+    test_game.apply(Action::Move(Direction::Right)).ok().unwrap();
+    test_game.save(path).ok().unwrap();
+    let content = std::fs::read_to_string(path).ok().unwrap();
+    let loaded = Game::load(path).ok().unwrap();
+    std::fs::remove_file(path).ok();
+    assert!(content.contains("\nterrain\n. ~ , 0 \n"));
+    assert_eq!(loaded.map().tile(Position(0, 1)), Tile::Water);
+    assert_eq!(loaded.display_map(), test_game.display_map());
+}
@@ -0,0 +1,90 @@
+use project::game::Game;
+use project::map::Map;
+use project::rules::GameRules;
+use project::visibility::visible_fields;
+use project::Position;
+use project::direction::Direction;
+
+fn fog_game(map: &str, radius: usize) -> Game {
+    let map: Map = map.parse().ok().unwrap();
+    let mut rules = GameRules::default();
+    rules.view_radius = radius;
+    let mut game = Game::with_map(vec!["first".to_string(), "second".to_string()], rules, map, 3);
+    game.spawn_all().ok().unwrap();
+    return game;
+}
+
+#[test]
+fn test_visible_fields_radius() {
+    let map: Map = ". . . . .\n. . . . .\n. . . . .\n. . . . .\n. . . . .\n".parse().ok().unwrap();
+    let visible = visible_fields(&map, Position(2, 2), 1);
+    assert_eq!(visible.len(), 5);
+    assert!(visible.contains(&Position(2, 2)));
+    assert!(visible.contains(&Position(1, 2)));
+    assert!(!visible.contains(&Position(1, 1)));
+    assert_eq!(visible_fields(&map, Position(0, 0), 2).len(), 6);
+    assert_eq!(visible_fields(&map, Position(2, 2), 10).len(), 25);
+}
+
+#[test]
+fn test_visible_fields_walls() {
+    let map: Map = ". # . .\n. # . .\n. . . .\n".parse().ok().unwrap();
+    let visible = visible_fields(&map, Position(0, 0), 3);
+    assert!(visible.contains(&Position(0, 1)));
+    assert!(!visible.contains(&Position(0, 2)));
+    assert!(!visible.contains(&Position(0, 3)));
+    assert!(visible.contains(&Position(2, 0)));
+    assert!(!visible.contains(&Position(2, 2)));
+}
+
+#[test]
+fn test_fog_disabled() {
+    let test_game = fog_game("1 . #\n. . .\n# 0 2\n", 0);
+    assert_eq!(test_game.display_map_for(1), test_game.display_map());
+}
+
+#[test]
+fn test_fog_hides_fields() {
+    let test_game = fog_game("1 . # . .\n. . # . .\n. . # . 0\n. . . . 2\n", 1);
+    assert_eq!(test_game.display_map_for(1), "1 . ? ? ? \n. ? ? ? ? \n? ? ? ? ? \n? ? ? ? ? \n");
+    assert_eq!(test_game.display_map_for(2), "? ? ? ? ? \n? ? ? ? ? \n? ? ? ? 0 \n? ? ? . 2 \n");
+}
+
+#[test]
+fn test_fog_remembers_explored_fields() {
+    let mut test_game = fog_game("1 . . . 0\n# # # . #\n2 . . . .\n", 2);
+    assert_eq!(test_game.display_map_for(1), "1 . . ? ? \n# # ? ? ? \n? ? ? ? ? \n");
+    test_game.make_move(1, Direction::Right).ok();
+    test_game.make_move(1, Direction::Right).ok();
+    assert_eq!(test_game.display_map_for(1), ". . 1 . 0 \n# # # . ? \n? ? ? ? ? \n");
+    test_game.make_move(2, Direction::Right).ok();
+    test_game.make_move(2, Direction::Right).ok();
+    test_game.make_move(2, Direction::Right).ok();
+    test_game.make_move(2, Direction::Up).ok();
+    assert_eq!(test_game.display_map_for(1), ". . 1 . 0 \n# # # 2 ? \n? ? ? ? ? \n");
+    // The remembered fields show the walls and the portal, but not the players.
+    test_game.make_move(1, Direction::Left).ok();
+    assert_eq!(test_game.display_map_for(1), ". 1 . . 0 \n# # # . ? \n? ? ? ? ? \n");
+}
+
+#[test]
+fn test_fog_dead_player_remembers() {
+    let mut test_game = fog_game("1 2 .\n. . .\n. . 0\n", 1);
+    test_game.battle(1, Direction::Right).ok().unwrap();
+    assert_eq!(test_game.display_map_for(2), ". . . \n? . ? \n? ? ? \n");
+}
+
+#[test]
+fn test_save_and_load_explored_fields() {
+    let path = std::env::temp_dir().join("labyrinth_test_fog.save").to_str().unwrap().to_string();
+    let mut test_game = fog_game("1 . . . 0\n# # # . #\n2 . . . .\n", 2);
+    test_game.make_move(1, Direction::Right).ok();
+    test_game.make_move(1, Direction::Right).ok();
+    test_game.make_move(1, Direction::Left).ok();
+    test_game.save(&path).ok().unwrap();
+    let loaded = Game::load(&path).ok().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded.rules().view_radius, 2);
+    assert_eq!(loaded.display_map_for(1), test_game.display_map_for(1));
+    assert_eq!(loaded.display_map_for(2), test_game.display_map_for(2));
+}